@@ -22,15 +22,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     match client.get_status().await {
         Ok(info) => {
             println!("\n📊 Current WARP Status:");
-            println!("  Status: {}", info.status);
-            if let Some(mode) = &info.mode {
-                println!("  Mode: {}", mode);
+            for line in info.to_string().lines() {
+                println!("  {}", line);
             }
-            if let Some(account_type) = &info.account_type {
-                println!("  Account Type: {}", account_type);
-            }
-            println!("  WARP Enabled: {}", info.warp_enabled);
-            println!("  Gateway Enabled: {}", info.gateway_enabled);
         }
         Err(e) => {
             println!("❌ Failed to get status: {}", e);
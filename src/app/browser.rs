@@ -0,0 +1,46 @@
+use crate::warp::RegistrationInfo;
+
+/// Opened when the organization isn't known, e.g. for consumer registrations
+pub const DOCS_URL: &str = "https://developers.cloudflare.com/warp-client/";
+
+/// Page to open for the settings of this device
+///
+/// Zero Trust registrations name their organization, whose team domain is
+/// `<organization>.cloudflareaccess.com`. Anything else, including an organization that
+/// isn't a valid subdomain, gets the WARP client docs.
+pub fn settings_url(registration: Option<&RegistrationInfo>) -> String {
+    let organization = registration
+        .and_then(|info| info.organization.as_deref())
+        .map(str::trim)
+        .filter(|name| {
+            !name.is_empty()
+                && !name.starts_with('-')
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    match organization {
+        Some(name) => format!("https://{}.cloudflareaccess.com/", name.to_lowercase()),
+        None => DOCS_URL.to_string(),
+    }
+}
+
+/// Open `url` in the default browser without waiting for it
+///
+/// Returns why it failed if there's no graphical session or no browser could be started.
+/// Without a graphical session a terminal browser could take over the TUI's terminal, so
+/// nothing is started then.
+#[cfg(feature = "browser")]
+pub fn open(url: &str) -> Result<(), String> {
+    if cfg!(all(unix, not(target_os = "macos")))
+        && std::env::var_os("DISPLAY").is_none()
+        && std::env::var_os("WAYLAND_DISPLAY").is_none()
+    {
+        return Err("no graphical session".to_string());
+    }
+    open::that_detached(url).map_err(|e| e.to_string())
+}
+
+/// Without the `browser` feature there's no browser to open
+#[cfg(not(feature = "browser"))]
+pub fn open(_url: &str) -> Result<(), String> {
+    Err("built without browser support".to_string())
+}
@@ -0,0 +1,45 @@
+use ratatui::layout::{Constraint, Flex, Layout, Position, Rect};
+
+/// Width of a button, including its border
+const BUTTON_WIDTH: u16 = 14;
+
+/// Height of a button, including its border
+pub const BUTTON_HEIGHT: u16 = 3;
+
+/// Clickable buttons shown below the status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    Connect,
+    Disconnect,
+    Mode,
+}
+
+impl Button {
+    pub const ALL: [Button; 3] = [Button::Connect, Button::Disconnect, Button::Mode];
+
+    /// Text shown on the button
+    pub fn label(self) -> &'static str {
+        match self {
+            Button::Connect => "Connect",
+            Button::Disconnect => "Disconnect",
+            Button::Mode => "Mode",
+        }
+    }
+}
+
+/// Lay the buttons out in a row, left-aligned within `area`
+pub fn layout_buttons(area: Rect) -> Vec<(Button, Rect)> {
+    let areas = Layout::horizontal([Constraint::Length(BUTTON_WIDTH); Button::ALL.len()])
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(area);
+    Button::ALL.into_iter().zip(areas.iter().copied()).collect()
+}
+
+/// The button containing the cell at (`column`, `row`), if any
+pub fn button_at(buttons: &[(Button, Rect)], column: u16, row: u16) -> Option<Button> {
+    buttons
+        .iter()
+        .find(|(_, area)| area.contains(Position::new(column, row)))
+        .map(|(button, _)| *button)
+}
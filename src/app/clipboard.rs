@@ -0,0 +1,27 @@
+/// Copy `text` to the system clipboard
+///
+/// The clipboard stays open once used: on Linux the copied text is served by this
+/// process, so it would otherwise be gone right away. Returns why it failed, e.g. when
+/// there's no clipboard because no graphical session is running.
+#[cfg(feature = "clipboard")]
+pub fn copy(text: &str) -> Result<(), String> {
+    use std::sync::Mutex;
+
+    static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+    let mut clipboard = CLIPBOARD.lock().map_err(|e| e.to_string())?;
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+    }
+    clipboard
+        .as_mut()
+        .expect("clipboard was just opened")
+        .set_text(text)
+        .map_err(|e| e.to_string())
+}
+
+/// Without the `clipboard` feature there's no clipboard to copy to
+#[cfg(not(feature = "clipboard"))]
+pub fn copy(_text: &str) -> Result<(), String> {
+    Err("built without clipboard support".to_string())
+}
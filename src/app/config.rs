@@ -0,0 +1,88 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::app::paths::config_dir;
+
+/// Behavior settings read from `config.toml`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Ask whether to disconnect when quitting while WARP is connected
+    pub confirm_quit_when_connected: bool,
+    /// Wrap around from the last entry of a selection list to the first and back
+    pub wrap_selection: bool,
+    /// Seconds a connect or disconnect may stay in progress before it's reported as stuck
+    pub stale_transition_secs: u64,
+    /// Named sets of settings offered in the profile picker, from `[[profiles]]` tables
+    pub profiles: Vec<Profile>,
+    /// Policy passed to `warp-cli override` when applying an override from the TUI
+    pub override_policy: Option<String>,
+    /// Seconds a warp-cli command may run before it's killed, the client's own timeout
+    /// if `None`
+    pub command_timeout_secs: Option<u64>,
+    /// Actions applied by the function keys, from the `[favorites]` table, e.g.
+    /// `F1 = "warp+doh"`
+    pub favorites: BTreeMap<String, String>,
+}
+
+/// A named set of settings applied together from the profile picker
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// Mode passed to `warp-cli mode`, left unchanged if `None`
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Mode passed to `warp-cli dns families`, left unchanged if `None`
+    #[serde(default)]
+    pub families_mode: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            confirm_quit_when_connected: false,
+            wrap_selection: true,
+            stale_transition_secs: 15,
+            profiles: Vec::new(),
+            override_policy: None,
+            command_timeout_secs: None,
+            favorites: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Default location of the config file, `~/.config/warp-tui/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Load the config from `path`, returning it along with any warnings.
+    ///
+    /// A missing file yields the defaults. An unreadable or invalid file falls back to
+    /// the defaults and produces a warning.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::from_toml_str(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(e) => (
+                Self::default(),
+                vec![format!("Failed to read config {}: {}", path.display(), e)],
+            ),
+        }
+    }
+
+    /// The profile called `name`, if one is defined
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    /// Parse a config file, returning it along with any warnings
+    pub fn from_toml_str(contents: &str) -> (Self, Vec<String>) {
+        match toml::from_str(contents) {
+            Ok(config) => (config, Vec::new()),
+            Err(e) => (Self::default(), vec![format!("Invalid config file: {}", e)]),
+        }
+    }
+}
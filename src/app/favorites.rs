@@ -0,0 +1,70 @@
+use crossterm::event::KeyCode;
+use std::collections::BTreeMap;
+
+use crate::warp::WarpMode;
+
+/// Highest function key a favorite can be bound to
+pub const MAX_FAVORITE_KEY: u8 = 12;
+
+/// Action applied directly by a favorite key, without opening a picker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FavoriteAction {
+    /// Switch to this mode
+    SetMode(WarpMode),
+}
+
+impl FavoriteAction {
+    /// Parse the value of a `[favorites]` entry, e.g. `warp+doh`
+    pub fn parse(value: &str) -> Option<Self> {
+        match WarpMode::from_cli_str(value) {
+            WarpMode::Unknown => None,
+            mode => Some(FavoriteAction::SetMode(mode)),
+        }
+    }
+}
+
+impl std::fmt::Display for FavoriteAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FavoriteAction::SetMode(mode) => write!(f, "{}", mode.as_cli_str()),
+        }
+    }
+}
+
+/// Parse a function key name such as `F1` or `f12`
+pub fn parse_function_key(name: &str) -> Option<KeyCode> {
+    let number = name.trim().strip_prefix(['F', 'f'])?.parse::<u8>().ok()?;
+    (1..=MAX_FAVORITE_KEY)
+        .contains(&number)
+        .then_some(KeyCode::F(number))
+}
+
+/// Turn the `[favorites]` table of the config into key bindings, returning them along
+/// with any warnings
+///
+/// Entries with a key other than F1-F12 or an unknown mode are skipped.
+pub fn parse_favorites(
+    favorites: &BTreeMap<String, String>,
+) -> (Vec<(KeyCode, FavoriteAction)>, Vec<String>) {
+    let mut bindings = Vec::new();
+    let mut warnings = Vec::new();
+    for (key, value) in favorites {
+        let Some(code) = parse_function_key(key) else {
+            warnings.push(format!(
+                "Invalid favorite key {:?}, expected F1 to F{}",
+                key, MAX_FAVORITE_KEY
+            ));
+            continue;
+        };
+        match FavoriteAction::parse(value) {
+            Some(action) => bindings.push((code, action)),
+            None => warnings.push(format!("Unknown mode {:?} for favorite {}", value, key)),
+        }
+    }
+    // Sorted by key number rather than name, so F2 comes before F10 in the footer
+    bindings.sort_by_key(|(code, _)| match code {
+        KeyCode::F(number) => *number,
+        _ => 0,
+    });
+    (bindings, warnings)
+}
@@ -0,0 +1,66 @@
+use std::collections::VecDeque;
+
+/// Number of latency samples kept for the sparkline
+pub const LATENCY_HISTORY_CAPACITY: usize = 60;
+
+/// Rolling window of the most recent latency samples in milliseconds.
+///
+/// Once the capacity is reached the oldest sample is dropped for every new one.
+#[derive(Debug)]
+pub struct LatencyHistory {
+    samples: VecDeque<u64>,
+    capacity: usize,
+}
+
+impl Default for LatencyHistory {
+    fn default() -> Self {
+        Self::with_capacity(LATENCY_HISTORY_CAPACITY)
+    }
+}
+
+impl LatencyHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// History keeping at most `capacity` samples
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Append a sample, dropping the oldest one when full
+    pub fn push(&mut self, latency_ms: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency_ms);
+    }
+
+    /// Remove all samples
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// All samples, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = u64> + '_ {
+        self.samples.iter().copied()
+    }
+}
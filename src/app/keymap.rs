@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::app::paths::config_dir;
+
+/// Actions whose keys can be changed in `keybindings.toml`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Connect,
+    Disconnect,
+    Refresh,
+    Mode,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::Connect,
+        Action::Disconnect,
+        Action::Refresh,
+        Action::Mode,
+        Action::Quit,
+    ];
+
+    /// Name of the action as written in `keybindings.toml`
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Connect => "connect",
+            Action::Disconnect => "disconnect",
+            Action::Refresh => "refresh",
+            Action::Mode => "mode",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// Description shown in the help overlay
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Connect => "Connect to WARP",
+            Action::Disconnect => "Disconnect from WARP",
+            Action::Refresh => "Refresh status",
+            Action::Mode => "Change mode",
+            Action::Quit => "Quit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    fn default_key(self) -> char {
+        match self {
+            Action::Connect => 'c',
+            Action::Disconnect => 'd',
+            Action::Refresh => 'r',
+            Action::Mode => 'm',
+            Action::Quit => 'q',
+        }
+    }
+}
+
+/// Keys of the fixed bindings in [`App::on_key_event`](crate::app::App::on_key_event),
+/// which can't be given to an [`Action`]
+pub const RESERVED_KEYS: [char; 31] = [
+    'a', 'b', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'n', 'o', 'p', 's', 't', 'u', 'v', 'w', 'x',
+    'y', 'z', '1', '2', '3', '4', '+', '-', '[', ']', ' ', '?',
+];
+
+/// A key bound to more than one thing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyConflict {
+    /// Two actions bound to the same key
+    Actions(char, Action, Action),
+    /// An action bound to one of the [`RESERVED_KEYS`]
+    Reserved(char, Action),
+}
+
+/// Keys bound to each [`Action`]
+///
+/// Letters match regardless of case, so binding `connect` to `c` also connects on `C`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    keys: [(Action, char); 5],
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL.map(|action| (action, action.default_key())),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Default location of the key bindings, `~/.config/warp-tui/keybindings.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("keybindings.toml"))
+    }
+
+    /// Load the key bindings from `path`, returning them along with any warnings.
+    ///
+    /// A missing file yields the defaults. An unreadable file, invalid TOML or conflicting
+    /// bindings fall back to the defaults and produce a warning.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::from_toml_str(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(e) => (
+                Self::default(),
+                vec![format!(
+                    "Failed to read key bindings {}: {}",
+                    path.display(),
+                    e
+                )],
+            ),
+        }
+    }
+
+    /// Parse key bindings mapping action names to single characters, returning them along
+    /// with any warnings
+    ///
+    /// Unknown actions, keys that aren't a single character and [`RESERVED_KEYS`] are
+    /// skipped, keeping the default for that action. Two actions on the same key fall back
+    /// to the defaults for all of them.
+    pub fn from_toml_str(contents: &str) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut warnings = Vec::new();
+
+        let file: BTreeMap<String, String> = match toml::from_str(contents) {
+            Ok(file) => file,
+            Err(e) => {
+                warnings.push(format!("Invalid key bindings file: {}", e));
+                return (keymap, warnings);
+            }
+        };
+
+        for (name, value) in file {
+            let Some(action) = Action::from_name(&name) else {
+                warnings.push(format!("Unknown action {:?} in key bindings", name));
+                continue;
+            };
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(key), None) if is_reserved(key) => warnings.push(format!(
+                    "Key {:?} for {:?} is reserved for a fixed binding, using the default",
+                    value, name
+                )),
+                (Some(key), None) if !key.is_whitespace() => keymap.bind(action, key),
+                _ => warnings.push(format!(
+                    "Invalid key {:?} for {:?} in key bindings, using the default",
+                    value, name
+                )),
+            }
+        }
+
+        let conflicts = keymap.conflicts();
+        if !conflicts.is_empty() {
+            for conflict in conflicts {
+                warnings.push(match conflict {
+                    KeyConflict::Actions(key, first, second) => format!(
+                        "Key {:?} is bound to both {:?} and {:?}, using the default key bindings",
+                        key,
+                        first.name(),
+                        second.name()
+                    ),
+                    KeyConflict::Reserved(key, action) => format!(
+                        "Key {:?} for {:?} is reserved for a fixed binding, using the default key bindings",
+                        key,
+                        action.name()
+                    ),
+                });
+            }
+            keymap = Self::default();
+        }
+
+        (keymap, warnings)
+    }
+
+    /// Bind `action` to `key`, replacing its previous key
+    pub fn bind(&mut self, action: Action, key: char) {
+        if let Some(entry) = self.keys.iter_mut().find(|(bound, _)| *bound == action) {
+            entry.1 = key;
+        }
+    }
+
+    /// The key bound to `action`
+    pub fn key_for(&self, action: Action) -> char {
+        self.keys
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(action.default_key(), |(_, key)| *key)
+    }
+
+    /// The action bound to `key`, if any
+    pub fn action_for(&self, key: char) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(_, bound)| same_key(*bound, key))
+            .map(|(action, _)| *action)
+    }
+
+    /// Actions bound to the same key as another action or as a fixed binding
+    pub fn conflicts(&self) -> Vec<KeyConflict> {
+        let mut conflicts = Vec::new();
+        for (i, (action, key)) in self.keys.iter().enumerate() {
+            if is_reserved(*key) {
+                conflicts.push(KeyConflict::Reserved(*key, *action));
+            }
+            for (other, other_key) in &self.keys[i + 1..] {
+                if same_key(*key, *other_key) {
+                    conflicts.push(KeyConflict::Actions(*key, *action, *other));
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+fn is_reserved(key: char) -> bool {
+    RESERVED_KEYS
+        .iter()
+        .any(|reserved| same_key(*reserved, key))
+}
+
+fn same_key(a: char, b: char) -> bool {
+    a.to_lowercase().eq(b.to_lowercase())
+}
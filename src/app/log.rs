@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum number of entries kept in the log buffer
+pub const LOG_CAPACITY: usize = 500;
+
+/// Bounded buffer of timestamped log entries shown in the log panel.
+///
+/// Once [`LOG_CAPACITY`] entries are stored the oldest ones are dropped.
+#[derive(Debug, Default)]
+pub struct LogBuffer {
+    entries: VecDeque<String>,
+    /// Number of lines the view is scrolled up from the newest entry
+    scroll: usize,
+}
+
+impl LogBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an entry prefixed with the current time
+    pub fn push(&mut self, message: impl AsRef<str>) {
+        if self.entries.len() == LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries
+            .push_back(format!("[{}] {}", timestamp(), message.as_ref()));
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    /// Remove all entries
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.scroll = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All entries, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(String::as_str)
+    }
+
+    /// Number of lines the view is scrolled up from the newest entry
+    pub fn scroll_offset(&self) -> usize {
+        self.scroll
+    }
+
+    /// Scroll towards older entries
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_add(lines).min(self.max_scroll());
+    }
+
+    /// Scroll towards newer entries
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    /// The entries visible in a view `height` lines tall, oldest first
+    pub fn visible(&self, height: usize) -> impl Iterator<Item = &str> {
+        let end = self.entries.len() - self.scroll;
+        let start = end.saturating_sub(height);
+        self.entries.range(start..end).map(String::as_str)
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.entries.len().saturating_sub(1)
+    }
+}
+
+/// Current UTC time of day formatted as HH:MM:SS
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -0,0 +1,3467 @@
+pub mod browser;
+pub mod button;
+pub mod clipboard;
+pub mod config;
+pub mod favorites;
+pub mod history;
+pub mod keymap;
+pub mod log;
+pub mod notification;
+pub mod paths;
+pub mod signal;
+pub mod state;
+pub mod tab;
+pub mod theme;
+
+use color_eyre::Result;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+    MouseEventKind,
+};
+use ratatui::{
+    DefaultTerminal, Frame,
+    layout::{Constraint, Direction, Flex, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Sparkline, Tabs},
+};
+use std::path::PathBuf;
+use std::sync::{Arc, mpsc as std_mpsc};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::app::button::{BUTTON_HEIGHT, Button, button_at, layout_buttons};
+use crate::app::config::{Config, Profile};
+use crate::app::favorites::{FavoriteAction, parse_favorites};
+use crate::app::history::LatencyHistory;
+use crate::app::keymap::{Action, KeyMap};
+use crate::app::log::LogBuffer;
+use crate::app::signal::Shutdown;
+use crate::app::state::{SessionState, StateError};
+use crate::app::tab::Tab;
+use crate::app::theme::{Theme, color_for_mode};
+use crate::warp::client::CancelToken;
+use crate::warp::error::WarpError;
+use crate::warp::manager::{STATUS_RETRY_DELAY, WarpManager, WarpMessage, should_retry_status};
+use crate::warp::{
+    DaemonStatus, DnsStats, FamiliesMode, NetworkInfo, NetworkType, RegistrationInfo,
+    SettingsBatch, TunnelProtocol, TunnelStats, WarpBackend, WarpClient, WarpInfo, WarpMode,
+    WarpResult, WarpSettingsInfo, WarpStats, WarpStatus,
+};
+
+const AVAILABLE_MODES: &[WarpMode] = &WarpMode::ALL;
+
+/// Keybindings listed in the help overlay after the configurable ones from [`KeyMap`]
+const KEYBINDINGS: &[(&str, &str)] = &[
+    ("f", "Change DNS families mode"),
+    ("p", "Change tunnel protocol"),
+    ("o", "Switch profile"),
+    ("w", "Enable/disable WARP"),
+    ("u", "Apply/clear an admin override"),
+    ("a", "Enable/disable auto-reconnect"),
+    ("z", "Enable/disable Gateway"),
+    ("g", "Show/hide registration"),
+    ("n", "Register, when there's no registration"),
+    ("e", "Reset all settings to defaults"),
+    ("v (registration panel)", "Reveal/hide the license key"),
+    ("s", "Show/hide DNS statistics"),
+    ("h", "Show/hide tunnel statistics"),
+    ("i", "Show/hide connection details"),
+    ("l", "Show/hide the raw warp-cli status output"),
+    ("v", "Trust/untrust the current Wi-Fi network"),
+    ("+/-", "Increase/decrease refresh interval"),
+    ("]/[", "Increase/decrease command timeout"),
+    ("Tab/S-Tab/1-4", "Switch tabs"),
+    ("PgUp/PgDn", "Scroll the log"),
+    ("t", "Run connectivity diagnostics"),
+    ("y", "Copy the status to the clipboard"),
+    ("b", "Open the WARP settings page in the browser"),
+    ("F1-F12", "Apply a favorite from the config"),
+    ("x", "Clear the log"),
+    ("Space", "Pause/resume auto-refresh"),
+    ("Up/Down, j/k", "Navigate selection lists"),
+    ("gg/G", "Jump to first/last entry"),
+    ("Enter", "Apply selection"),
+    ("Esc", "Cancel selection"),
+    ("Ctrl-X/Esc", "Abort the running command"),
+    ("?", "Toggle this help"),
+];
+
+/// Minimum time between two automatic reconnects, so a flapping connection doesn't make
+/// the app hammer the daemon
+pub const RECONNECT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Connect or disconnect presses within this long of the previous connect or disconnect
+/// are ignored, so mashing the keys doesn't queue up warp-cli calls
+pub const CONNECT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Time between two health checks of the warp daemon
+pub const DAEMON_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long quitting waits for WARP to finish disconnecting
+const QUIT_DISCONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Refresh interval bounds and step used by the '+' and '-' keys, in milliseconds
+pub const MIN_REFRESH_INTERVAL_MS: u64 = 250;
+pub const MAX_REFRESH_INTERVAL_MS: u64 = 10_000;
+pub const REFRESH_INTERVAL_STEP_MS: i64 = 250;
+
+/// Bounds and step of the command timeout adjusted with '[' and ']'
+pub const MIN_COMMAND_TIMEOUT_SECS: u64 = 5;
+pub const MAX_COMMAND_TIMEOUT_SECS: u64 = 120;
+pub const COMMAND_TIMEOUT_STEP_SECS: i64 = 5;
+
+/// Number of lines scrolled by PageUp/PageDown in the log panel
+const LOG_SCROLL_STEP: usize = 5;
+
+/// How long an error stays in the error area after it was last reported
+pub const ERROR_DISPLAY_DURATION: Duration = Duration::from_secs(5);
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(2);
+
+/// Height of the latency sparkline including its border
+const SPARKLINE_HEIGHT: u16 = 4;
+
+/// Terminals shorter than this get the compact single-line status
+pub const COMPACT_HEIGHT: u16 = 12;
+
+/// How long to wait for a terminal event before redrawing, short enough for the
+/// refresh countdown, shown in tenths of a second, to tick smoothly
+const EVENT_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Frames of the spinner shown while a command is in progress
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// A warp-cli command whose result the UI is waiting for
+#[derive(Debug)]
+struct PendingOperation {
+    /// Text shown next to the spinner, e.g. "Applying mode…"
+    label: &'static str,
+    /// Name of the operation used in error messages
+    action: &'static str,
+    /// Result of the command running on a background thread
+    result: std_mpsc::Receiver<WarpResult<Completion>>,
+    /// Stops the command on the background thread
+    cancel: CancelToken,
+}
+
+/// An action waiting for the user to confirm it in a dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingAction {
+    /// Quit while WARP is connected, optionally disconnecting first
+    QuitWhileConnected,
+    /// Apply the configured admin override, or clear the active one
+    Override,
+    /// Create a new registration for an unregistered device
+    Register,
+    /// Reset every WARP setting to its default
+    ResetSettings,
+}
+
+impl PendingAction {
+    /// Question shown in the dialog, one line per line of the dialog
+    fn prompt(self) -> &'static str {
+        match self {
+            PendingAction::QuitWhileConnected => "WARP is connected. Disconnect before quitting?",
+            PendingAction::Override => {
+                "Overrides weaken the security policy of this device. Continue?"
+            }
+            PendingAction::Register => "Register this device with Cloudflare WARP?",
+            PendingAction::ResetSettings => {
+                "Reset ALL WARP settings to their defaults?\n\
+                The mode, protocol, overrides and every other\n\
+                customization will be lost. This can't be undone."
+            }
+        }
+    }
+
+    /// Keys accepted by the dialog and what they do
+    fn choices(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            PendingAction::QuitWhileConnected => &[
+                ("d", "Quit and disconnect"),
+                ("l", "Quit and leave connected"),
+                ("Esc", "Cancel"),
+            ],
+            PendingAction::Override => &[
+                ("a", "Apply the configured override"),
+                ("c", "Clear the override"),
+                ("Esc", "Cancel"),
+            ],
+            PendingAction::Register => &[("y", "Register"), ("Esc", "Cancel")],
+            PendingAction::ResetSettings => &[("y", "Reset everything"), ("Esc", "Cancel")],
+        }
+    }
+}
+
+/// What to do once a background operation succeeds
+#[derive(Debug)]
+enum Completion {
+    /// Log the message, if any, and refresh the status
+    Refresh(Option<String>),
+    /// Show the output in the diagnostics panel
+    Diagnostics(String),
+    /// Note the new registration and refresh the status
+    Registered(RegistrationInfo),
+    /// Log the message, nothing changed
+    Log(String),
+    /// Show the DNS statistics, or why they couldn't be fetched, in their panel
+    DnsStats(Result<DnsStats, String>),
+    /// Show the tunnel statistics, or why they couldn't be fetched, in their panel
+    TunnelStats(Result<TunnelStats, String>),
+    /// Show the registration, or why it couldn't be fetched, in its panel
+    Registration(Result<RegistrationInfo, String>),
+}
+
+/// State of the diagnostics panel
+#[derive(Debug)]
+struct DiagnosticsPanel {
+    /// Output of the connectivity check
+    output: String,
+    /// Number of lines scrolled past the top
+    scroll: u16,
+}
+
+/// State of the registration panel
+#[derive(Debug)]
+struct RegistrationPanel {
+    /// Registration details, or the error message if they couldn't be fetched
+    info: Result<RegistrationInfo, String>,
+    /// Show the license key unmasked?
+    reveal_license_key: bool,
+}
+
+/// The main application which holds the state and logic of the application.
+#[derive(Debug)]
+pub struct App {
+    /// Is the application running?
+    running: bool,
+    /// Set when a termination signal asks the application to quit
+    shutdown: Shutdown,
+    /// Warp client for executing commands
+    warp_client: Arc<dyn WarpBackend>,
+    /// Manager running warp-cli commands in the background
+    warp_manager: Arc<WarpManager>,
+    /// Status updates and errors published by the manager
+    warp_events: Option<mpsc::UnboundedReceiver<WarpMessage>>,
+    /// Command queue of the manager, set while the async event loop is running
+    warp_commands: Option<mpsc::UnboundedSender<WarpMessage>>,
+    /// Current warp information
+    warp_info: WarpInfo,
+    /// Last error reported by a warp-cli command, and when it was last reported
+    ///
+    /// Errors from the manager arrive as messages, so only their `Display` is kept.
+    last_error: Option<(String, Instant)>,
+    /// Current refresh interval in milliseconds
+    refresh_interval_ms: u64,
+    /// Last refresh time
+    last_refresh: Instant,
+    /// Mode selection state, indexing the modes matching `mode_filter`
+    mode_selection: Option<ListState>,
+    /// Text typed into the mode selection to narrow down the list
+    mode_filter: String,
+    /// DNS families mode selection state
+    families_selection: Option<ListState>,
+    /// Tunnel protocol selection state
+    protocol_selection: Option<ListState>,
+    /// Tunnel protocol reported by warp-cli when the protocol selection was opened
+    current_protocol: Option<TunnelProtocol>,
+    /// Profile selection state, indexing the profiles in the config
+    profile_selection: Option<ListState>,
+    /// Name of the profile applied last
+    active_profile: Option<String>,
+    /// Is the help overlay visible?
+    show_help: bool,
+    /// Are the connection details (endpoint, tunnel IP) shown in the main panel?
+    show_details: bool,
+    /// Is auto-refresh paused?
+    auto_refresh_paused: bool,
+    /// Was a status refresh sent to the manager and not answered yet? Lets its
+    /// `StatusUpdate` through while auto-refresh is paused
+    status_requested: bool,
+    /// Registration panel state, `Some` while the panel is shown
+    registration: Option<RegistrationPanel>,
+    /// Diagnostics panel state, `Some` while the panel is shown
+    diagnostics: Option<DiagnosticsPanel>,
+    /// Lines scrolled past the top of the raw status panel, `Some` while the panel is shown
+    raw_status_scroll: Option<u16>,
+    /// DNS statistics, or the error message if they couldn't be fetched; `Some` while the
+    /// panel is shown
+    dns_stats: Option<Result<DnsStats, String>>,
+    /// Tunnel statistics, or the error message if they couldn't be fetched; `Some` while
+    /// the panel is shown
+    tunnel_stats: Option<Result<TunnelStats, String>>,
+    /// Commands executed and errors encountered
+    log: LogBuffer,
+    /// Recent latency samples while connected, shown as a sparkline
+    latency_history: LatencyHistory,
+    /// Colors used to render the status
+    theme: Theme,
+    /// Behavior settings from the config file
+    config: Config,
+    /// Keys bound to the configurable actions, from the key bindings file
+    keymap: KeyMap,
+    /// Action awaiting confirmation, shown as a dialog
+    pending_action: Option<PendingAction>,
+    /// Footer describing the warp-cli version in use
+    version_label: String,
+    /// Command in progress, shown with a spinner
+    pending: Option<PendingOperation>,
+    /// Reconnect automatically when WARP drops unexpectedly?
+    auto_reconnect: bool,
+    /// When the last automatic reconnect was started
+    last_reconnect: Option<Instant>,
+    /// When the last connect or disconnect was issued from a key press or button
+    last_connect_command: Option<Instant>,
+    /// When the status became Connecting or Disconnecting, `None` while it's settled
+    transition_since: Option<Instant>,
+    /// When the status last became Connected, `None` while it isn't connected
+    connected_since: Option<Instant>,
+    /// Current frame of the spinner, advanced on every render
+    spinner_frame: usize,
+    /// Was the previous key a 'g' in a selection list, waiting for a second 'g'?
+    pending_g: bool,
+    /// Buttons drawn in the last frame and where, empty while they're hidden
+    buttons: Vec<(Button, Rect)>,
+    /// Mode last selected in the mode picker
+    last_mode: Option<WarpMode>,
+    /// Where the session state is saved on quit, `None` to not save it
+    state_path: Option<PathBuf>,
+    /// Was warp-cli not found? Commands that need it are disabled while set
+    cli_missing: bool,
+    /// Has the first status fetch finished? A placeholder is shown until it has
+    initial_load_done: bool,
+    /// Result of the last daemon health check, shown in the footer
+    daemon_status: Option<DaemonStatus>,
+    /// Health check running in the background
+    daemon_check: Option<std_mpsc::Receiver<WarpResult<DaemonStatus>>>,
+    /// When the last health check was started
+    last_daemon_check: Option<Instant>,
+    /// Is the device unregistered? `None` until a status without an account type was
+    /// checked with `warp-cli registration show`
+    registration_missing: Option<bool>,
+    /// Registration check running in the background
+    registration_check: Option<std_mpsc::Receiver<WarpResult<RegistrationInfo>>>,
+    /// Short confirmation shown briefly, and when it was shown
+    toast: Option<(String, Instant)>,
+    /// Tab shown in the main area
+    active_tab: Tab,
+    /// Settings shown in the settings tab, or the error message if they couldn't be
+    /// fetched; `None` until the startup fetch finishes or the tab is first opened
+    settings: Option<Result<WarpSettingsInfo, String>>,
+    /// Settings fetch started at startup, running in the background
+    settings_check: Option<std_mpsc::Receiver<WarpResult<WarpSettingsInfo>>>,
+    /// Tunnel statistics from the last refresh, `None` while disconnected
+    last_stats: Option<WarpStats>,
+    /// Actions bound to function keys in the config, shown in the footer
+    favorites: Vec<(KeyCode, FavoriteAction)>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        let mut warp_manager = WarpManager::new();
+        let warp_events = warp_manager.take_event_receiver();
+
+        Self {
+            running: true,
+            shutdown: Shutdown::new(),
+            warp_client: Arc::new(WarpClient::default()),
+            warp_manager: Arc::new(warp_manager),
+            warp_events,
+            warp_commands: None,
+            warp_info: WarpInfo::default(),
+            last_error: None,
+            refresh_interval_ms: 1000,
+            last_refresh: Instant::now(),
+            mode_selection: None,
+            mode_filter: String::new(),
+            families_selection: None,
+            protocol_selection: None,
+            current_protocol: None,
+            profile_selection: None,
+            active_profile: None,
+            show_help: false,
+            show_details: false,
+            auto_refresh_paused: false,
+            status_requested: false,
+            registration: None,
+            dns_stats: None,
+            tunnel_stats: None,
+            diagnostics: None,
+            raw_status_scroll: None,
+            log: LogBuffer::new(),
+            latency_history: LatencyHistory::new(),
+            theme: Theme::default(),
+            config: Config::default(),
+            keymap: KeyMap::default(),
+            pending_action: None,
+            version_label: "warp-cli: unknown version".to_string(),
+            pending: None,
+            spinner_frame: 0,
+            transition_since: None,
+            connected_since: None,
+            auto_reconnect: false,
+            last_reconnect: None,
+            last_connect_command: None,
+            pending_g: false,
+            buttons: Vec::new(),
+            last_mode: None,
+            state_path: None,
+            cli_missing: false,
+            initial_load_done: false,
+            daemon_status: None,
+            daemon_check: None,
+            last_daemon_check: None,
+            registration_missing: None,
+            registration_check: None,
+            toast: None,
+            active_tab: Tab::default(),
+            settings: None,
+            settings_check: None,
+            last_stats: None,
+            favorites: Vec::new(),
+        }
+    }
+}
+
+impl App {
+    /// Construct a new instance of [`App`], loading the theme and config from the config
+    /// directory and the state saved by the previous session.
+    pub fn new() -> Self {
+        let mut app = Self::default();
+        if let Some(path) = Config::default_path() {
+            let (config, warnings) = Config::load(&path);
+            app.config = config;
+            app.apply_command_timeout();
+            let warnings = warnings.into_iter().chain(app.apply_favorites());
+            for warning in warnings {
+                app.log.push(format!("Warning: {}", warning));
+            }
+        }
+
+        if let Some(path) = KeyMap::default_path() {
+            let (keymap, warnings) = KeyMap::load(&path);
+            app.keymap = keymap;
+            for warning in warnings {
+                app.log.push(format!("Warning: {}", warning));
+            }
+        }
+
+        if let Some(path) = Theme::default_path() {
+            let (theme, warnings) = Theme::load(&path);
+            app.theme = theme;
+            for warning in warnings {
+                app.log.push(format!("Warning: {}", warning));
+            }
+        }
+
+        if let Some(path) = SessionState::default_path() {
+            match state::load(&path) {
+                Ok(state) => app.apply_state(&state),
+                Err(StateError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => app
+                    .log
+                    .push(format!("Warning: ignoring saved state: {}", e)),
+            }
+            app.state_path = Some(path);
+        }
+
+        let version = app.warp_client.get_version_sync();
+        app.cli_missing = matches!(version, Err(WarpError::CommandNotFound));
+        app.version_label = version_label(&version);
+        app
+    }
+
+    /// Restore the settings of a previous session
+    pub fn apply_state(&mut self, state: &SessionState) {
+        if let Some(interval) = state.refresh_interval_ms {
+            self.refresh_interval_ms = adjust_refresh_interval(interval, 0);
+        }
+        self.last_mode = state.last_mode();
+        if let Some(auto_reconnect) = state.auto_reconnect {
+            self.auto_reconnect = auto_reconnect;
+        }
+        self.active_profile = state.last_profile.clone();
+    }
+
+    /// Settings to remember for the next session
+    pub fn session_state(&self) -> SessionState {
+        SessionState {
+            refresh_interval_ms: Some(self.refresh_interval_ms),
+            last_mode: self
+                .last_mode
+                .as_ref()
+                .map(|mode| mode.as_cli_str().to_string()),
+            auto_reconnect: Some(self.auto_reconnect),
+            last_profile: self.active_profile.clone(),
+        }
+    }
+
+    /// Save the session state, ignoring failures since there's nowhere left to report them
+    fn save_state(&self) {
+        if let Some(path) = &self.state_path {
+            let _ = state::save(path, &self.session_state());
+        }
+    }
+
+    /// Replace the behavior settings
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self.apply_command_timeout();
+        for warning in self.apply_favorites() {
+            self.log.push(format!("Warning: {}", warning));
+        }
+        self
+    }
+
+    /// Bind the favorites of the config to their keys, returning warnings for the
+    /// entries that were skipped
+    fn apply_favorites(&mut self) -> Vec<String> {
+        let (favorites, warnings) = parse_favorites(&self.config.favorites);
+        self.favorites = favorites;
+        warnings
+    }
+
+    /// Actions bound to function keys, in key order
+    pub fn favorites(&self) -> &[(KeyCode, FavoriteAction)] {
+        &self.favorites
+    }
+
+    /// The favorite action bound to `key`, if any
+    pub fn favorite_for(&self, key: KeyCode) -> Option<&FavoriteAction> {
+        self.favorites
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| action)
+    }
+
+    /// Give the client the command timeout from the config, if it sets one
+    fn apply_command_timeout(&self) {
+        if let Some(secs) = self.config.command_timeout_secs {
+            self.warp_client
+                .set_timeout(Duration::from_secs(adjust_command_timeout(secs, 0)));
+        }
+    }
+
+    /// Time a warp-cli command may run before it's killed
+    pub fn command_timeout(&self) -> Duration {
+        self.warp_client.timeout()
+    }
+
+    /// Replace the key bindings of the configurable actions
+    pub fn with_keymap(mut self, keymap: KeyMap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Replace the warp-cli client used for commands and background refreshes
+    pub fn with_client(mut self, client: WarpClient) -> Self {
+        let mut warp_manager = WarpManager::with_client(client.clone());
+        self.warp_events = warp_manager.take_event_receiver();
+        self.warp_manager = Arc::new(warp_manager);
+        self.warp_client = Arc::new(client);
+        self.apply_command_timeout();
+        self
+    }
+
+    /// Run every command through `backend`
+    ///
+    /// Lets tests drive the app with scripted responses instead of warp-cli. The
+    /// [`WarpManager`] only runs a [`WarpClient`], so it's left out: its messages are
+    /// dropped and [`App::run_async`] falls back to the blocking [`App::run`].
+    pub fn with_backend(mut self, backend: Arc<dyn WarpBackend>) -> Self {
+        self.warp_client = backend;
+        self.warp_events = None;
+        self.apply_command_timeout();
+        self
+    }
+
+    /// How long WARP has been connected, `None` while it isn't
+    pub fn connected_for(&self) -> Option<Duration> {
+        self.connected_since.map(|since| since.elapsed())
+    }
+
+    /// Has the first status fetch finished, successfully or not?
+    pub fn is_initial_load_done(&self) -> bool {
+        self.initial_load_done
+    }
+
+    /// Is the application running, i.e. hasn't the user quit?
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Action awaiting confirmation, if any
+    pub fn pending_action(&self) -> Option<PendingAction> {
+        self.pending_action
+    }
+
+    /// Was warp-cli not found?
+    pub fn is_cli_missing(&self) -> bool {
+        self.cli_missing
+    }
+
+    /// Footer describing the warp-cli version in use
+    pub fn version_label(&self) -> &str {
+        &self.version_label
+    }
+
+    /// Colors used to render the status
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Keys bound to the configurable actions
+    pub fn keymap(&self) -> &KeyMap {
+        &self.keymap
+    }
+
+    /// Current warp information
+    pub fn warp_info(&self) -> &WarpInfo {
+        &self.warp_info
+    }
+
+    /// Last error reported by a warp-cli command
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_ref().map(|(error, _)| error.as_str())
+    }
+
+    /// Error shown in the error area at `now`, see [`error_visible`]
+    ///
+    /// A missing warp-cli stays until it's found, other errors clear after
+    /// [`ERROR_DISPLAY_DURATION`] or once a status refresh succeeds.
+    pub fn error_banner_at(&self, now: Instant) -> Option<&str> {
+        self.last_error
+            .as_ref()
+            .filter(|(_, reported)| error_visible(*reported, now, self.cli_missing))
+            .map(|(error, _)| error.as_str())
+    }
+
+    /// Current refresh interval in milliseconds
+    pub fn refresh_interval_ms(&self) -> u64 {
+        self.refresh_interval_ms
+    }
+
+    /// Is auto-refresh paused?
+    pub fn is_auto_refresh_paused(&self) -> bool {
+        self.auto_refresh_paused
+    }
+
+    /// Pause or resume auto-refresh, including the periodic refreshes of the manager
+    fn toggle_auto_refresh_paused(&mut self) {
+        self.auto_refresh_paused = !self.auto_refresh_paused;
+        self.warp_manager.set_paused(self.auto_refresh_paused);
+    }
+
+    /// Latency samples taken while connected, drawn as the sparkline
+    pub fn latency_history(&self) -> &LatencyHistory {
+        &self.latency_history
+    }
+
+    /// Commands executed and errors encountered
+    pub fn log(&self) -> &LogBuffer {
+        &self.log
+    }
+
+    /// Are the connection details shown?
+    pub fn is_details_visible(&self) -> bool {
+        self.show_details
+    }
+
+    /// Is the help overlay visible?
+    pub fn is_help_visible(&self) -> bool {
+        self.show_help
+    }
+
+    /// Label of the command in progress, if any
+    pub fn pending_operation(&self) -> Option<&str> {
+        self.pending.as_ref().map(|pending| pending.label)
+    }
+
+    /// The manager used by the async event loop
+    pub fn warp_manager(&self) -> &WarpManager {
+        &self.warp_manager
+    }
+
+    /// Exit code describing the WARP status, see [`status_exit_code`]
+    pub fn exit_code(&self) -> i32 {
+        status_exit_code(&self.warp_info.status)
+    }
+
+    /// Handle that makes the event loop quit when requested, e.g. from a signal handler
+    pub fn shutdown_handle(&self) -> Shutdown {
+        self.shutdown.clone()
+    }
+
+    /// Quit if a shutdown was requested through [`App::shutdown_handle`]
+    ///
+    /// Skips the quit confirmation, there's nobody to answer it.
+    pub fn check_shutdown(&mut self) {
+        if self.running && self.shutdown.is_requested() {
+            self.log.push("Shutting down");
+            self.quit();
+        }
+    }
+
+    /// Run the application's main loop, returning the [`App::exit_code`] once quit.
+    ///
+    /// This is the blocking fallback used when no tokio runtime is available;
+    /// every warp-cli command runs on the render thread.
+    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<i32> {
+        self.running = true;
+
+        // Show the loading placeholder before the first, possibly slow, status fetch
+        terminal.draw(|frame| self.render(frame))?;
+        self.start_settings_check();
+        self.update_warp_status();
+
+        // A terminal error ends the loop like quitting does, so the state is still saved
+        let mut terminal_error = None;
+        while self.running {
+            self.poll_pending_operation();
+            self.poll_daemon_check();
+            self.poll_registration_check();
+            self.poll_settings_check();
+            self.check_daemon_if_due();
+            if let Err(e) = terminal.draw(|frame| self.render(frame)) {
+                terminal_error = Some(e.into());
+                break;
+            }
+
+            // Check if we need to auto-refresh
+            if self.should_auto_refresh() {
+                self.update_warp_status();
+            }
+
+            if let Err(e) = self.handle_crossterm_events() {
+                terminal_error = Some(e);
+                break;
+            }
+            self.check_shutdown();
+        }
+        self.save_state();
+        match terminal_error {
+            Some(e) => Err(e),
+            None => Ok(self.exit_code()),
+        }
+    }
+
+    /// Run the application's main loop on the current tokio runtime, returning the
+    /// [`App::exit_code`] once quit.
+    ///
+    /// warp-cli commands are handed to the [`WarpManager`] so the UI keeps
+    /// rendering while they run. An app built [`App::with_backend`] has no manager to hand
+    /// them to and runs [`App::run`] instead.
+    pub async fn run_async(mut self, mut terminal: DefaultTerminal) -> Result<i32> {
+        self.running = true;
+
+        let Some(mut warp_events) = self.warp_events.take() else {
+            return self.run(terminal);
+        };
+
+        let manager = Arc::clone(&self.warp_manager);
+        manager.start_background_tasks().await;
+        tokio::spawn(async move { manager.process_messages().await });
+        self.warp_commands = Some(self.warp_manager.get_sender());
+
+        let mut terminal_events = spawn_terminal_event_reader();
+        let mut tick = tokio::time::interval(EVENT_POLL_TIMEOUT);
+
+        // Initialize warp status
+        self.start_settings_check();
+        self.request_refresh();
+
+        // A terminal error ends the loop like quitting does, so the state is still saved
+        let mut terminal_error = None;
+        while self.running {
+            self.poll_pending_operation();
+            self.poll_daemon_check();
+            self.poll_registration_check();
+            self.poll_settings_check();
+            self.check_daemon_if_due();
+            if let Err(e) = terminal.draw(|frame| self.render(frame)) {
+                terminal_error = Some(e);
+                break;
+            }
+
+            tokio::select! {
+                Some(event) = terminal_events.recv() => match event {
+                    Ok(event) => self.handle_event(event),
+                    Err(e) => {
+                        terminal_error = Some(e);
+                        break;
+                    }
+                },
+                Some(message) = warp_events.recv() => self.handle_warp_message(message),
+                _ = tick.tick() => {
+                    if self.should_auto_refresh() {
+                        self.request_refresh();
+                    }
+                }
+            }
+            self.check_shutdown();
+        }
+        self.save_state();
+        match terminal_error {
+            Some(e) => Err(e.into()),
+            None => Ok(self.exit_code()),
+        }
+    }
+
+    /// Apply every message currently queued on the manager's event channel
+    pub fn process_warp_messages(&mut self) {
+        let Some(mut warp_events) = self.warp_events.take() else {
+            return;
+        };
+        while let Ok(message) = warp_events.try_recv() {
+            self.handle_warp_message(message);
+        }
+        self.warp_events = Some(warp_events);
+    }
+
+    /// Update the state of [`App`] from a message published by the manager
+    pub fn handle_warp_message(&mut self, message: WarpMessage) {
+        match message {
+            WarpMessage::StatusUpdate(info, stats) => {
+                // While paused only the refreshes asked for are shown
+                let requested = std::mem::take(&mut self.status_requested);
+                if self.auto_refresh_paused && !requested {
+                    return;
+                }
+                self.handle_status_with_stats_result(Ok((info, stats.map(|stats| *stats))));
+            }
+            WarpMessage::Error(error) => {
+                self.status_requested = false;
+                self.initial_load_done = true;
+                self.record_error(error);
+            }
+            WarpMessage::Log(message) => self.log.push(message),
+            _ => {}
+        }
+    }
+
+    /// Remember the latest error and log it unless it repeats the previous one
+    ///
+    /// A repeated error is shown for another [`ERROR_DISPLAY_DURATION`], so one that keeps
+    /// coming back stays in view.
+    fn record_error(&mut self, error: String) {
+        if self.last_error() != Some(error.as_str()) {
+            self.log.push(format!("Error: {}", error));
+        }
+        self.last_error = Some((error, Instant::now()));
+    }
+
+    /// Run `operation` on a background thread, showing `label` with a spinner until it finishes
+    ///
+    /// The returned [`Completion`] is applied once it succeeds.
+    fn start_operation(
+        &mut self,
+        label: &'static str,
+        action: &'static str,
+        operation: impl FnOnce(&dyn WarpBackend) -> WarpResult<Completion> + Send + 'static,
+    ) {
+        let cancel = CancelToken::new();
+        let client = self.warp_client.cancellable(cancel.clone());
+        let (sender, receiver) = std_mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(operation(&*client));
+        });
+        self.pending = Some(PendingOperation {
+            label,
+            action,
+            result: receiver,
+            cancel,
+        });
+    }
+
+    /// Abort the pending operation, killing its warp-cli process
+    ///
+    /// The background thread reports [`WarpError::Cancelled`] once the process is gone,
+    /// see [`Self::poll_pending_operation`].
+    pub fn abort_operation(&mut self) {
+        if let Some(pending) = &self.pending {
+            pending.cancel.cancel();
+        }
+    }
+
+    /// Clear the pending operation once its background thread reports a result
+    pub fn poll_pending_operation(&mut self) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+
+        let action = pending.action;
+        match pending.result.try_recv() {
+            Ok(Ok(Completion::Refresh(message))) => {
+                self.pending = None;
+                if let Some(message) = message {
+                    self.log.push(message);
+                }
+                self.request_refresh();
+                // The command may have changed the settings
+                self.start_settings_check();
+            }
+            Ok(Ok(Completion::Diagnostics(output))) => {
+                self.pending = None;
+                self.diagnostics = Some(DiagnosticsPanel { output, scroll: 0 });
+            }
+            Ok(Ok(Completion::Registered(info))) => {
+                self.pending = None;
+                self.handle_registration_created(Ok(info));
+            }
+            Ok(Ok(Completion::Log(message))) => {
+                self.pending = None;
+                self.log.push(message);
+            }
+            Ok(Ok(Completion::DnsStats(stats))) => {
+                self.pending = None;
+                self.dns_stats = Some(stats);
+            }
+            Ok(Ok(Completion::TunnelStats(stats))) => {
+                self.pending = None;
+                self.tunnel_stats = Some(stats);
+            }
+            Ok(Ok(Completion::Registration(info))) => {
+                self.pending = None;
+                self.registration = Some(RegistrationPanel {
+                    info,
+                    reveal_license_key: false,
+                });
+            }
+            Ok(Err(WarpError::Cancelled)) => {
+                self.pending = None;
+                self.log.push(format!("{} aborted", action));
+                // The command may have got partway, so find out where WARP ended up
+                self.request_refresh();
+            }
+            Ok(Err(e)) => {
+                self.pending = None;
+                self.record_error(format!("{} failed: {}", action, e));
+            }
+            Err(std_mpsc::TryRecvError::Empty) => {}
+            Err(std_mpsc::TryRecvError::Disconnected) => {
+                self.pending = None;
+                self.record_error(format!("{} failed: command thread exited", action));
+            }
+        }
+    }
+
+    /// Start a daemon health check in the background every [`DAEMON_CHECK_INTERVAL`],
+    /// unless one is still running
+    fn check_daemon_if_due(&mut self) {
+        if self.daemon_check.is_some()
+            || self
+                .last_daemon_check
+                .is_some_and(|last| last.elapsed() < DAEMON_CHECK_INTERVAL)
+        {
+            return;
+        }
+
+        self.daemon_check = Some(self.spawn_with_client(|client| client.daemon_status_sync()));
+        self.last_daemon_check = Some(Instant::now());
+    }
+
+    /// Run `f` on a background thread with a clone of the client, returning a receiver for
+    /// its result
+    fn spawn_with_client<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&dyn WarpBackend) -> T + Send + 'static,
+    ) -> std_mpsc::Receiver<T> {
+        let client = Arc::clone(&self.warp_client);
+        let (sender, receiver) = std_mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(f(&*client));
+        });
+        receiver
+    }
+
+    /// Apply the result of the background health check once it's done
+    fn poll_daemon_check(&mut self) {
+        let Some(receiver) = &self.daemon_check else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.daemon_check = None;
+                self.handle_daemon_status_result(result);
+            }
+            Err(std_mpsc::TryRecvError::Empty) => {}
+            Err(std_mpsc::TryRecvError::Disconnected) => self.daemon_check = None,
+        }
+    }
+
+    /// Update the daemon status from a health check, logging when it changes
+    pub fn handle_daemon_status_result(&mut self, result: WarpResult<DaemonStatus>) {
+        match result {
+            Ok(status) => {
+                if self
+                    .daemon_status
+                    .is_some_and(|previous| previous != status)
+                {
+                    self.log.push(format!("WARP daemon: {}", status));
+                }
+                self.daemon_status = Some(status);
+            }
+            Err(e) => {
+                self.daemon_status = None;
+                self.record_error(format!("Daemon health check failed: {}", e));
+            }
+        }
+    }
+
+    /// Apply the result of the background registration check once it's done
+    fn poll_registration_check(&mut self) {
+        let Some(receiver) = &self.registration_check else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.registration_check = None;
+                self.handle_registration_check_result(result);
+            }
+            Err(std_mpsc::TryRecvError::Empty) => {}
+            Err(std_mpsc::TryRecvError::Disconnected) => self.registration_check = None,
+        }
+    }
+
+    /// Fetch the settings in the background, so commands are disabled once they turn out
+    /// to be locked
+    ///
+    /// Until the result arrives the settings count as not locked.
+    pub fn start_settings_check(&mut self) {
+        if self.cli_missing || self.settings_check.is_some() {
+            return;
+        }
+        self.settings_check = Some(self.spawn_with_client(|client| client.get_settings_parsed()));
+    }
+
+    /// Are the settings being fetched by [`App::start_settings_check`]?
+    pub fn is_settings_loading(&self) -> bool {
+        self.settings_check.is_some()
+    }
+
+    /// Apply the settings fetched by [`App::start_settings_check`] once they arrive
+    pub fn poll_settings_check(&mut self) {
+        let Some(receiver) = &self.settings_check else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(result) => {
+                self.settings_check = None;
+                self.settings = Some(result.map_err(|e| e.to_string()));
+            }
+            Err(std_mpsc::TryRecvError::Empty) => {}
+            Err(std_mpsc::TryRecvError::Disconnected) => self.settings_check = None,
+        }
+    }
+
+    /// Update the registration state from `warp-cli registration show`
+    ///
+    /// Only [`WarpError::NoRegistration`] means the device is unregistered; other errors
+    /// are treated as registered so the call to action isn't shown by mistake.
+    pub fn handle_registration_check_result(&mut self, result: WarpResult<RegistrationInfo>) {
+        let missing = matches!(result, Err(WarpError::NoRegistration));
+        if missing && self.registration_missing != Some(true) {
+            self.log.push("No WARP registration found");
+        }
+        self.registration_missing = Some(missing);
+    }
+
+    /// Handle the result of creating a registration with `warp-cli registration new`
+    ///
+    /// On success the registration panel opens with the new device ID; a failure is
+    /// shown in the error area.
+    pub fn handle_registration_created(&mut self, result: WarpResult<RegistrationInfo>) {
+        match result {
+            Ok(info) => {
+                self.registration_missing = Some(false);
+                let account_type = info.account_type.as_deref().unwrap_or("N/A");
+                let device_id = info.device_id.as_deref().unwrap_or("N/A");
+                self.log.push(format!(
+                    "Registered (account type: {}) as device {}",
+                    account_type, device_id
+                ));
+                self.registration = Some(RegistrationPanel {
+                    info: Ok(info),
+                    reveal_license_key: false,
+                });
+                self.request_refresh();
+            }
+            Err(e) => self.record_error(format!("Registration failed: {}", e)),
+        }
+    }
+
+    /// Was the device found to have no registration?
+    pub fn is_registration_missing(&self) -> bool {
+        self.registration_missing == Some(true)
+    }
+
+    /// Result of the last daemon health check, `None` before the first one
+    pub fn daemon_status(&self) -> Option<DaemonStatus> {
+        self.daemon_status
+    }
+
+    /// Check if it's time to auto-refresh
+    pub fn should_auto_refresh(&self) -> bool {
+        if self.auto_refresh_paused {
+            return false;
+        }
+        let refresh_interval = Duration::from_millis(self.refresh_interval_ms);
+        self.last_refresh.elapsed() >= refresh_interval
+    }
+
+    /// Get current refresh interval in milliseconds
+    fn current_refresh_interval(&self) -> u64 {
+        self.refresh_interval_ms
+    }
+
+    /// Change the refresh interval by `delta_ms` and restart the refresh timer
+    fn change_refresh_interval(&mut self, delta_ms: i64) {
+        self.refresh_interval_ms = adjust_refresh_interval(self.refresh_interval_ms, delta_ms);
+        self.last_refresh = Instant::now();
+    }
+
+    /// Change the command timeout by `delta_secs`, for the manager's commands too
+    fn change_command_timeout(&mut self, delta_secs: i64) {
+        let secs = adjust_command_timeout(self.command_timeout().as_secs(), delta_secs);
+        self.warp_client.set_timeout(Duration::from_secs(secs));
+        self.log.push(format!("Command timeout: {}s", secs));
+    }
+
+    /// Send a command to the manager, returning false if the async event loop isn't running
+    fn send_command(&self, message: WarpMessage) -> bool {
+        match &self.warp_commands {
+            Some(sender) => sender.send(message).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Refresh the warp status, in the background when possible
+    fn request_refresh(&mut self) {
+        if self.send_command(WarpMessage::Refresh) {
+            self.status_requested = true;
+            self.last_refresh = Instant::now();
+        } else {
+            self.update_warp_status();
+        }
+    }
+
+    /// Update the warp status information
+    ///
+    /// A status that parses as Unknown although warp-cli succeeded usually means it was
+    /// caught mid-update, e.g. during a mode change, so it's fetched again after a short
+    /// delay (see [`should_retry_status`]).
+    fn update_warp_status(&mut self) {
+        let mut result = self.warp_client.get_status_with_stats_sync();
+        let mut retries = 0;
+        while should_retry_status(&result, retries) {
+            retries += 1;
+            std::thread::sleep(STATUS_RETRY_DELAY);
+            result = self.warp_client.get_status_with_stats_sync();
+        }
+        self.handle_status_with_stats_result(result);
+    }
+
+    /// Update the state of [`App`] from a status command that also fetched statistics,
+    /// recording the latency for the sparkline
+    pub fn handle_status_with_stats_result(
+        &mut self,
+        result: WarpResult<(WarpInfo, Option<WarpStats>)>,
+    ) {
+        match result {
+            Ok((info, stats)) => {
+                if info.is_connected()
+                    && let Some(latency) = stats.as_ref().and_then(|stats| stats.latency_ms)
+                {
+                    self.latency_history.push(latency);
+                }
+                self.last_stats = stats;
+                self.handle_status_result(Ok(info));
+            }
+            Err(e) => self.handle_status_result(Err(e)),
+        }
+    }
+
+    /// Update the state of [`App`] from the result of a status command
+    pub fn handle_status_result(&mut self, result: WarpResult<WarpInfo>) {
+        match result {
+            Ok(info) => {
+                self.set_warp_info(info);
+                self.last_error = None;
+                self.cli_missing = false;
+            }
+            Err(e) => {
+                // If we can't get status, reset to default
+                self.set_warp_info(WarpInfo::default());
+                self.cli_missing = matches!(e, WarpError::CommandNotFound);
+                self.record_error(format!("Status update failed: {}", e));
+            }
+        }
+        // Reset the refresh timer whenever we update status
+        self.last_refresh = Instant::now();
+    }
+
+    /// Replace the status, logging what changed, tracking how long a connect or disconnect
+    /// has been in progress and notifying the desktop when WARP connects or disconnects
+    fn set_warp_info(&mut self, info: WarpInfo) {
+        if let Some(message) = notification::status_change_message(&self.warp_info.status, &info) {
+            notification::notify(&message);
+        }
+        // Unknown means the status was never fetched or the fetch failed, which is logged
+        // as an error instead
+        if self.warp_info.status != WarpStatus::Unknown && info.status != WarpStatus::Unknown {
+            for change in self.warp_info.diff(&info) {
+                self.log.push(change.to_string());
+            }
+        }
+        self.initial_load_done = true;
+        // Without an account type the device may be unregistered, which only
+        // `registration show` can tell
+        if info.account_type.is_some() {
+            self.registration_missing = Some(false);
+        } else if info.status != WarpStatus::Unknown
+            && self.registration_missing.is_none()
+            && self.registration_check.is_none()
+        {
+            self.registration_check =
+                Some(self.spawn_with_client(|client| client.get_registration_sync()));
+        }
+        let dropped = self.warp_info.is_connected()
+            && info.status == WarpStatus::Disconnected
+            && info
+                .reason
+                .as_deref()
+                .is_some_and(|reason| !is_user_initiated_disconnect(reason));
+
+        self.transition_since = if !info.is_transitioning() {
+            None
+        } else if info.status == self.warp_info.status {
+            self.transition_since.or_else(|| Some(Instant::now()))
+        } else {
+            Some(Instant::now())
+        };
+        if !info.is_connected() {
+            self.latency_history.clear();
+            self.last_stats = None;
+            self.connected_since = None;
+        } else if !self.warp_info.is_connected() {
+            self.connected_since = Some(Instant::now());
+        }
+        self.warp_info = info;
+
+        if dropped {
+            self.auto_reconnect_after_drop();
+        }
+    }
+
+    /// Reconnect once after WARP dropped, if enabled and not done within the cooldown
+    fn auto_reconnect_after_drop(&mut self) {
+        if !self.auto_reconnect || self.pending.is_some() || self.is_locked() {
+            return;
+        }
+
+        let now = Instant::now();
+        if !reconnect_allowed(self.last_reconnect, now) {
+            self.log
+                .push("WARP dropped, not reconnecting again so soon after the last attempt");
+            return;
+        }
+        self.last_reconnect = Some(now);
+
+        let reason = self.warp_info.reason.as_deref().unwrap_or("unknown reason");
+        self.log.push(format!(
+            "WARP dropped ({}), reconnecting: warp-cli connect",
+            reason
+        ));
+        self.start_operation("Reconnecting…", "Reconnect", |client| {
+            client
+                .connect_sync()
+                .map(|outcome| Completion::Refresh(Some(outcome.to_string())))
+        });
+    }
+
+    /// Is auto-reconnect enabled?
+    pub fn is_auto_reconnect_enabled(&self) -> bool {
+        self.auto_reconnect
+    }
+
+    /// Turn auto-reconnect on or off
+    fn handle_toggle_auto_reconnect(&mut self) {
+        self.auto_reconnect = !self.auto_reconnect;
+        self.log.push(if self.auto_reconnect {
+            "Auto-reconnect enabled"
+        } else {
+            "Auto-reconnect disabled"
+        });
+    }
+
+    /// How long the status has been Connecting or Disconnecting, `None` while it's settled
+    pub fn transition_elapsed(&self) -> Option<Duration> {
+        self.transition_since.map(|since| since.elapsed())
+    }
+
+    /// Has the status been Connecting or Disconnecting for longer than the configured
+    /// threshold, suggesting warp-cli is stuck?
+    pub fn is_transition_stale(&self) -> bool {
+        self.transition_elapsed()
+            .is_some_and(|elapsed| elapsed.as_secs() >= self.config.stale_transition_secs)
+    }
+
+    /// Is a connect or disconnect in progress and not yet considered stuck?
+    ///
+    /// Connecting and disconnecting wait for it to settle, but a stuck one may be retried.
+    fn is_transition_active(&self) -> bool {
+        self.warp_info.is_transitioning() && !self.is_transition_stale()
+    }
+
+    /// Handle refresh command
+    fn handle_refresh(&mut self) {
+        self.log.push("warp-cli status");
+        self.request_refresh();
+    }
+
+    /// Handle connect command
+    fn handle_connect(&mut self) {
+        if self.debounce_connect_command("Connect") {
+            return;
+        }
+        // Wait for an ongoing connect or disconnect to settle
+        if self.pending.is_some() || self.cli_missing || self.is_transition_active() {
+            return;
+        }
+        if self.refuse_if_locked("Connecting") {
+            return;
+        }
+
+        self.last_connect_command = Some(Instant::now());
+        self.log.push("warp-cli connect");
+        self.start_operation("Connecting…", "Connect", |client| {
+            client
+                .connect_sync()
+                .map(|outcome| Completion::Refresh(Some(outcome.to_string())))
+        });
+    }
+
+    /// Log and return true if `action` comes too soon after the last connect or
+    /// disconnect, see [`connect_command_allowed`]
+    fn debounce_connect_command(&mut self, action: &str) -> bool {
+        if connect_command_allowed(self.last_connect_command, Instant::now()) {
+            return false;
+        }
+        self.log
+            .push(format!("{} ignored — command in progress", action));
+        true
+    }
+
+    /// Handle disconnect command
+    fn handle_disconnect(&mut self) {
+        if self.debounce_connect_command("Disconnect") {
+            return;
+        }
+        // Wait for an ongoing connect or disconnect to settle
+        if self.pending.is_some() || self.cli_missing || self.is_transition_active() {
+            return;
+        }
+        if self.refuse_if_locked("Disconnecting") {
+            return;
+        }
+
+        self.last_connect_command = Some(Instant::now());
+        self.log.push("warp-cli disconnect");
+        self.start_operation("Disconnecting…", "Disconnect", |client| {
+            client
+                .disconnect_sync()
+                .map(|outcome| Completion::Refresh(Some(outcome.to_string())))
+        });
+    }
+
+    /// Handle a key pressed while the confirmation dialog is shown
+    fn handle_dialog_key(&mut self, code: KeyCode) {
+        let Some(action) = self.pending_action else {
+            return;
+        };
+        match (action, code) {
+            (_, KeyCode::Esc) => self.pending_action = None,
+            (PendingAction::QuitWhileConnected, KeyCode::Char('d') | KeyCode::Char('D')) => {
+                self.pending_action = None;
+                self.disconnect_and_quit();
+            }
+            (PendingAction::QuitWhileConnected, KeyCode::Char('l') | KeyCode::Char('L')) => {
+                self.pending_action = None;
+                self.quit();
+            }
+            (PendingAction::Override, KeyCode::Char('a') | KeyCode::Char('A')) => {
+                self.pending_action = None;
+                self.apply_override();
+            }
+            (PendingAction::Override, KeyCode::Char('c') | KeyCode::Char('C')) => {
+                self.pending_action = None;
+                self.clear_override();
+            }
+            (PendingAction::Register, KeyCode::Char('y') | KeyCode::Char('Y')) => {
+                self.pending_action = None;
+                self.register();
+            }
+            (PendingAction::ResetSettings, KeyCode::Char('y') | KeyCode::Char('Y')) => {
+                self.pending_action = None;
+                self.reset_settings();
+            }
+            _ => {}
+        }
+    }
+
+    /// Ask for confirmation before touching the admin override
+    fn handle_override(&mut self) {
+        if self.pending.is_some() || self.cli_missing {
+            return;
+        }
+        if self.refuse_if_locked("Overriding") {
+            return;
+        }
+        self.pending_action = Some(PendingAction::Override);
+    }
+
+    /// Ask for confirmation before registering, only while no registration exists
+    fn handle_register(&mut self) {
+        if self.pending.is_some() || self.cli_missing || !self.is_registration_missing() {
+            return;
+        }
+        if self.refuse_if_locked("Registering") {
+            return;
+        }
+        self.pending_action = Some(PendingAction::Register);
+    }
+
+    /// Create a new registration
+    fn register(&mut self) {
+        self.log.push("warp-cli registration new");
+        self.start_operation("Registering device…", "Registration", |client| {
+            client
+                .create_registration_sync()
+                .map(Completion::Registered)
+        });
+    }
+
+    /// Ask for confirmation before resetting the settings to their defaults
+    fn handle_reset_settings(&mut self) {
+        if self.pending.is_some() || self.cli_missing {
+            return;
+        }
+        if self.refuse_if_locked("Resetting settings") {
+            return;
+        }
+        self.pending_action = Some(PendingAction::ResetSettings);
+    }
+
+    /// Reset the WARP settings to their defaults
+    fn reset_settings(&mut self) {
+        self.log.push("warp-cli settings reset");
+        self.start_operation("Resetting settings…", "Reset", |client| {
+            client
+                .reset_settings_sync()
+                .map(|()| Completion::Refresh(Some("Settings reset to defaults".to_string())))
+        });
+    }
+
+    /// Apply the override policy from the config
+    fn apply_override(&mut self) {
+        let Some(policy) = self.config.override_policy.clone() else {
+            self.record_error(
+                "No override policy configured, set override_policy in config.toml".to_string(),
+            );
+            return;
+        };
+
+        self.log.push(format!("warp-cli override {}", policy));
+        self.start_operation("Applying override…", "Override", move |client| {
+            client
+                .set_override_sync(&policy)
+                .map(|()| Completion::Refresh(Some("Override applied".to_string())))
+        });
+    }
+
+    /// Clear the admin override
+    fn clear_override(&mut self) {
+        self.log.push("warp-cli override clear");
+        self.start_operation("Clearing override…", "Clear override", |client| {
+            client
+                .clear_override_sync()
+                .map(|()| Completion::Refresh(Some("Override cleared".to_string())))
+        });
+    }
+
+    /// Switch to `tab`, fetching the settings when the settings tab is opened
+    fn select_tab(&mut self, tab: Tab) {
+        if tab == Tab::Settings && self.active_tab != Tab::Settings {
+            self.start_settings_check();
+        }
+        self.active_tab = tab;
+    }
+
+    /// Are the WARP settings locked by the organization managing this device?
+    ///
+    /// Read from `switch_locked` in the settings, which are fetched in the background at
+    /// startup and after each command, and reloaded from the settings tab.
+    pub fn is_locked(&self) -> bool {
+        matches!(&self.settings, Some(Ok(settings)) if settings.switch_locked)
+    }
+
+    /// Log that `action` isn't allowed if the settings are locked, returning whether they are
+    fn refuse_if_locked(&mut self, action: &str) -> bool {
+        if !self.is_locked() {
+            return false;
+        }
+        self.log.push(format!(
+            "{} is disabled: managed by your organization",
+            action
+        ));
+        true
+    }
+
+    /// Tab shown in the main area
+    pub fn active_tab(&self) -> Tab {
+        self.active_tab
+    }
+
+    /// Settings shown in the settings tab, `None` until the tab is first opened
+    pub fn settings(&self) -> Option<Result<&WarpSettingsInfo, &str>> {
+        self.settings
+            .as_ref()
+            .map(|settings| settings.as_ref().map_err(String::as_str))
+    }
+
+    /// Copy the status to the clipboard, or log it if there's no clipboard
+    fn copy_status(&mut self) {
+        let text = self.warp_info.to_string();
+        match clipboard::copy(&text) {
+            Ok(()) => self.toast = Some(("Copied!".to_string(), Instant::now())),
+            Err(e) => {
+                self.log
+                    .push(format!("Clipboard unavailable ({}), status:", e));
+                for line in text.lines() {
+                    self.log.push(line);
+                }
+            }
+        }
+    }
+
+    /// Open the settings page of the organization in the browser, or log its URL if there's
+    /// no browser
+    ///
+    /// The organization is looked up in the background, since it takes a warp-cli call.
+    fn open_settings_page(&mut self) {
+        if self.pending.is_some() {
+            return;
+        }
+
+        self.start_operation("Opening settings page…", "Open settings page", |client| {
+            let registration = client.get_registration_sync().ok();
+            let url = browser::settings_url(registration.as_ref());
+            Ok(Completion::Log(match browser::open(&url) {
+                Ok(()) => format!("Opened {}", url),
+                Err(e) => format!("Browser unavailable ({}), open {}", e, url),
+            }))
+        });
+    }
+
+    /// The toast currently shown, if it hasn't expired yet
+    pub fn toast(&self) -> Option<&str> {
+        self.toast
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < TOAST_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Set running to false to quit the application.
+    fn quit(&mut self) {
+        self.running = false;
+    }
+
+    /// Quit, first asking whether to disconnect if configured to and WARP is connected
+    fn request_quit(&mut self) {
+        if self.config.confirm_quit_when_connected && self.warp_info.is_connected() {
+            self.pending_action = Some(PendingAction::QuitWhileConnected);
+        } else {
+            self.quit();
+        }
+    }
+
+    /// Disconnect from WARP, then quit
+    fn disconnect_and_quit(&mut self) {
+        self.log.push("warp-cli disconnect");
+        match self
+            .warp_client
+            .disconnect_and_wait_sync(QUIT_DISCONNECT_TIMEOUT)
+        {
+            Ok(WarpStatus::Disconnected) => self.warp_info.status = WarpStatus::Disconnected,
+            Ok(status) => {
+                self.log.push(format!("Still {} when quitting", status));
+                self.warp_info.status = status;
+            }
+            Err(e) => self.record_error(format!("Disconnect failed: {}", e)),
+        }
+        self.quit();
+    }
+
+    /// Handle mode selection
+    fn handle_mode_selection(&mut self) {
+        // Toggle mode selection UI
+        if self.mode_selection.is_none() {
+            if self.cli_missing || self.refuse_if_locked("Changing the mode") {
+                return;
+            }
+            self.mode_filter.clear();
+            self.mode_selection = Some(ListState::default());
+            self.reset_mode_selection();
+        } else {
+            self.mode_selection = None;
+        }
+    }
+
+    /// Highlight the current mode among the modes matching the filter,
+    /// falling back to the mode selected last time, then to the first match
+    fn reset_mode_selection(&mut self) {
+        let modes = filter_modes(&self.mode_filter);
+        let position = |mode: &WarpMode| modes.iter().position(|m| m == mode);
+        let selected_idx = self
+            .warp_info
+            .mode
+            .as_ref()
+            .and_then(position)
+            .or_else(|| self.last_mode.as_ref().and_then(position))
+            .or(if modes.is_empty() { None } else { Some(0) });
+
+        if let Some(state) = &mut self.mode_selection {
+            state.select(selected_idx);
+        }
+    }
+
+    /// Add a character typed into the mode selection to the filter
+    fn push_mode_filter(&mut self, c: char) {
+        self.mode_filter.push(c);
+        self.reset_mode_selection();
+    }
+
+    /// Remove the last character of the mode filter
+    fn pop_mode_filter(&mut self) {
+        self.mode_filter.pop();
+        self.reset_mode_selection();
+    }
+
+    /// Esc in the mode selection clears the filter first, then closes the list
+    fn handle_mode_selection_escape(&mut self) {
+        if self.mode_filter.is_empty() {
+            self.mode_selection = None;
+        } else {
+            self.mode_filter.clear();
+            self.reset_mode_selection();
+        }
+    }
+
+    /// Text typed to filter the mode selection
+    pub fn mode_filter(&self) -> &str {
+        &self.mode_filter
+    }
+
+    /// Handle mode selection key
+    fn handle_mode_select(&mut self) {
+        let Some(selected) = self.mode_selection.as_ref().and_then(ListState::selected) else {
+            return;
+        };
+        self.mode_selection = None;
+        // The list may have changed since the entry was highlighted; a stale
+        // selection closes the picker without applying anything
+        match filter_modes(&self.mode_filter).get(selected) {
+            Some(mode) => self.apply_mode(mode.clone()),
+            None => tracing::warn!("Ignoring stale mode selection {}", selected),
+        }
+    }
+
+    /// Switch to `mode` in the background, unless another command is running
+    fn apply_mode(&mut self, mode: WarpMode) {
+        if self.pending.is_some() {
+            return;
+        }
+        let cli_mode = mode.as_cli_str();
+        self.last_mode = Some(mode);
+        self.log.push(format!("warp-cli mode {}", cli_mode));
+        self.start_operation("Applying mode…", "Set mode", move |client| {
+            client
+                .set_mode_sync(cli_mode)
+                .map(|()| Completion::Refresh(None))
+        });
+    }
+
+    /// Apply the favorite bound to `key`; keys without one are ignored
+    fn handle_favorite(&mut self, key: KeyCode) {
+        let Some(action) = self.favorite_for(key).cloned() else {
+            return;
+        };
+        match action {
+            FavoriteAction::SetMode(mode) => {
+                if self.cli_missing || self.refuse_if_locked("Changing the mode") {
+                    return;
+                }
+                self.apply_mode(mode);
+            }
+        }
+    }
+
+    /// Flip the WARP enabled flag
+    fn handle_toggle_warp(&mut self) {
+        if self.pending.is_some() || self.cli_missing {
+            return;
+        }
+
+        if self.refuse_if_locked("Enabling or disabling WARP") {
+            return;
+        }
+
+        let enabled = !self.warp_info.warp_enabled;
+        self.log
+            .push(format!("warp-cli settings warp-enabled {}", enabled));
+        let action = if enabled {
+            "Enable WARP"
+        } else {
+            "Disable WARP"
+        };
+        self.start_operation("Applying setting…", action, move |client| {
+            client
+                .set_warp_enabled_sync(enabled)
+                .map(|()| Completion::Refresh(None))
+        });
+    }
+
+    /// Flip the Gateway enabled flag
+    fn handle_toggle_gateway(&mut self) {
+        if self.pending.is_some() || self.cli_missing {
+            return;
+        }
+
+        if self.refuse_if_locked("Enabling or disabling Gateway") {
+            return;
+        }
+
+        let enabled = !self.warp_info.gateway_enabled;
+        self.log
+            .push(format!("warp-cli settings gateway-enabled {}", enabled));
+        let action = if enabled {
+            "Enable Gateway"
+        } else {
+            "Disable Gateway"
+        };
+        self.start_operation("Applying setting…", action, move |client| {
+            client
+                .set_gateway_enabled_sync(enabled)
+                .map(|()| Completion::Refresh(None))
+        });
+    }
+
+    /// Trust the Wi-Fi network this device is on, or stop trusting it if it already is
+    fn handle_toggle_network_trust(&mut self) {
+        if self.pending.is_some() || self.cli_missing {
+            return;
+        }
+        if self.refuse_if_locked("Changing the trusted networks") {
+            return;
+        }
+
+        self.start_operation("Updating trusted networks…", "Trust network", |client| {
+            let ssid = client.current_ssid_sync()?;
+            let message = if client.list_trusted_ssids_sync()?.contains(&ssid) {
+                client.remove_trusted_ssid_sync(&ssid)?;
+                format!("{} is no longer trusted", ssid)
+            } else {
+                client.add_trusted_ssid_sync(&ssid)?;
+                format!("Trusted {}, WARP turns off on it automatically", ssid)
+            };
+            Ok(Completion::Refresh(Some(message)))
+        });
+    }
+
+    /// Toggle the diagnostics panel, running the connectivity check when opening it
+    fn handle_diagnostics(&mut self) {
+        if self.diagnostics.is_some() {
+            self.diagnostics = None;
+            return;
+        }
+        if self.pending.is_some() {
+            return;
+        }
+
+        self.log.push("warp-cli debug connectivity-check");
+        self.start_operation("Running diagnostics…", "Connectivity check", |client| {
+            client
+                .run_connectivity_check_sync()
+                .map(Completion::Diagnostics)
+        });
+    }
+
+    /// Scroll the diagnostics panel by `delta` lines
+    fn scroll_diagnostics(&mut self, delta: i32) {
+        if let Some(diagnostics) = &mut self.diagnostics {
+            let max_scroll = diagnostics.output.lines().count().saturating_sub(1) as i32;
+            diagnostics.scroll = (diagnostics.scroll as i32 + delta).clamp(0, max_scroll) as u16;
+        }
+    }
+
+    /// Output of the connectivity check, while the diagnostics panel is shown
+    pub fn diagnostics_output(&self) -> Option<&str> {
+        self.diagnostics
+            .as_ref()
+            .map(|diagnostics| diagnostics.output.as_str())
+    }
+
+    /// Toggle the raw status panel
+    ///
+    /// The client only keeps the unparsed status output while the panel is shown, so it's
+    /// refreshed right away when opening it.
+    fn handle_raw_status_panel(&mut self) {
+        if self.raw_status_scroll.is_some() {
+            self.raw_status_scroll = None;
+            self.warp_client.set_capture_raw_status(false);
+            return;
+        }
+        self.raw_status_scroll = Some(0);
+        self.warp_client.set_capture_raw_status(true);
+        self.request_refresh();
+    }
+
+    /// Scroll the raw status panel by `delta` lines
+    fn scroll_raw_status(&mut self, delta: i32) {
+        let lines = self
+            .warp_info
+            .raw
+            .as_deref()
+            .map_or(0, |raw| raw.lines().count());
+        if let Some(scroll) = &mut self.raw_status_scroll {
+            let max_scroll = lines.saturating_sub(1) as i32;
+            *scroll = (*scroll as i32 + delta).clamp(0, max_scroll) as u16;
+        }
+    }
+
+    /// Is the raw status panel shown?
+    pub fn is_raw_status_shown(&self) -> bool {
+        self.raw_status_scroll.is_some()
+    }
+
+    /// Device ID shown in the registration panel, `None` while it's closed or failed to load
+    pub fn registration_panel_device_id(&self) -> Option<&str> {
+        self.registration
+            .as_ref()
+            .and_then(|panel| panel.info.as_ref().ok())
+            .and_then(|info| info.device_id.as_deref())
+    }
+
+    /// Toggle the registration panel, fetching the registration in the background when
+    /// opening it
+    fn handle_registration_panel(&mut self) {
+        if self.registration.is_some() {
+            self.registration = None;
+            return;
+        }
+        if self.pending.is_some() {
+            return;
+        }
+
+        self.start_operation(
+            "Fetching registration…",
+            "Registration details",
+            |client| panel_result(client.get_registration_sync()).map(Completion::Registration),
+        );
+    }
+
+    /// Toggle the DNS statistics panel, fetching the statistics in the background when
+    /// opening it
+    fn handle_dns_stats_panel(&mut self) {
+        if self.dns_stats.is_some() {
+            self.dns_stats = None;
+            return;
+        }
+        if self.pending.is_some() {
+            return;
+        }
+
+        self.start_operation("Fetching DNS statistics…", "DNS statistics", |client| {
+            panel_result(client.get_dns_stats_sync()).map(Completion::DnsStats)
+        });
+    }
+
+    /// DNS statistics shown in the panel, `None` while it's closed
+    pub fn dns_stats(&self) -> Option<Result<&DnsStats, &str>> {
+        self.dns_stats
+            .as_ref()
+            .map(|stats| stats.as_ref().map_err(String::as_str))
+    }
+
+    /// Toggle the tunnel statistics panel, fetching the statistics in the background when
+    /// opening it
+    fn handle_tunnel_stats_panel(&mut self) {
+        if self.tunnel_stats.is_some() {
+            self.tunnel_stats = None;
+            return;
+        }
+        if self.pending.is_some() {
+            return;
+        }
+
+        self.start_operation(
+            "Fetching tunnel statistics…",
+            "Tunnel statistics",
+            |client| panel_result(client.get_tunnel_stats_sync()).map(Completion::TunnelStats),
+        );
+    }
+
+    /// Tunnel statistics shown in the panel, `None` while it's closed
+    pub fn tunnel_stats(&self) -> Option<Result<&TunnelStats, &str>> {
+        self.tunnel_stats
+            .as_ref()
+            .map(|stats| stats.as_ref().map_err(String::as_str))
+    }
+
+    /// Handle families mode selection
+    fn handle_families_selection(&mut self) {
+        // Toggle families mode selection UI
+        if self.families_selection.is_none() {
+            if self.pending.is_some()
+                || self.cli_missing
+                || self.refuse_if_locked("Changing the DNS families mode")
+            {
+                return;
+            }
+            let mut state = ListState::default();
+            state.select(Some(0));
+            self.families_selection = Some(state);
+        } else {
+            self.families_selection = None;
+        }
+    }
+
+    /// Handle families mode selection key
+    fn handle_families_select(&mut self) {
+        if let Some(list_state) = &mut self.families_selection
+            && let Some(selected) = list_state.selected()
+        {
+            self.families_selection = None;
+            let Some(mode) = FamiliesMode::ALL
+                .get(selected)
+                .map(FamiliesMode::as_cli_str)
+            else {
+                return;
+            };
+            if self.pending.is_some() {
+                return;
+            }
+            self.log.push(format!("warp-cli dns families {}", mode));
+            self.start_operation(
+                "Applying families mode…",
+                "Set families mode",
+                move |client| {
+                    client
+                        .set_families_mode_sync(mode)
+                        .map(|()| Completion::Refresh(None))
+                },
+            );
+        }
+    }
+
+    /// Handle tunnel protocol selection
+    fn handle_protocol_selection(&mut self) {
+        // Toggle tunnel protocol selection UI
+        if self.protocol_selection.is_none() {
+            if self.cli_missing || self.refuse_if_locked("Changing the tunnel protocol") {
+                return;
+            }
+
+            // Preselect the active protocol so it stands out, as of the last settings fetch
+            self.current_protocol = match &self.settings {
+                Some(Ok(settings)) => settings.tunnel_protocol,
+                _ => None,
+            };
+            let selected_idx = self
+                .current_protocol
+                .and_then(|current| TunnelProtocol::ALL.iter().position(|p| *p == current))
+                .unwrap_or(0);
+
+            let mut state = ListState::default();
+            state.select(Some(selected_idx));
+            self.protocol_selection = Some(state);
+        } else {
+            self.protocol_selection = None;
+        }
+    }
+
+    /// Handle tunnel protocol selection key
+    fn handle_protocol_select(&mut self) {
+        if let Some(list_state) = &mut self.protocol_selection
+            && let Some(selected) = list_state.selected()
+        {
+            self.protocol_selection = None;
+            let Some(protocol) = TunnelProtocol::ALL
+                .get(selected)
+                .map(TunnelProtocol::as_cli_str)
+            else {
+                return;
+            };
+            if self.pending.is_some() {
+                return;
+            }
+            self.log
+                .push(format!("warp-cli tunnel protocol set {}", protocol));
+            self.start_operation(
+                "Applying protocol…",
+                "Set tunnel protocol",
+                move |client| {
+                    client
+                        .set_tunnel_protocol_sync(protocol)
+                        .map(|()| Completion::Refresh(None))
+                },
+            );
+        }
+    }
+
+    /// Handle profile selection
+    fn handle_profile_selection(&mut self) {
+        // Toggle profile selection UI
+        if self.profile_selection.is_none() {
+            if self.pending.is_some()
+                || self.cli_missing
+                || self.refuse_if_locked("Switching profiles")
+            {
+                return;
+            }
+            if self.config.profiles.is_empty() {
+                self.log
+                    .push("No profiles defined, add [[profiles]] tables to config.toml");
+                return;
+            }
+
+            // Preselect the active profile
+            let selected_idx = self
+                .active_profile
+                .as_deref()
+                .and_then(|name| self.config.profiles.iter().position(|p| p.name == name))
+                .unwrap_or(0);
+
+            let mut state = ListState::default();
+            state.select(Some(selected_idx));
+            self.profile_selection = Some(state);
+        } else {
+            self.profile_selection = None;
+        }
+    }
+
+    /// Handle profile selection key
+    fn handle_profile_select(&mut self) {
+        if let Some(list_state) = &mut self.profile_selection
+            && let Some(selected) = list_state.selected()
+        {
+            self.profile_selection = None;
+            if let Some(profile) = self.config.profiles.get(selected).cloned() {
+                self.apply_profile(&profile);
+            }
+        }
+    }
+
+    /// Apply the settings of `profile` together in the background
+    ///
+    /// If one of them fails, the ones applied before it are undone and the failure is
+    /// reported as [`WarpError::BatchFailed`].
+    pub fn apply_profile(&mut self, profile: &Profile) {
+        if self.pending.is_some() || self.cli_missing {
+            return;
+        }
+        if self.refuse_if_locked("Switching profiles") {
+            return;
+        }
+        let batch = match profile_batch(profile) {
+            Ok(batch) => batch,
+            Err(e) => {
+                self.record_error(format!("Profile {}: {}", profile.name, e));
+                return;
+            }
+        };
+
+        self.active_profile = Some(profile.name.clone());
+        if batch == SettingsBatch::default() {
+            self.log.push(format!("Profile {} applied", profile.name));
+            return;
+        }
+        if let Some(mode) = &batch.mode {
+            self.last_mode = Some(mode.clone());
+        }
+        self.log.push(format!("Applying profile {}", profile.name));
+        let applied = format!("Profile {} applied", profile.name);
+        self.start_operation("Applying profile…", "Apply profile", move |client| {
+            client
+                .apply_settings_batch(&batch)
+                .map(|()| Completion::Refresh(Some(applied)))
+        });
+    }
+
+    /// Name of the profile applied last, in this session or a previous one
+    pub fn active_profile(&self) -> Option<&str> {
+        self.active_profile.as_deref()
+    }
+
+    /// The open selection list and its number of entries, `None` if it has no entries
+    fn active_selection(&mut self) -> Option<(&mut ListState, usize)> {
+        let mode_count = filter_modes(&self.mode_filter).len();
+        if let Some(list_state) = &mut self.mode_selection {
+            (mode_count > 0).then_some((list_state, mode_count))
+        } else if let Some(list_state) = &mut self.families_selection {
+            Some((list_state, FamiliesMode::ALL.len()))
+        } else if let Some(list_state) = &mut self.protocol_selection {
+            Some((list_state, TunnelProtocol::ALL.len()))
+        } else if let Some(list_state) = &mut self.profile_selection {
+            let profile_count = self.config.profiles.len();
+            (profile_count > 0).then_some((list_state, profile_count))
+        } else {
+            None
+        }
+    }
+
+    /// Handle selection movement up
+    fn select_previous(&mut self) {
+        let wrap = self.config.wrap_selection;
+        if let Some((list_state, len)) = self.active_selection() {
+            let current = list_state.selected().unwrap_or(0);
+            list_state.select(Some(previous_index(current, len, wrap)));
+        }
+    }
+
+    /// Handle selection movement down
+    fn select_next(&mut self) {
+        let wrap = self.config.wrap_selection;
+        if let Some((list_state, len)) = self.active_selection() {
+            let current = list_state.selected().unwrap_or(0);
+            list_state.select(Some(next_index(current, len, wrap)));
+        }
+    }
+
+    /// Handle selection movement to the first entry
+    fn select_first(&mut self) {
+        if let Some((list_state, _)) = self.active_selection() {
+            list_state.select(Some(0));
+        }
+    }
+
+    /// Handle selection movement to the last entry
+    fn select_last(&mut self) {
+        if let Some((list_state, len)) = self.active_selection() {
+            list_state.select(Some(len - 1));
+        }
+    }
+
+    /// Handle 'g' in a selection list: "gg" jumps to the first entry
+    fn handle_g_press(&mut self, pending_g: bool) {
+        if pending_g {
+            self.select_first();
+        } else {
+            self.pending_g = true;
+        }
+    }
+
+    /// Index of the highlighted entry in the open selection list
+    pub fn selected_index(&self) -> Option<usize> {
+        self.mode_selection
+            .as_ref()
+            .or(self.families_selection.as_ref())
+            .or(self.protocol_selection.as_ref())
+            .or(self.profile_selection.as_ref())
+            .and_then(ListState::selected)
+    }
+
+    /// Highlight entry `index` of the open selection list
+    ///
+    /// The index isn't checked against the list, applying an out-of-range selection
+    /// closes the list without doing anything.
+    pub fn select_index(&mut self, index: usize) {
+        if let Some(list_state) = self
+            .mode_selection
+            .as_mut()
+            .or(self.families_selection.as_mut())
+            .or(self.protocol_selection.as_mut())
+            .or(self.profile_selection.as_mut())
+        {
+            list_state.select(Some(index));
+        }
+    }
+
+    /// Renders the user interface.
+    fn render(&mut self, frame: &mut Frame) {
+        self.spinner_frame = self.spinner_frame.wrapping_add(1);
+
+        self.render_content(frame);
+
+        if let Some(message) = self.toast() {
+            render_toast(frame, message);
+        } else {
+            self.toast = None;
+        }
+
+        if let Some(pending) = &self.pending {
+            render_pending(frame, pending.label, spinner_char(self.spinner_frame));
+        }
+
+        if let Some(action) = self.pending_action {
+            render_dialog(frame, action);
+        }
+
+        if self.show_help {
+            self.render_help(frame);
+        }
+    }
+
+    /// Renders the title, main panel, log and footer.
+    fn render_content(&mut self, frame: &mut Frame) {
+        let area = frame.area();
+        // Recomputed below on every frame, so resizes are picked up
+        self.buttons.clear();
+
+        // Short terminals drop the title and the log to leave room for the content
+        let compact = is_compact(area);
+        let (title_height, content_height) = if compact { (0, 0) } else { (3, 10) };
+
+        // The logs tab shows the log in the main area instead
+        let log_height = if compact || self.active_tab == Tab::Logs {
+            0
+        } else {
+            8
+        };
+        let error = self.error_banner_at(Instant::now()).map(str::to_string);
+        let error_height = if error.is_some() { 1 } else { 0 };
+
+        // Create the layout
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(title_height), // Title and tabs
+                Constraint::Min(content_height),  // Main content/Mode selection
+                Constraint::Length(log_height),   // Log
+                Constraint::Length(error_height), // Error
+                Constraint::Length(1),            // Footer
+            ])
+            .split(area);
+
+        if let Some(error) = error {
+            render_error(frame, chunks[3], &error, self.cli_missing);
+        }
+
+        if log_height > 0 {
+            self.render_log(frame, chunks[2]);
+        }
+        let mut footer = Vec::new();
+        // Other tabs hide the status panel, so the footer keeps the status in view
+        if !compact && self.active_tab != Tab::Status {
+            let status_color = self.theme.color_for(&self.warp_info.status);
+            footer.push(Span::from(self.warp_info.summary()).fg(status_color));
+            footer.push(Span::from(" | ").dark_gray());
+        }
+        if self.warp_client.is_dry_run() {
+            footer.push(Span::from("DRY RUN").yellow().bold());
+            footer.push(Span::from(" | ").dark_gray());
+        }
+        if let Some(status) = self.daemon_status {
+            let color = match status {
+                DaemonStatus::Running => Color::Green,
+                DaemonStatus::Unresponsive => Color::Yellow,
+                DaemonStatus::NotFound => Color::Red,
+            };
+            footer.push(Span::from("Daemon: ").dark_gray());
+            footer.push(Span::from(status.to_string()).fg(color));
+            footer.push(Span::from(" | ").dark_gray());
+        }
+        for (key, action) in &self.favorites {
+            if let KeyCode::F(number) = key {
+                footer.push(Span::from(format!("F{} ", number)).bold());
+                footer.push(Span::from(action.to_string()).dark_gray());
+                footer.push(Span::from(" | ").dark_gray());
+            }
+        }
+        footer.push(Span::from(self.version_label.as_str()).dark_gray());
+        frame.render_widget(
+            Paragraph::new(Line::from(footer).right_aligned()),
+            chunks[4],
+        );
+
+        // Render the title with the tab bar
+        if !compact {
+            let title = Line::from("Cloudflare WARP TUI").bold().blue().centered();
+            let tabs = Tabs::new(
+                Tab::ALL
+                    .iter()
+                    .enumerate()
+                    .map(|(i, tab)| format!("{} {}", i + 1, tab.title())),
+            )
+            .select(self.active_tab.index())
+            .highlight_style(Style::default().bold().reversed())
+            .block(Block::bordered().title(title));
+            frame.render_widget(tabs, chunks[0]);
+        }
+
+        // Show mode selection if active
+        if let Some(mode_selection) = &mut self.mode_selection {
+            let modes = filter_modes(&self.mode_filter);
+            let modes: Vec<&str> = modes.iter().map(|m| m.as_cli_str()).collect();
+            let title = if self.mode_filter.is_empty() {
+                "Select Mode (type to filter)".to_string()
+            } else {
+                format!("Select Mode (filter: {})", self.mode_filter)
+            };
+            render_selection(frame, chunks[1], &title, &modes, mode_selection);
+            return;
+        }
+
+        // Show families mode selection if active
+        if let Some(families_selection) = &mut self.families_selection {
+            let families_modes: Vec<&str> =
+                FamiliesMode::ALL.iter().map(|m| m.as_cli_str()).collect();
+            render_selection(
+                frame,
+                chunks[1],
+                "Select DNS Families Mode",
+                &families_modes,
+                families_selection,
+            );
+            return;
+        }
+
+        // Show tunnel protocol selection if active, marking the active protocol
+        if let Some(protocol_selection) = &mut self.protocol_selection {
+            let protocols: Vec<String> = TunnelProtocol::ALL
+                .iter()
+                .map(|p| {
+                    if Some(*p) == self.current_protocol {
+                        format!("{} (current)", p)
+                    } else {
+                        p.to_string()
+                    }
+                })
+                .collect();
+            let protocols: Vec<&str> = protocols.iter().map(String::as_str).collect();
+            render_selection(
+                frame,
+                chunks[1],
+                "Select Tunnel Protocol",
+                &protocols,
+                protocol_selection,
+            );
+            return;
+        }
+
+        // Show profile selection if active, marking the active profile
+        if let Some(profile_selection) = &mut self.profile_selection {
+            let profiles: Vec<String> = self
+                .config
+                .profiles
+                .iter()
+                .map(|p| {
+                    if self.active_profile.as_deref() == Some(p.name.as_str()) {
+                        format!("{} (active)", p.name)
+                    } else {
+                        p.name.clone()
+                    }
+                })
+                .collect();
+            let profiles: Vec<&str> = profiles.iter().map(String::as_str).collect();
+            render_selection(
+                frame,
+                chunks[1],
+                "Select Profile",
+                &profiles,
+                profile_selection,
+            );
+            return;
+        }
+
+        // Show the diagnostics panel if active
+        if let Some(diagnostics) = &self.diagnostics {
+            render_diagnostics(frame, chunks[1], diagnostics);
+            return;
+        }
+
+        // Show the raw status panel if active
+        if let Some(scroll) = self.raw_status_scroll {
+            render_raw_status(frame, chunks[1], &self.warp_info, scroll);
+            return;
+        }
+
+        // Show the DNS statistics panel if active
+        if let Some(dns_stats) = &self.dns_stats {
+            render_dns_stats(frame, chunks[1], dns_stats);
+            return;
+        }
+
+        // Show the tunnel statistics panel if active
+        if let Some(tunnel_stats) = &self.tunnel_stats {
+            render_tunnel_stats(frame, chunks[1], tunnel_stats);
+            return;
+        }
+
+        // Show the registration panel if active
+        if let Some(registration) = &self.registration {
+            render_registration(frame, chunks[1], registration);
+            return;
+        }
+
+        match self.active_tab {
+            Tab::Status => self.render_status(frame, chunks[1]),
+            Tab::Settings => render_settings(
+                frame,
+                chunks[1],
+                self.settings.as_ref(),
+                self.is_settings_loading(),
+                self.command_timeout(),
+            ),
+            Tab::Stats => self.render_stats(frame, chunks[1]),
+            Tab::Logs => self.render_log(frame, chunks[1]),
+        }
+    }
+
+    /// Renders the status tab: the connection status and the buttons.
+    fn render_status(&mut self, frame: &mut Frame, area: Rect) {
+        if self.cli_missing {
+            render_cli_missing(frame, area);
+            return;
+        }
+
+        if !self.initial_load_done {
+            render_loading(frame, area, spinner_char(self.spinner_frame));
+            return;
+        }
+
+        if self.is_registration_missing() {
+            render_no_registration(frame, area);
+            return;
+        }
+
+        let status_color = self.theme.color_for(&self.warp_info.status);
+
+        // No room for the details or the buttons, just the essentials on one line
+        if is_compact(frame.area()) {
+            frame.render_widget(
+                Paragraph::new(compact_status_line(&self.warp_info))
+                    .style(Style::default().fg(status_color)),
+                area,
+            );
+            return;
+        }
+
+        let status_text = match &self.warp_info.reason {
+            Some(reason) => format!("{} ({})", self.warp_info.status, reason),
+            None => self.warp_info.status.to_string(),
+        };
+
+        let mode_text = match &self.warp_info.mode {
+            Some(mode) => format!("Mode: {}", mode),
+            None => "Mode: N/A".to_string(),
+        };
+
+        let networks_text = if self.warp_info.connected_networks.is_empty() {
+            "Connected Networks: None".to_string()
+        } else if !self.warp_info.networks.is_empty() {
+            self.warp_info
+                .networks
+                .iter()
+                .fold("Connected Networks:".to_string(), |text, network| {
+                    format!("{}\n  {}", text, network_line(network))
+                })
+        } else {
+            self.warp_info
+                .connected_networks
+                .iter()
+                .fold("Connected Networks:".to_string(), |text, network| {
+                    format!("{}\n  • {}", text, network)
+                })
+        };
+
+        let override_text = match &self.warp_info.override_state {
+            Some(state) => format!("Override: {}\n", state),
+            None => String::new(),
+        };
+
+        let details_text = if self.show_details {
+            format!(
+                "Endpoint: {}\nTunnel IP: {}\n",
+                self.warp_info.endpoint.as_deref().unwrap_or("N/A"),
+                self.warp_info.tunnel_ip.as_deref().unwrap_or("N/A"),
+            )
+        } else {
+            "Details: press 'i' to show\n".to_string()
+        };
+
+        // Errors are shown in the error area below
+        let error_text = if self.is_transition_stale() {
+            stale_transition_warning(&self.warp_info.status)
+        } else {
+            String::new()
+        };
+
+        let text = format!(
+            "Account Type: {}\n\
+            WARP Enabled: {}\n\
+            Gateway Enabled: {}\n\
+            {}\n\
+            {}{}\
+            Auto-refresh: {}ms{}\n\
+            {}\n\
+            Press '?' for help",
+            self.warp_info.account_type.as_deref().unwrap_or("N/A"),
+            if self.warp_info.warp_enabled {
+                "Yes"
+            } else {
+                "No"
+            },
+            if self.warp_info.gateway_enabled {
+                "Yes"
+            } else {
+                "No"
+            },
+            networks_text,
+            override_text,
+            details_text,
+            self.current_refresh_interval(),
+            if self.auto_refresh_paused {
+                " (PAUSED)".to_string()
+            } else {
+                format!(
+                    ", next refresh in {:.1}s",
+                    refresh_countdown(self.last_refresh.elapsed(), self.refresh_interval_ms)
+                        .as_secs_f64()
+                )
+            },
+            error_text
+        );
+
+        // Render main content
+        let block = Block::bordered();
+        let sparkline_height = if self.latency_history.is_empty() {
+            0
+        } else {
+            SPARKLINE_HEIGHT
+        };
+        let [text_area, sparkline_area, buttons_area] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(sparkline_height),
+            Constraint::Length(BUTTON_HEIGHT),
+        ])
+        .areas(block.inner(area));
+        frame.render_widget(block.style(Style::default().fg(status_color)), area);
+        // The mode gets its own color so DNS-only modes stand out from tunneled ones
+        let mode_color = self
+            .warp_info
+            .mode
+            .as_ref()
+            .map_or(status_color, color_for_mode);
+        let mut lines = Vec::new();
+        if self.is_locked() {
+            lines.push(Line::from("Managed by your organization").bold().yellow());
+        }
+        lines.push(Line::from(format!("Status: {}", status_text)));
+        if let Some(connected_for) = self.connected_for() {
+            lines.push(Line::from(format!(
+                "Connected for {}",
+                format_elapsed(connected_for)
+            )));
+        }
+        lines.push(Line::from(mode_text).style(Style::default().fg(mode_color)));
+        lines.extend(text.lines().map(|line| Line::from(line.to_string())));
+        frame.render_widget(
+            Paragraph::new(lines).style(Style::default().fg(status_color)),
+            text_area,
+        );
+        if !self.latency_history.is_empty() {
+            self.render_latency_sparkline(frame, sparkline_area);
+        }
+
+        self.buttons = layout_buttons(buttons_area);
+        for (button, area) in &self.buttons {
+            frame.render_widget(
+                Paragraph::new(Line::from(button.label()).centered()).block(Block::bordered()),
+                *area,
+            );
+        }
+    }
+
+    /// Renders the stats tab: the tunnel statistics and the latency history.
+    fn render_stats(&self, frame: &mut Frame, area: Rect) {
+        let text = match &self.last_stats {
+            Some(stats) => format!(
+                "Endpoint: {}\n\
+                Latency: {}\n\
+                Packet Loss: {}\n\
+                Sent: {} bytes\n\
+                Received: {} bytes",
+                stats.endpoint.as_deref().unwrap_or("N/A"),
+                stats
+                    .latency_ms
+                    .map(|ms| format!("{} ms", ms))
+                    .unwrap_or_else(|| "N/A".to_string()),
+                stats
+                    .loss_percent
+                    .map(|loss| format!("{:.1}%", loss))
+                    .unwrap_or_else(|| "N/A".to_string()),
+                stats.bytes_sent,
+                stats.bytes_received,
+            ),
+            None => "No statistics, WARP is not connected".to_string(),
+        };
+
+        let [text_area, sparkline_area] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(SPARKLINE_HEIGHT)])
+                .areas(area);
+        frame.render_widget(
+            Paragraph::new(text).block(Block::bordered().title("Tunnel Statistics")),
+            text_area,
+        );
+        self.render_latency_sparkline(frame, sparkline_area);
+    }
+
+    /// Renders the recent latency samples as a sparkline.
+    fn render_latency_sparkline(&self, frame: &mut Frame, area: Rect) {
+        let samples: Vec<u64> = self.latency_history.samples().collect();
+        let title = match samples.last() {
+            Some(latest) => format!("Latency ({} ms)", latest),
+            None => "Latency".to_string(),
+        };
+        frame.render_widget(
+            Sparkline::default()
+                .block(Block::bordered().title(title))
+                .data(&samples),
+            area,
+        );
+    }
+
+    /// Renders the most recent log entries.
+    fn render_log(&self, frame: &mut Frame, area: Rect) {
+        let height = area.height.saturating_sub(2) as usize;
+        let lines: Vec<Line> = self.log.visible(height).map(Line::from).collect();
+
+        let title = if self.log.scroll_offset() > 0 {
+            format!("Log (scrolled up {} lines)", self.log.scroll_offset())
+        } else {
+            "Log".to_string()
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+
+    /// Renders the keybinding help as a popup centered over the screen.
+    fn render_help(&self, frame: &mut Frame) {
+        let configurable = Action::ALL
+            .into_iter()
+            .filter(|action| *action != Action::Quit)
+            .map(|action| {
+                (
+                    self.keymap.key_for(action).to_string(),
+                    action.description(),
+                )
+            });
+        let fixed = KEYBINDINGS
+            .iter()
+            .map(|(key, description)| (key.to_string(), *description));
+        let quit = (
+            format!("{}/Esc/Ctrl-C", self.keymap.key_for(Action::Quit)),
+            Action::Quit.description(),
+        );
+        let entries: Vec<(String, &str)> = configurable
+            .chain(fixed)
+            .chain(std::iter::once(quit))
+            .collect();
+        let key_width = entries
+            .iter()
+            .map(|(key, _)| key.chars().count() + 1)
+            .max()
+            .unwrap_or_default();
+        let lines: Vec<Line> = entries
+            .into_iter()
+            .map(|(key, description)| {
+                Line::from(format!("{:<width$}{}", key, description, width = key_width))
+            })
+            .collect();
+
+        let area = centered_rect(frame.area(), 50, lines.len() as u16 + 2);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .title("Help")
+                    .title_bottom(Line::from("Press any key to close").centered()),
+            ),
+            area,
+        );
+    }
+
+    /// Reads the crossterm events and updates the state of [`App`].
+    ///
+    /// If your application needs to perform work in between handling events, you can use the
+    /// [`event::poll`] function to check if there are any events available with a timeout.
+    fn handle_crossterm_events(&mut self) -> Result<()> {
+        // Poll for events with a small timeout to avoid blocking
+        if event::poll(EVENT_POLL_TIMEOUT)? {
+            self.handle_event(event::read()?);
+        }
+        Ok(())
+    }
+
+    /// Dispatch a single crossterm event.
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            // it's important to check KeyEventKind::Press to avoid handling key release events
+            Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(key),
+            Event::Mouse(mouse) => self.on_mouse_event(mouse),
+            Event::Resize(_, _) => {}
+            _ => {}
+        }
+    }
+
+    /// Handles a click on one of the buttons.
+    pub fn on_mouse_event(&mut self, mouse: MouseEvent) {
+        if mouse.kind != MouseEventKind::Down(MouseButton::Left) || self.show_help {
+            return;
+        }
+
+        match button_at(&self.buttons, mouse.column, mouse.row) {
+            Some(Button::Connect) => self.handle_connect(),
+            Some(Button::Disconnect) => self.handle_disconnect(),
+            Some(Button::Mode) => self.handle_mode_selection(),
+            None => {}
+        }
+    }
+
+    /// Runs a configurable action bound in the [`KeyMap`].
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Connect => self.handle_connect(),
+            Action::Disconnect => self.handle_disconnect(),
+            Action::Refresh if self.active_tab == Tab::Settings => self.start_settings_check(),
+            Action::Refresh => self.handle_refresh(),
+            Action::Mode => self.handle_mode_selection(),
+            Action::Quit => self.request_quit(),
+        }
+    }
+
+    /// Handles the key events and updates the state of [`App`].
+    pub fn on_key_event(&mut self, key: KeyEvent) {
+        // Any key other than the second 'g' of "gg" cancels it
+        let pending_g = std::mem::take(&mut self.pending_g);
+
+        match (key.modifiers, key.code) {
+            // Global control keys
+            (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => self.quit(),
+
+            // Any key dismisses the help overlay
+            _ if self.show_help => self.show_help = false,
+
+            // Confirmation dialog specific keys
+            _ if self.pending_action.is_some() => self.handle_dialog_key(key.code),
+
+            // Abort the running command
+            (KeyModifiers::CONTROL, KeyCode::Char('x') | KeyCode::Char('X'))
+            | (_, KeyCode::Esc)
+                if self.pending.is_some() =>
+            {
+                self.abort_operation()
+            }
+            // Nothing to abort, keep Ctrl-X from clearing the log
+            (KeyModifiers::CONTROL, KeyCode::Char('x') | KeyCode::Char('X')) => {}
+
+            // Mode selection specific keys
+            _ if self.mode_selection.is_some() => match key.code {
+                KeyCode::Esc => self.handle_mode_selection_escape(),
+                KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+                KeyCode::Char('g') => self.handle_g_press(pending_g),
+                KeyCode::Char('G') => self.select_last(),
+                KeyCode::Enter => self.handle_mode_select(),
+                // No mode name contains the navigation keys above, so they can't be typed
+                KeyCode::Char(c) if c.is_ascii_alphanumeric() || c == '+' || c == '_' => {
+                    self.push_mode_filter(c)
+                }
+                KeyCode::Backspace => self.pop_mode_filter(),
+                _ => {}
+            },
+
+            // Families mode selection specific keys
+            _ if self.families_selection.is_some() => match key.code {
+                KeyCode::Esc => self.families_selection = None,
+                KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+                KeyCode::Char('g') => self.handle_g_press(pending_g),
+                KeyCode::Char('G') => self.select_last(),
+                KeyCode::Enter => self.handle_families_select(),
+                _ => {}
+            },
+
+            // Tunnel protocol selection specific keys
+            _ if self.protocol_selection.is_some() => match key.code {
+                KeyCode::Esc => self.protocol_selection = None,
+                KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+                KeyCode::Char('g') => self.handle_g_press(pending_g),
+                KeyCode::Char('G') => self.select_last(),
+                KeyCode::Enter => self.handle_protocol_select(),
+                _ => {}
+            },
+
+            // Profile selection specific keys
+            _ if self.profile_selection.is_some() => match key.code {
+                KeyCode::Esc => self.profile_selection = None,
+                KeyCode::Up | KeyCode::Char('k') => self.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+                KeyCode::Char('g') => self.handle_g_press(pending_g),
+                KeyCode::Char('G') => self.select_last(),
+                KeyCode::Enter => self.handle_profile_select(),
+                _ => {}
+            },
+
+            // Diagnostics panel specific keys
+            _ if self.diagnostics.is_some() => match key.code {
+                KeyCode::Esc | KeyCode::Char('t') | KeyCode::Char('T') => self.diagnostics = None,
+                KeyCode::Up | KeyCode::Char('k') => self.scroll_diagnostics(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.scroll_diagnostics(1),
+                KeyCode::PageUp => self.scroll_diagnostics(-10),
+                KeyCode::PageDown => self.scroll_diagnostics(10),
+                _ => {}
+            },
+
+            // Raw status panel specific keys
+            _ if self.raw_status_scroll.is_some() => match key.code {
+                KeyCode::Esc | KeyCode::Char('l') | KeyCode::Char('L') => {
+                    self.handle_raw_status_panel()
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.scroll_raw_status(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.scroll_raw_status(1),
+                KeyCode::PageUp => self.scroll_raw_status(-10),
+                KeyCode::PageDown => self.scroll_raw_status(10),
+                _ => {}
+            },
+
+            // DNS statistics panel specific keys
+            _ if self.dns_stats.is_some() => match key.code {
+                KeyCode::Esc | KeyCode::Char('s') | KeyCode::Char('S') => self.dns_stats = None,
+                _ => {}
+            },
+
+            // Tunnel statistics panel specific keys
+            _ if self.tunnel_stats.is_some() => match key.code {
+                KeyCode::Esc | KeyCode::Char('h') | KeyCode::Char('H') => self.tunnel_stats = None,
+                _ => {}
+            },
+
+            // Registration panel specific keys
+            _ if self.registration.is_some() => match key.code {
+                KeyCode::Esc | KeyCode::Char('g') | KeyCode::Char('G') => self.registration = None,
+                KeyCode::Char('v') | KeyCode::Char('V') => {
+                    if let Some(registration) = &mut self.registration {
+                        registration.reveal_license_key = !registration.reveal_license_key;
+                    }
+                }
+                _ => {}
+            },
+
+            // Normal mode keys
+            (_, KeyCode::Esc) => self.request_quit(),
+            (_, KeyCode::Char(c)) if self.keymap.action_for(c).is_some() => {
+                if let Some(action) = self.keymap.action_for(c) {
+                    self.handle_action(action);
+                }
+            }
+            (_, KeyCode::Char('f') | KeyCode::Char('F')) => self.handle_families_selection(),
+            (_, KeyCode::Char('p') | KeyCode::Char('P')) => self.handle_protocol_selection(),
+            (_, KeyCode::Char('o') | KeyCode::Char('O')) => self.handle_profile_selection(),
+            (_, KeyCode::Char('g') | KeyCode::Char('G')) => self.handle_registration_panel(),
+            (_, KeyCode::Char('s') | KeyCode::Char('S')) => self.handle_dns_stats_panel(),
+            (_, KeyCode::Char('h') | KeyCode::Char('H')) => self.handle_tunnel_stats_panel(),
+            (_, KeyCode::Char('i') | KeyCode::Char('I')) => self.show_details = !self.show_details,
+            (_, KeyCode::Char('t') | KeyCode::Char('T')) => self.handle_diagnostics(),
+            (_, KeyCode::Char('l') | KeyCode::Char('L')) => self.handle_raw_status_panel(),
+            (_, KeyCode::Char('v') | KeyCode::Char('V')) => self.handle_toggle_network_trust(),
+            (_, KeyCode::Char('w') | KeyCode::Char('W')) => self.handle_toggle_warp(),
+            (_, KeyCode::Char('u') | KeyCode::Char('U')) => self.handle_override(),
+            (_, KeyCode::Char('n') | KeyCode::Char('N')) => self.handle_register(),
+            (_, KeyCode::Char('e') | KeyCode::Char('E')) => self.handle_reset_settings(),
+            (_, KeyCode::Char('z') | KeyCode::Char('Z')) => self.handle_toggle_gateway(),
+            (_, KeyCode::Char('a') | KeyCode::Char('A')) => self.handle_toggle_auto_reconnect(),
+            (_, KeyCode::Char('+')) => self.change_refresh_interval(REFRESH_INTERVAL_STEP_MS),
+            (_, KeyCode::Char('-')) => self.change_refresh_interval(-REFRESH_INTERVAL_STEP_MS),
+            (_, KeyCode::Char(']')) => self.change_command_timeout(COMMAND_TIMEOUT_STEP_SECS),
+            (_, KeyCode::Char('[')) => self.change_command_timeout(-COMMAND_TIMEOUT_STEP_SECS),
+            (_, KeyCode::Tab) => self.select_tab(self.active_tab.next()),
+            (_, KeyCode::BackTab) => self.select_tab(self.active_tab.previous()),
+            (_, KeyCode::Char(c)) if Tab::from_number_key(c).is_some() => {
+                if let Some(tab) = Tab::from_number_key(c) {
+                    self.select_tab(tab);
+                }
+            }
+            // The logs tab scrolls line by line
+            (_, KeyCode::Up | KeyCode::Char('k')) if self.active_tab == Tab::Logs => {
+                self.log.scroll_up(1)
+            }
+            (_, KeyCode::Down | KeyCode::Char('j')) if self.active_tab == Tab::Logs => {
+                self.log.scroll_down(1)
+            }
+            (_, KeyCode::PageUp) => self.log.scroll_up(LOG_SCROLL_STEP),
+            (_, KeyCode::PageDown) => self.log.scroll_down(LOG_SCROLL_STEP),
+            (_, KeyCode::Char('x') | KeyCode::Char('X')) => self.log.clear(),
+            (_, KeyCode::Char('y') | KeyCode::Char('Y')) => self.copy_status(),
+            (_, KeyCode::Char('b') | KeyCode::Char('B')) => self.open_settings_page(),
+            (_, KeyCode::F(_)) => self.handle_favorite(key.code),
+            (_, KeyCode::Char(' ')) => self.toggle_auto_refresh_paused(),
+            (_, KeyCode::Char('?')) => self.show_help = true,
+            _ => {}
+        }
+    }
+}
+
+/// Renders a selection list with the selected entry highlighted.
+fn render_selection(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    items: &[&str],
+    state: &mut ListState,
+) {
+    let items: Vec<ListItem> = items.iter().map(|item| ListItem::new(*item)).collect();
+
+    let list = List::new(items)
+        .block(Block::bordered().title(title))
+        .style(Style::default())
+        .highlight_style(Style::default().reversed());
+
+    frame.render_stateful_widget(list, area, state);
+}
+
+/// Renders the placeholder shown until the first status arrives.
+fn render_loading(frame: &mut Frame, area: Rect, spinner: char) {
+    frame.render_widget(
+        Paragraph::new(Line::from(format!("{} Loading WARP status…", spinner)).centered())
+            .block(Block::bordered()),
+        area,
+    );
+}
+
+/// Renders the call to action shown while the device has no registration.
+fn render_no_registration(frame: &mut Frame, area: Rect) {
+    let text = vec![
+        Line::from("No WARP registration found.").bold(),
+        Line::from(""),
+        Line::from("Press 'n' to register."),
+    ];
+    frame.render_widget(
+        Paragraph::new(text).centered().yellow().block(
+            Block::bordered()
+                .title("Not registered")
+                .border_style(Style::default().yellow()),
+        ),
+        area,
+    );
+}
+
+/// Renders the banner explaining that warp-cli has to be installed.
+fn render_cli_missing(frame: &mut Frame, area: Rect) {
+    let text = vec![
+        Line::from("warp-cli was not found").bold(),
+        Line::from(""),
+        Line::from("Install Cloudflare WARP to use warp-tui:"),
+        Line::from("https://developers.cloudflare.com/warp-client/get-started/"),
+        Line::from(""),
+        Line::from(
+            "If it's installed outside your PATH, set WARP_CLI_PATH to the warp-cli binary.",
+        ),
+        Line::from("Press 'r' to check again, 'q' to quit."),
+    ];
+    frame.render_widget(
+        Paragraph::new(text).centered().red().block(
+            Block::bordered()
+                .title("warp-cli not found")
+                .border_style(Style::default().red()),
+        ),
+        area,
+    );
+}
+
+/// Renders the latest error on a single red line; a persistent one is highlighted
+fn render_error(frame: &mut Frame, area: Rect, error: &str, persistent: bool) {
+    let line = Line::from(format!("Error: {}", error));
+    let line = if persistent {
+        line.bold().white().on_red()
+    } else {
+        line.red()
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// Renders the output of the connectivity check.
+fn render_diagnostics(frame: &mut Frame, area: Rect, diagnostics: &DiagnosticsPanel) {
+    frame.render_widget(
+        Paragraph::new(diagnostics.output.as_str())
+            .scroll((diagnostics.scroll, 0))
+            .block(
+                Block::bordered()
+                    .title("Diagnostics")
+                    .title_bottom(Line::from(
+                        "Up/Down/PgUp/PgDn to scroll, 't' or 'Esc' to close",
+                    )),
+            ),
+        area,
+    );
+}
+
+/// Renders the parsed status next to the raw `warp-cli status` output it came from.
+fn render_raw_status(frame: &mut Frame, area: Rect, info: &WarpInfo, scroll: u16) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+
+    frame.render_widget(
+        Paragraph::new(info.to_string()).block(Block::bordered().title("Parsed")),
+        columns[0],
+    );
+    let raw = info
+        .raw
+        .as_deref()
+        .unwrap_or("Waiting for the next status refresh…");
+    frame.render_widget(
+        Paragraph::new(raw).scroll((scroll, 0)).block(
+            Block::bordered()
+                .title("Raw warp-cli status")
+                .title_bottom(Line::from(
+                    "Up/Down/PgUp/PgDn to scroll, 'l' or 'Esc' to close",
+                )),
+        ),
+        columns[1],
+    );
+}
+
+/// Renders the registration details panel.
+fn render_registration(frame: &mut Frame, area: Rect, registration: &RegistrationPanel) {
+    let text = match &registration.info {
+        Ok(info) => {
+            let license_key = if registration.reveal_license_key {
+                info.license_key.clone()
+            } else {
+                info.masked_license_key()
+            };
+            format!(
+                "Device ID: {}\n\
+                Organization: {}\n\
+                Account Type: {}\n\
+                License Key: {}\n\n\
+                Press 'v' to {} the license key, 'g' or 'Esc' to close",
+                info.device_id.as_deref().unwrap_or("N/A"),
+                info.organization.as_deref().unwrap_or("N/A"),
+                info.account_type.as_deref().unwrap_or("N/A"),
+                license_key.as_deref().unwrap_or("N/A"),
+                if registration.reveal_license_key {
+                    "hide"
+                } else {
+                    "reveal"
+                },
+            )
+        }
+        Err(error) => format!(
+            "Failed to get registration: {}\n\n\
+            Press 'g' or 'Esc' to close",
+            error
+        ),
+    };
+
+    frame.render_widget(
+        Paragraph::new(text).block(Block::bordered().title("Registration")),
+        area,
+    );
+}
+
+/// Renders the DNS statistics panel.
+fn render_dns_stats(frame: &mut Frame, area: Rect, dns_stats: &Result<DnsStats, String>) {
+    let text = match dns_stats {
+        Ok(stats) => format!(
+            "Total Queries: {}\n\
+            Cached: {}\n\
+            Cache Hit Rate: {}\n\
+            Blocked: {}\n\
+            Average Latency: {}\n\n\
+            Press 's' or 'Esc' to close",
+            stats.total_queries,
+            stats.cached,
+            stats
+                .cache_hit_rate()
+                .map(|rate| format!("{:.1}%", rate * 100.0))
+                .unwrap_or_else(|| "N/A".to_string()),
+            stats.blocked,
+            stats
+                .latency
+                .map(|ms| format!("{:.1} ms", ms))
+                .unwrap_or_else(|| "N/A".to_string()),
+        ),
+        Err(error) => format!(
+            "Failed to get DNS statistics: {}\n\n\
+            Press 's' or 'Esc' to close",
+            error
+        ),
+    };
+
+    frame.render_widget(
+        Paragraph::new(text).block(Block::bordered().title("DNS Statistics")),
+        area,
+    );
+}
+
+/// Renders the tunnel statistics panel.
+fn render_tunnel_stats(frame: &mut Frame, area: Rect, tunnel_stats: &Result<TunnelStats, String>) {
+    let text = match tunnel_stats {
+        Ok(stats) => format!(
+            "Received: {} bytes ({} packets)\n\
+            Sent: {} bytes ({} packets)\n\
+            Last Handshake: {}\n\n\
+            Press 'h' or 'Esc' to close",
+            stats.rx_bytes,
+            stats.rx_packets,
+            stats.tx_bytes,
+            stats.tx_packets,
+            stats
+                .since_last_handshake
+                .map(|elapsed| format!("{} ago", format_elapsed(elapsed)))
+                .unwrap_or_else(|| "Never".to_string()),
+        ),
+        Err(error) => format!(
+            "Failed to get tunnel statistics: {}\n\n\
+            Press 'h' or 'Esc' to close",
+            error
+        ),
+    };
+
+    frame.render_widget(
+        Paragraph::new(text).block(Block::bordered().title("Tunnel Statistics")),
+        area,
+    );
+}
+
+/// Renders the settings tab from the last fetched settings.
+fn render_settings(
+    frame: &mut Frame,
+    area: Rect,
+    settings: Option<&Result<WarpSettingsInfo, String>>,
+    loading: bool,
+    command_timeout: Duration,
+) {
+    let yes_no = |value: bool| if value { "Yes" } else { "No" };
+    // Not a WARP setting, so it's shown whether or not the settings loaded
+    let timeout_line = format!(
+        "Command Timeout: {}s (press '[' or ']' to change)",
+        command_timeout.as_secs()
+    );
+    let text = match settings {
+        _ if loading => "Loading settings…".to_string(),
+        Some(Ok(settings)) => format!(
+            "Mode: {}\n\
+            Tunnel Protocol: {}\n\
+            DNS Families Mode: {}\n\
+            Always On: {}\n\
+            Switch Locked: {}\n\
+            DNS Logging: {}\n\n\
+            Press 'r' to reload",
+            settings.mode,
+            settings
+                .tunnel_protocol
+                .map(|protocol| protocol.to_string())
+                .unwrap_or_else(|| "N/A".to_string()),
+            settings.families_mode.as_deref().unwrap_or("N/A"),
+            yes_no(settings.always_on),
+            yes_no(settings.switch_locked),
+            yes_no(settings.dns_log_enabled),
+        ),
+        Some(Err(error)) => format!(
+            "Failed to get settings: {}\n\n\
+            Press 'r' to try again",
+            error
+        ),
+        None => "Settings not loaded, press 'r' to load them".to_string(),
+    };
+    let text = format!("{}\n\n{}", text, timeout_line);
+
+    frame.render_widget(
+        Paragraph::new(text).block(Block::bordered().title("Settings")),
+        area,
+    );
+}
+
+/// Can another automatic reconnect start at `now`, given when the last one started?
+pub fn reconnect_allowed(last_attempt: Option<Instant>, now: Instant) -> bool {
+    last_attempt.is_none_or(|last| now.saturating_duration_since(last) >= RECONNECT_COOLDOWN)
+}
+
+/// Is an error reported at `reported` still shown at `now`?
+///
+/// Persistent errors, such as a missing warp-cli, are shown until they're resolved.
+pub fn error_visible(reported: Instant, now: Instant, persistent: bool) -> bool {
+    persistent || now.saturating_duration_since(reported) < ERROR_DISPLAY_DURATION
+}
+
+/// Can a connect or disconnect be issued at `now`, given when the last one was?
+pub fn connect_command_allowed(last_command: Option<Instant>, now: Instant) -> bool {
+    last_command.is_none_or(|last| now.saturating_duration_since(last) >= CONNECT_DEBOUNCE)
+}
+
+/// Was WARP disconnected on purpose, judging by the reason warp-cli reports?
+fn is_user_initiated_disconnect(reason: &str) -> bool {
+    reason.to_lowercase().contains("manual")
+}
+
+/// Warning shown once a connect or disconnect has been in progress for too long
+fn stale_transition_warning(status: &WarpStatus) -> String {
+    match status {
+        WarpStatus::Disconnecting => {
+            "Still disconnecting — something may be wrong. Press 'd' to retry or 'c' to connect.\n"
+        }
+        _ => {
+            "Still connecting — something may be wrong. Press 'c' to retry or 'd' to disconnect.\n"
+        }
+    }
+    .to_string()
+}
+
+/// The settings `profile` changes, or why one of its values isn't valid
+fn profile_batch(profile: &Profile) -> Result<SettingsBatch, String> {
+    let mode = match profile.mode.as_deref().map(WarpMode::from_cli_str) {
+        Some(WarpMode::Unknown) => {
+            return Err(format!(
+                "unknown mode {:?}",
+                profile.mode.as_deref().unwrap_or("")
+            ));
+        }
+        mode => mode,
+    };
+    let families_mode = match &profile.families_mode {
+        Some(families_mode) => Some(
+            FamiliesMode::from_cli_str(families_mode)
+                .ok_or_else(|| format!("unknown DNS families mode {:?}", families_mode))?,
+        ),
+        None => None,
+    };
+    Ok(SettingsBatch {
+        mode,
+        families_mode,
+        tunnel_protocol: None,
+    })
+}
+
+/// Modes whose name contains `filter`, ignoring case
+pub fn filter_modes(filter: &str) -> Vec<WarpMode> {
+    let filter = filter.to_lowercase();
+    AVAILABLE_MODES
+        .iter()
+        .filter(|mode| {
+            mode.as_cli_str().contains(&filter) || mode.to_string().to_lowercase().contains(&filter)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Index above `current` in a list of `len` entries, wrapping to the last entry if `wrap`
+/// and staying on the first one otherwise.
+pub fn previous_index(current: usize, len: usize, wrap: bool) -> usize {
+    match current {
+        0 if wrap => len.saturating_sub(1),
+        0 => 0,
+        _ => current - 1,
+    }
+}
+
+/// Index below `current` in a list of `len` entries, wrapping to the first entry if `wrap`
+/// and staying on the last one otherwise.
+pub fn next_index(current: usize, len: usize, wrap: bool) -> usize {
+    if current + 1 < len {
+        current + 1
+    } else if wrap {
+        0
+    } else {
+        len.saturating_sub(1)
+    }
+}
+
+/// Apply `delta_ms` to a refresh interval, clamped to
+/// [`MIN_REFRESH_INTERVAL_MS`]..=[`MAX_REFRESH_INTERVAL_MS`].
+pub fn adjust_refresh_interval(current_ms: u64, delta_ms: i64) -> u64 {
+    current_ms
+        .saturating_add_signed(delta_ms)
+        .clamp(MIN_REFRESH_INTERVAL_MS, MAX_REFRESH_INTERVAL_MS)
+}
+
+/// Apply `delta_secs` to a command timeout, clamped to
+/// [`MIN_COMMAND_TIMEOUT_SECS`]..=[`MAX_COMMAND_TIMEOUT_SECS`].
+pub fn adjust_command_timeout(current_secs: u64, delta_secs: i64) -> u64 {
+    current_secs
+        .saturating_add_signed(delta_secs)
+        .clamp(MIN_COMMAND_TIMEOUT_SECS, MAX_COMMAND_TIMEOUT_SECS)
+}
+
+/// Renders the confirmation dialog of `action` centered over the screen.
+fn render_dialog(frame: &mut Frame, action: PendingAction) {
+    let mut lines: Vec<Line> = action
+        .prompt()
+        .lines()
+        .map(|line| Line::from(line).bold())
+        .collect();
+    lines.push(Line::from(""));
+    lines.extend(
+        action
+            .choices()
+            .iter()
+            .map(|(key, description)| Line::from(format!("{:<5}{}", key, description))),
+    );
+
+    let area = centered_rect(frame.area(), 50, lines.len() as u16 + 2);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title("Confirm")),
+        area,
+    );
+}
+
+/// Renders `label` with a spinner in a small popup centered over the screen.
+fn render_pending(frame: &mut Frame, label: &str, spinner: char) {
+    let area = centered_rect(frame.area(), 30, 3);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(Line::from(format!("{} {}", spinner, label)).centered())
+            .block(Block::bordered()),
+        area,
+    );
+}
+
+/// Renders `message` in a small popup at the bottom of the screen.
+fn render_toast(frame: &mut Frame, message: &str) {
+    let [area] = Layout::vertical([Constraint::Length(3)])
+        .flex(Flex::End)
+        .areas(frame.area());
+    let area = centered_rect(area, 20, 3);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(Line::from(message).centered()).block(Block::bordered()),
+        area,
+    );
+}
+
+/// Frame `frame` of the spinner, wrapping around
+pub fn spinner_char(frame: usize) -> char {
+    SPINNER[frame % SPINNER.len()]
+}
+
+/// A network with its trust indicator, e.g. "✓ HomeWifi (Wi-Fi, trusted)"
+pub fn network_line(network: &NetworkInfo) -> String {
+    let indicator = if network.trusted { '✓' } else { '•' };
+    let mut details = Vec::new();
+    if network.network_type != NetworkType::Unknown {
+        details.push(network.network_type.to_string());
+    }
+    if network.trusted {
+        details.push("trusted".to_string());
+    }
+    if details.is_empty() {
+        format!("{} {}", indicator, network.name)
+    } else {
+        format!("{} {} ({})", indicator, network.name, details.join(", "))
+    }
+}
+
+/// Does `area` call for the compact single-line status?
+pub fn is_compact(area: Rect) -> bool {
+    area.height < COMPACT_HEIGHT
+}
+
+/// The [`WarpInfo::summary`] and enabled flags on a single line, for [`is_compact`]
+/// terminals
+pub fn compact_status_line(info: &WarpInfo) -> String {
+    let flag = |enabled| if enabled { "on" } else { "off" };
+    format!(
+        "{} | WARP: {} | Gateway: {}",
+        info.summary(),
+        flag(info.warp_enabled),
+        flag(info.gateway_enabled)
+    )
+}
+
+/// Exit code for `--exit-code`: 0 when connected, 2 when disconnected and 3 otherwise,
+/// e.g. while connecting or when the status is unknown
+pub fn status_exit_code(status: &WarpStatus) -> i32 {
+    match status {
+        WarpStatus::Connected => 0,
+        WarpStatus::Disconnected => 2,
+        _ => 3,
+    }
+}
+
+/// Format a duration as `HH:MM:SS`; the hours keep growing past 99
+pub fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// Keep a failed fetch so its panel can show why, unless it was aborted
+fn panel_result<T>(result: WarpResult<T>) -> WarpResult<Result<T, String>> {
+    match result {
+        Err(WarpError::Cancelled) => Err(WarpError::Cancelled),
+        result => Ok(result.map_err(|e| e.to_string())),
+    }
+}
+
+/// Time until the next auto-refresh, clamped at zero once it's due
+pub fn refresh_countdown(elapsed: Duration, interval_ms: u64) -> Duration {
+    Duration::from_millis(interval_ms).saturating_sub(elapsed)
+}
+
+/// Describe the result of [`WarpClient::get_version_sync`] for the footer
+pub fn version_label(version: &WarpResult<String>) -> String {
+    match version {
+        Ok(version) => format!("warp-cli: {}", version),
+        Err(WarpError::CommandNotFound) => "warp-cli: not found".to_string(),
+        Err(_) => "warp-cli: unknown version".to_string(),
+    }
+}
+
+/// Compute a rect of the given width (in percent of `area`) and height (in rows)
+/// centered within `area`.
+fn centered_rect(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Percentage(percent_x)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Read crossterm events on a dedicated thread and forward them to the event loop.
+///
+/// The thread exits once the receiving end is dropped.
+fn spawn_terminal_event_reader() -> mpsc::UnboundedReceiver<std::io::Result<Event>> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || {
+        loop {
+            let event = match event::poll(EVENT_POLL_TIMEOUT) {
+                Ok(true) => event::read(),
+                Ok(false) if sender.is_closed() => break,
+                Ok(false) => continue,
+                Err(e) => Err(e),
+            };
+            if sender.send(event).is_err() {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
@@ -0,0 +1,44 @@
+use crate::warp::{WarpInfo, WarpStatus};
+
+/// Text of the notification for a status change, `None` unless WARP went from connected
+/// to disconnected or back
+pub fn status_change_message(previous: &WarpStatus, info: &WarpInfo) -> Option<String> {
+    let changed = matches!(
+        (previous, &info.status),
+        (WarpStatus::Connected, WarpStatus::Disconnected)
+            | (WarpStatus::Disconnected, WarpStatus::Connected)
+    );
+    if !changed {
+        return None;
+    }
+
+    Some(match &info.reason {
+        Some(reason) => format!("{} ({})", info.status, reason),
+        None => info.status.to_string(),
+    })
+}
+
+/// Show `body` as a desktop notification
+///
+/// Failures are only logged, a missing notification daemon shouldn't interrupt the TUI.
+/// The notification is sent from a background thread since talking to the daemon can
+/// block.
+#[cfg(feature = "notifications")]
+pub fn notify(body: &str) {
+    const SUMMARY: &str = "Cloudflare WARP";
+
+    let body = body.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = notify_rust::Notification::new()
+            .summary(SUMMARY)
+            .body(&body)
+            .show()
+        {
+            tracing::warn!(error = %e, "failed to send desktop notification");
+        }
+    });
+}
+
+/// Without the `notifications` feature nothing is shown
+#[cfg(not(feature = "notifications"))]
+pub fn notify(_body: &str) {}
@@ -0,0 +1,21 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Directory holding the warp-tui configuration files,
+/// `$XDG_CONFIG_HOME/warp-tui` or `~/.config/warp-tui`
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config").map(|dir| dir.join("warp-tui"))
+}
+
+/// Directory holding state kept between sessions,
+/// `$XDG_STATE_HOME/warp-tui` or `~/.local/state/warp-tui`
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state").map(|dir| dir.join("warp-tui"))
+}
+
+fn xdg_dir(var: &str, home_fallback: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(home_fallback)))
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Flag telling the event loop to quit, set from outside of it when the
+/// process receives a termination signal
+///
+/// Clones share the flag, so the handle given to [`listen`] stops the app it came from.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown(Arc<AtomicBool>);
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the event loop to quit
+    pub fn request(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Wait for SIGTERM, SIGINT or SIGHUP and request a shutdown
+///
+/// Spawn this on the runtime driving the event loop; the loop then quits
+/// through its normal path so the terminal gets restored.
+pub async fn listen(shutdown: Shutdown) {
+    match wait_for_signal().await {
+        Ok(name) => {
+            tracing::info!("Received {}, shutting down", name);
+            shutdown.request();
+        }
+        Err(e) => tracing::warn!("Failed to install the signal handler: {}", e),
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() -> std::io::Result<&'static str> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    Ok(tokio::select! {
+        _ = terminate.recv() => "SIGTERM",
+        _ = interrupt.recv() => "SIGINT",
+        _ = hangup.recv() => "SIGHUP",
+    })
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() -> std::io::Result<&'static str> {
+    tokio::signal::ctrl_c().await?;
+    Ok("Ctrl-C")
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::app::paths::state_dir;
+use crate::warp::WarpMode;
+
+#[derive(Error, Debug)]
+pub enum StateError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid state file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Failed to serialize state: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Settings remembered between sessions
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Auto-refresh interval in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval_ms: Option<u64>,
+    /// Mode last selected in the mode picker, as passed to `warp-cli mode`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_mode: Option<String>,
+    /// Reconnect automatically when WARP drops
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_reconnect: Option<bool>,
+    /// Name of the profile last applied from the profile picker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_profile: Option<String>,
+}
+
+impl SessionState {
+    /// Default location of the state file, `~/.local/state/warp-tui/state.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        state_dir().map(|dir| dir.join("state.toml"))
+    }
+
+    /// The last selected mode, if it's a known one
+    pub fn last_mode(&self) -> Option<WarpMode> {
+        self.last_mode
+            .as_deref()
+            .map(WarpMode::from_cli_str)
+            .filter(|mode| *mode != WarpMode::Unknown)
+    }
+}
+
+/// Read the state saved at `path`
+pub fn load(path: &Path) -> Result<SessionState, StateError> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&contents)?)
+}
+
+/// Write `state` to `path`, creating its parent directory if needed
+pub fn save(path: &Path, state: &SessionState) -> Result<(), StateError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, toml::to_string(state)?)?;
+    Ok(())
+}
@@ -0,0 +1,51 @@
+/// Tabs of the main window, switched with Tab/Shift-Tab or the number keys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tab {
+    /// Connection status and the connect/disconnect controls
+    #[default]
+    Status,
+    /// Settings reported by `warp-cli settings`
+    Settings,
+    /// Tunnel statistics and latency history
+    Stats,
+    /// The full log
+    Logs,
+}
+
+impl Tab {
+    /// Every tab, in the order shown in the tab bar
+    pub const ALL: [Tab; 4] = [Tab::Status, Tab::Settings, Tab::Stats, Tab::Logs];
+
+    /// Name shown in the tab bar
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Status => "Status",
+            Tab::Settings => "Settings",
+            Tab::Stats => "Stats",
+            Tab::Logs => "Logs",
+        }
+    }
+
+    /// Position in the tab bar
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|tab| *tab == self).unwrap_or(0)
+    }
+
+    /// The tab to the right, wrapping around to the first
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    /// The tab to the left, wrapping around to the last
+    pub fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// The tab selected by number key `key`, '1' being the first
+    pub fn from_number_key(key: char) -> Option<Self> {
+        let number = key.to_digit(10)? as usize;
+        number
+            .checked_sub(1)
+            .and_then(|i| Self::ALL.get(i).copied())
+    }
+}
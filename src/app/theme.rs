@@ -0,0 +1,160 @@
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::app::paths::config_dir;
+use crate::warp::{WarpMode, WarpStatus};
+
+/// Colors used to render each [`WarpStatus`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub connected: Color,
+    pub disconnected: Color,
+    pub connecting: Color,
+    pub disconnecting: Color,
+    pub unknown: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            connected: Color::Green,
+            disconnected: Color::Red,
+            connecting: Color::Yellow,
+            disconnecting: Color::Yellow,
+            unknown: Color::Gray,
+        }
+    }
+}
+
+/// The color used to render `mode`: blue for DNS-only modes, cyan for modes that route
+/// traffic through the WARP tunnel
+pub fn color_for_mode(mode: &WarpMode) -> Color {
+    match mode {
+        WarpMode::DoH | WarpMode::DoT | WarpMode::DnsOnly => Color::Blue,
+        WarpMode::WarpDoH
+        | WarpMode::WarpDoT
+        | WarpMode::Warp
+        | WarpMode::Proxy
+        | WarpMode::TunnelOnly => Color::Cyan,
+        WarpMode::Unknown => Color::Gray,
+    }
+}
+
+/// Color names as written in `theme.toml`; every key is optional
+#[derive(Debug, Default, Deserialize)]
+struct ThemeFile {
+    connected: Option<String>,
+    disconnected: Option<String>,
+    connecting: Option<String>,
+    disconnecting: Option<String>,
+    unknown: Option<String>,
+}
+
+impl Theme {
+    /// The color used to render `status`
+    pub fn color_for(&self, status: &WarpStatus) -> Color {
+        match status {
+            WarpStatus::Connected => self.connected,
+            WarpStatus::Disconnected => self.disconnected,
+            WarpStatus::Connecting => self.connecting,
+            WarpStatus::Disconnecting => self.disconnecting,
+            WarpStatus::Unknown => self.unknown,
+        }
+    }
+
+    /// Default location of the theme file, `~/.config/warp-tui/theme.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("theme.toml"))
+    }
+
+    /// Load a theme from `path`, returning it along with any warnings.
+    ///
+    /// A missing file yields the default theme. An unreadable file, invalid TOML or
+    /// an invalid color falls back to the defaults and produces a warning.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Self::from_toml_str(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(e) => (
+                Self::default(),
+                vec![format!("Failed to read theme {}: {}", path.display(), e)],
+            ),
+        }
+    }
+
+    /// Parse a theme mapping status names to color names, returning it along with any warnings
+    pub fn from_toml_str(contents: &str) -> (Self, Vec<String>) {
+        let mut theme = Self::default();
+        let mut warnings = Vec::new();
+
+        let file: ThemeFile = match toml::from_str(contents) {
+            Ok(file) => file,
+            Err(e) => {
+                warnings.push(format!("Invalid theme file: {}", e));
+                return (theme, warnings);
+            }
+        };
+
+        let entries = [
+            ("connected", file.connected, &mut theme.connected),
+            ("disconnected", file.disconnected, &mut theme.disconnected),
+            ("connecting", file.connecting, &mut theme.connecting),
+            (
+                "disconnecting",
+                file.disconnecting,
+                &mut theme.disconnecting,
+            ),
+            ("unknown", file.unknown, &mut theme.unknown),
+        ];
+        for (key, value, color) in entries {
+            let Some(value) = value else {
+                continue;
+            };
+            match parse_color(&value) {
+                Some(parsed) => *color = parsed,
+                None => warnings.push(format!(
+                    "Invalid color {:?} for {:?} in theme, using the default",
+                    value, key
+                )),
+            }
+        }
+
+        (theme, warnings)
+    }
+}
+
+/// Parse a color name such as `green` or `bright_red`, or a hex color `#RRGGBB`
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().to_lowercase();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    let color = match value.replace(['-', ' '], "_").as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "bright_red" | "light_red" => Color::LightRed,
+        "bright_green" | "light_green" => Color::LightGreen,
+        "bright_yellow" | "light_yellow" => Color::LightYellow,
+        "bright_blue" | "light_blue" => Color::LightBlue,
+        "bright_magenta" | "light_magenta" => Color::LightMagenta,
+        "bright_cyan" | "light_cyan" => Color::LightCyan,
+        "white" | "bright_white" => Color::White,
+        "reset" | "default" => Color::Reset,
+        _ => return None,
+    };
+    Some(color)
+}
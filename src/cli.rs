@@ -0,0 +1,85 @@
+use crate::warp::WarpMode;
+
+/// Usage text printed for `--help` and invalid arguments
+pub const USAGE: &str = "\
+Usage: warp-tui [OPTIONS]
+
+Without options the interactive terminal UI is started.
+
+Options:
+  --status, --oneshot  Print the current WARP status and exit
+                       (exit code 0 if connected, 1 otherwise)
+  --json               Print the status as JSON (implies --status)
+  --set-mode <MODE>    Switch to MODE (e.g. warp+doh) and exit
+                       (exit code 0 on success, 1 otherwise)
+  --dry-run            Don't run warp-cli commands that change WARP,
+                       only log them (status is still read)
+  --exit-code          Exit the TUI with a code for the WARP status at quit
+                       (0 if connected, 2 if disconnected, 3 otherwise)
+  -h, --help           Print this help";
+
+/// Command line options
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cli {
+    /// Print the status and exit instead of starting the TUI
+    pub oneshot: bool,
+    /// Print the status as JSON
+    pub json: bool,
+    /// Switch to this mode and exit instead of starting the TUI
+    pub set_mode: Option<WarpMode>,
+    /// Only log warp-cli commands that change WARP instead of running them
+    pub dry_run: bool,
+    /// Exit the TUI with a code describing the WARP status at quit instead of 0
+    pub exit_code: bool,
+    /// Print the usage text
+    pub help: bool,
+}
+
+impl Cli {
+    /// Parse the command line arguments, excluding the program name
+    pub fn parse<I, S>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_ref() {
+                "--status" | "--oneshot" => cli.oneshot = true,
+                "--json" => {
+                    cli.oneshot = true;
+                    cli.json = true;
+                }
+                "--set-mode" | "--mode" => {
+                    let mode = args
+                        .next()
+                        .ok_or_else(|| format!("{} requires a mode", arg.as_ref()))?;
+                    cli.set_mode = Some(parse_mode(mode.as_ref())?);
+                }
+                "--dry-run" => cli.dry_run = true,
+                "--exit-code" => cli.exit_code = true,
+                "-h" | "--help" => cli.help = true,
+                other => return Err(format!("unknown argument: {}", other)),
+            }
+        }
+
+        Ok(cli)
+    }
+}
+
+/// Parse a mode argument, rejecting anything that isn't a known mode
+fn parse_mode(mode: &str) -> Result<WarpMode, String> {
+    match WarpMode::from_cli_str(mode) {
+        WarpMode::Unknown => {
+            let valid: Vec<&str> = WarpMode::ALL.iter().map(|m| m.as_cli_str()).collect();
+            Err(format!(
+                "unknown mode: {} (expected one of: {})",
+                mode,
+                valid.join(", ")
+            ))
+        }
+        mode => Ok(mode),
+    }
+}
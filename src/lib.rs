@@ -1,3 +1,6 @@
+pub mod app;
+pub mod cli;
+pub mod logging;
 pub mod warp;
 
 pub use warp::*;
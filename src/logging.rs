@@ -0,0 +1,28 @@
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Environment variable naming the file diagnostic logs are appended to
+pub const LOG_PATH_ENV: &str = "WARP_TUI_LOG";
+
+/// Send diagnostic logs to the file named by [`LOG_PATH_ENV`], if it's set.
+///
+/// Nothing is logged otherwise: writing to stderr would corrupt the terminal UI.
+pub fn init_logging() -> std::io::Result<()> {
+    match std::env::var_os(LOG_PATH_ENV) {
+        Some(path) if !path.is_empty() => init_file_logging(Path::new(&path)),
+        _ => Ok(()),
+    }
+}
+
+/// Append debug level logs to `path`
+fn init_file_logging(path: &Path) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    // Ignore a subscriber that's already installed, e.g. by an embedding application
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(Mutex::new(file))
+        .try_init();
+    Ok(())
+}
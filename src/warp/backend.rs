@@ -0,0 +1,237 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::warp::client::CancelToken;
+use crate::warp::{
+    ConnectOutcome, DaemonStatus, DisconnectOutcome, DnsStats, RegistrationInfo, SettingsBatch,
+    TunnelStats, WarpClient, WarpInfo, WarpResult, WarpSettingsInfo, WarpStats, WarpStatus,
+};
+
+/// The blocking WARP commands the TUI runs
+///
+/// [`WarpClient`] implements them with warp-cli. Tests can give an
+/// [`App`](crate::app::App) another implementation with
+/// [`App::with_backend`](crate::app::App::with_backend) to script the responses.
+pub trait WarpBackend: fmt::Debug + Send + Sync {
+    /// Get the current WARP status
+    fn get_status_sync(&self) -> WarpResult<WarpInfo>;
+
+    /// Get the WARP status, along with the connection statistics while connected
+    fn get_status_with_stats_sync(&self) -> WarpResult<(WarpInfo, Option<WarpStats>)>;
+
+    /// Get the warp-cli version
+    fn get_version_sync(&self) -> WarpResult<String>;
+
+    /// Check whether the WARP daemon is running and responding
+    fn daemon_status_sync(&self) -> WarpResult<DaemonStatus>;
+
+    /// Connect to WARP
+    fn connect_sync(&self) -> WarpResult<ConnectOutcome>;
+
+    /// Disconnect from WARP
+    fn disconnect_sync(&self) -> WarpResult<DisconnectOutcome>;
+
+    /// Disconnect from WARP and wait up to `timeout` for the status to follow
+    fn disconnect_and_wait_sync(&self, timeout: Duration) -> WarpResult<WarpStatus>;
+
+    /// Set the WARP mode
+    fn set_mode_sync(&self, mode: &str) -> WarpResult<()>;
+
+    /// Set the DNS families mode
+    fn set_families_mode_sync(&self, mode: &str) -> WarpResult<()>;
+
+    /// Set the tunnel protocol
+    fn set_tunnel_protocol_sync(&self, protocol: &str) -> WarpResult<()>;
+
+    /// Turn WARP on or off
+    fn set_warp_enabled_sync(&self, enabled: bool) -> WarpResult<()>;
+
+    /// Turn Gateway on or off
+    fn set_gateway_enabled_sync(&self, enabled: bool) -> WarpResult<()>;
+
+    /// Get the current settings
+    fn get_settings_parsed(&self) -> WarpResult<WarpSettingsInfo>;
+
+    /// Apply every setting of `batch`, restoring the previous values if one of them fails
+    fn apply_settings_batch(&self, batch: &SettingsBatch) -> WarpResult<()>;
+
+    /// Reset the settings to their defaults
+    fn reset_settings_sync(&self) -> WarpResult<()>;
+
+    /// Apply an admin override policy
+    fn set_override_sync(&self, policy: &str) -> WarpResult<()>;
+
+    /// Clear the admin override
+    fn clear_override_sync(&self) -> WarpResult<()>;
+
+    /// Get the registration of this device
+    fn get_registration_sync(&self) -> WarpResult<RegistrationInfo>;
+
+    /// Register this device, returning the new registration
+    fn create_registration_sync(&self) -> WarpResult<RegistrationInfo>;
+
+    /// SSID of the connected Wi-Fi network
+    fn current_ssid_sync(&self) -> WarpResult<String>;
+
+    /// List the trusted Wi-Fi networks
+    fn list_trusted_ssids_sync(&self) -> WarpResult<Vec<String>>;
+
+    /// Trust a Wi-Fi network
+    fn add_trusted_ssid_sync(&self, ssid: &str) -> WarpResult<()>;
+
+    /// Stop trusting a Wi-Fi network
+    fn remove_trusted_ssid_sync(&self, ssid: &str) -> WarpResult<()>;
+
+    /// Run the connectivity check, returning its report
+    fn run_connectivity_check_sync(&self) -> WarpResult<String>;
+
+    /// Get the DNS statistics
+    fn get_dns_stats_sync(&self) -> WarpResult<DnsStats>;
+
+    /// Get the tunnel statistics
+    fn get_tunnel_stats_sync(&self) -> WarpResult<TunnelStats>;
+
+    /// Time a command may run before it's stopped
+    fn timeout(&self) -> Duration;
+
+    /// Change the time a command may run before it's stopped
+    fn set_timeout(&self, timeout: Duration);
+
+    /// Start or stop keeping the unparsed status output in [`WarpInfo::raw`]
+    fn set_capture_raw_status(&self, capture_raw_status: bool);
+
+    /// Are mutating commands only logged instead of run?
+    fn is_dry_run(&self) -> bool;
+
+    /// A handle to this backend whose commands fail with
+    /// [`WarpError::Cancelled`](crate::warp::error::WarpError::Cancelled) once `token` is
+    /// cancelled
+    fn cancellable(&self, token: CancelToken) -> Arc<dyn WarpBackend>;
+}
+
+impl WarpBackend for WarpClient {
+    fn get_status_sync(&self) -> WarpResult<WarpInfo> {
+        WarpClient::get_status_sync(self)
+    }
+
+    fn get_status_with_stats_sync(&self) -> WarpResult<(WarpInfo, Option<WarpStats>)> {
+        WarpClient::get_status_with_stats_sync(self)
+    }
+
+    fn get_version_sync(&self) -> WarpResult<String> {
+        WarpClient::get_version_sync(self)
+    }
+
+    fn daemon_status_sync(&self) -> WarpResult<DaemonStatus> {
+        WarpClient::daemon_status_sync(self)
+    }
+
+    fn connect_sync(&self) -> WarpResult<ConnectOutcome> {
+        WarpClient::connect_sync(self)
+    }
+
+    fn disconnect_sync(&self) -> WarpResult<DisconnectOutcome> {
+        WarpClient::disconnect_sync(self)
+    }
+
+    fn disconnect_and_wait_sync(&self, timeout: Duration) -> WarpResult<WarpStatus> {
+        WarpClient::disconnect_and_wait_sync(self, timeout)
+    }
+
+    fn set_mode_sync(&self, mode: &str) -> WarpResult<()> {
+        WarpClient::set_mode_sync(self, mode)
+    }
+
+    fn set_families_mode_sync(&self, mode: &str) -> WarpResult<()> {
+        WarpClient::set_families_mode_sync(self, mode)
+    }
+
+    fn set_tunnel_protocol_sync(&self, protocol: &str) -> WarpResult<()> {
+        WarpClient::set_tunnel_protocol_sync(self, protocol)
+    }
+
+    fn set_warp_enabled_sync(&self, enabled: bool) -> WarpResult<()> {
+        WarpClient::set_warp_enabled_sync(self, enabled)
+    }
+
+    fn set_gateway_enabled_sync(&self, enabled: bool) -> WarpResult<()> {
+        WarpClient::set_gateway_enabled_sync(self, enabled)
+    }
+
+    fn get_settings_parsed(&self) -> WarpResult<WarpSettingsInfo> {
+        WarpClient::get_settings_parsed(self)
+    }
+
+    fn apply_settings_batch(&self, batch: &SettingsBatch) -> WarpResult<()> {
+        WarpClient::apply_settings_batch(self, batch)
+    }
+
+    fn reset_settings_sync(&self) -> WarpResult<()> {
+        WarpClient::reset_settings_sync(self)
+    }
+
+    fn set_override_sync(&self, policy: &str) -> WarpResult<()> {
+        WarpClient::set_override_sync(self, policy)
+    }
+
+    fn clear_override_sync(&self) -> WarpResult<()> {
+        WarpClient::clear_override_sync(self)
+    }
+
+    fn get_registration_sync(&self) -> WarpResult<RegistrationInfo> {
+        WarpClient::get_registration_sync(self)
+    }
+
+    fn create_registration_sync(&self) -> WarpResult<RegistrationInfo> {
+        WarpClient::create_registration_sync(self)
+    }
+
+    fn current_ssid_sync(&self) -> WarpResult<String> {
+        WarpClient::current_ssid_sync(self)
+    }
+
+    fn list_trusted_ssids_sync(&self) -> WarpResult<Vec<String>> {
+        WarpClient::list_trusted_ssids_sync(self)
+    }
+
+    fn add_trusted_ssid_sync(&self, ssid: &str) -> WarpResult<()> {
+        WarpClient::add_trusted_ssid_sync(self, ssid)
+    }
+
+    fn remove_trusted_ssid_sync(&self, ssid: &str) -> WarpResult<()> {
+        WarpClient::remove_trusted_ssid_sync(self, ssid)
+    }
+
+    fn run_connectivity_check_sync(&self) -> WarpResult<String> {
+        WarpClient::run_connectivity_check_sync(self)
+    }
+
+    fn get_dns_stats_sync(&self) -> WarpResult<DnsStats> {
+        WarpClient::get_dns_stats_sync(self)
+    }
+
+    fn get_tunnel_stats_sync(&self) -> WarpResult<TunnelStats> {
+        WarpClient::get_tunnel_stats_sync(self)
+    }
+
+    fn timeout(&self) -> Duration {
+        WarpClient::timeout(self)
+    }
+
+    fn set_timeout(&self, timeout: Duration) {
+        WarpClient::set_timeout(self, timeout)
+    }
+
+    fn set_capture_raw_status(&self, capture_raw_status: bool) {
+        WarpClient::set_capture_raw_status(self, capture_raw_status)
+    }
+
+    fn is_dry_run(&self) -> bool {
+        WarpClient::is_dry_run(self)
+    }
+
+    fn cancellable(&self, token: CancelToken) -> Arc<dyn WarpBackend> {
+        Arc::new(self.clone().with_cancel_token(token))
+    }
+}
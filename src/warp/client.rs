@@ -1,26 +1,151 @@
 use serde::Deserialize;
-use std::process::Command;
-use std::time::Duration;
+use std::io::Read;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::process::Command as AsyncCommand;
 use tokio::time::timeout;
 
 use crate::warp::error::{WarpError, WarpResult};
-use crate::warp::types::{RegistrationInfo, WarpInfo, WarpMode, WarpStatus};
+use crate::warp::types::{
+    AccountInfo, CommandOutput, ConnectOutcome, DaemonStatus, DisconnectOutcome, DnsStats,
+    FamiliesMode, NetworkInfo, NetworkType, RegistrationInfo, SettingChange, SettingsBatch,
+    TunnelProtocol, TunnelStats, WarpInfo, WarpMode, WarpSettingsInfo, WarpStats, WarpStatus,
+    mask_license_key,
+};
 
+/// Output of `warp-cli --json status`
 #[derive(Debug, Deserialize)]
-struct WarpSettings {
-    settings: Settings,
+struct StatusJson {
+    status: String,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    account_type: Option<String>,
+    #[serde(default)]
+    warp_enabled: bool,
+    #[serde(default)]
+    gateway_enabled: bool,
+    #[serde(default)]
+    endpoint: Option<String>,
+    #[serde(default)]
+    tunnel_ip: Option<String>,
+    #[serde(default, rename = "override")]
+    override_state: Option<String>,
+    /// Connected network, the counterpart of the "Network:" line
+    #[serde(default)]
+    network: Option<String>,
+    /// SSID of the connected Wi-Fi network, the counterpart of the "SSID:" line
+    #[serde(default)]
+    ssid: Option<String>,
+    #[serde(default)]
+    trusted_networks: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
-struct Settings {
-    operation_mode: String,
+struct WarpSettingsOutput {
+    settings: WarpSettingsInfo,
 }
 
-#[derive(Clone, Debug)]
+/// Where versions of `warp-cli --json settings` put the operation mode, as JSON pointers
+const OPERATION_MODE_PATHS: &[&str] = &[
+    "/settings/operation_mode",
+    "/operation_mode",
+    "/settings/mode",
+    "/mode",
+];
+
+/// Delays between retries of a command that failed with a transient error
+const RETRY_BACKOFF: [Duration; 3] = [
+    Duration::from_millis(200),
+    Duration::from_millis(400),
+    Duration::from_millis(800),
+];
+
+/// Error messages from warp-cli that are worth retrying
+const TRANSIENT_ERROR_PATTERNS: &[&str] = &[
+    "daemon not ready",
+    "daemon is not ready",
+    "try again",
+    "temporarily unavailable",
+    "connection refused",
+];
+
+/// Error messages from warp-cli when the daemon isn't running
+const DAEMON_DOWN_PATTERNS: &[&str] = &[
+    "unable to connect to the cloudflarewarp daemon",
+    "daemon is not running",
+];
+
+/// Time the daemon gets to answer a health check, unless the command timeout is shorter
+pub const DAEMON_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Binary invoked when no other path is configured
+const DEFAULT_BINARY: &str = "warp-cli";
+
+/// Environment variable overriding the warp-cli binary, e.g. `/opt/cloudflare-warp/bin/warp-cli`
+pub const BINARY_PATH_ENV: &str = "WARP_CLI_PATH";
+
+/// Default time a warp-cli command may run before it's killed
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default number of lines kept from the output of diagnostic commands
+pub const DEFAULT_MAX_OUTPUT_LINES: usize = 1000;
+
+/// How often [`WarpClient::disconnect_and_wait_sync`] checks whether the tunnel is down
+pub const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug)]
 pub struct WarpClient {
-    #[allow(dead_code)] // Used for async operations which may be used in the future
-    command_timeout: Duration,
+    /// In milliseconds, shared by all clones so changing it applies to each of them
+    command_timeout: Arc<AtomicU64>,
+    binary_path: PathBuf,
+    prefer_json: bool,
+    max_output_lines: usize,
+    /// Only set on the instance built by [`WarpClientBuilder::build`], never on clones
+    disconnect_on_drop: bool,
+    /// Log mutating commands instead of running them
+    dry_run: bool,
+    /// Kills the running command and fails the next ones once cancelled
+    cancel: Option<CancelToken>,
+    /// Keep the unparsed status output in [`WarpInfo::raw`], shared by all clones
+    capture_raw_status: Arc<AtomicBool>,
+}
+
+impl Clone for WarpClient {
+    /// Clones share the configuration but never disconnect on drop, only the original does
+    fn clone(&self) -> Self {
+        Self {
+            command_timeout: Arc::clone(&self.command_timeout),
+            binary_path: self.binary_path.clone(),
+            prefer_json: self.prefer_json,
+            max_output_lines: self.max_output_lines,
+            disconnect_on_drop: false,
+            dry_run: self.dry_run,
+            cancel: self.cancel.clone(),
+            capture_raw_status: Arc::clone(&self.capture_raw_status),
+        }
+    }
+}
+
+impl Drop for WarpClient {
+    /// Disconnect if [`WarpClientBuilder::disconnect_on_drop`] was set
+    ///
+    /// Errors can't be returned from `drop`, so a failed disconnect is logged and WARP may
+    /// stay connected.
+    fn drop(&mut self) {
+        if !self.disconnect_on_drop {
+            return;
+        }
+        match self.disconnect_sync() {
+            Ok(outcome) => tracing::debug!(?outcome, "disconnected on drop"),
+            Err(e) => tracing::warn!(error = %e, "failed to disconnect on drop"),
+        }
+    }
 }
 
 impl Default for WarpClient {
@@ -29,28 +154,182 @@ impl Default for WarpClient {
     }
 }
 
-impl WarpClient {
-    pub fn new() -> Self {
+/// Builder for a [`WarpClient`] with a custom configuration
+#[derive(Clone, Debug)]
+pub struct WarpClientBuilder {
+    command_timeout: Duration,
+    binary_path: PathBuf,
+    prefer_json: bool,
+    max_output_lines: usize,
+    disconnect_on_drop: bool,
+    dry_run: bool,
+    capture_raw_status: bool,
+}
+
+impl Default for WarpClientBuilder {
+    fn default() -> Self {
         Self {
-            command_timeout: Duration::from_secs(30),
+            command_timeout: DEFAULT_TIMEOUT,
+            binary_path: default_binary_path(),
+            prefer_json: true,
+            max_output_lines: DEFAULT_MAX_OUTPUT_LINES,
+            disconnect_on_drop: false,
+            dry_run: false,
+            capture_raw_status: false,
         }
     }
+}
 
-    #[allow(dead_code)] // May be used in future async implementations
-    pub fn with_timeout(timeout_secs: u64) -> Self {
-        Self {
-            command_timeout: Duration::from_secs(timeout_secs),
+impl WarpClientBuilder {
+    /// Time a command may run before it's killed
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.command_timeout = timeout;
+        self
+    }
+
+    /// warp-cli binary to run, looked up in `PATH` unless it contains a directory
+    ///
+    /// Defaults to [`BINARY_PATH_ENV`] when set. Otherwise Windows uses the standard
+    /// install location when warp-cli is there, since it isn't added to `PATH`.
+    pub fn binary_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.binary_path = path.into();
+        self
+    }
+
+    /// Try `warp-cli --json` before falling back to scraping the plain output
+    pub fn prefer_json(mut self, prefer_json: bool) -> Self {
+        self.prefer_json = prefer_json;
+        self
+    }
+
+    /// Lines kept from the output of diagnostic commands, the rest is dropped
+    pub fn max_output_lines(mut self, max_output_lines: usize) -> Self {
+        self.max_output_lines = max_output_lines;
+        self
+    }
+
+    /// Disconnect when the built client is dropped, so short-lived tools don't leave WARP
+    /// connected
+    ///
+    /// Only the built instance disconnects, clones of it don't. Failures are logged since
+    /// `drop` can't report them.
+    pub fn disconnect_on_drop(mut self, disconnect_on_drop: bool) -> Self {
+        self.disconnect_on_drop = disconnect_on_drop;
+        self
+    }
+
+    /// Log commands that change the WARP state or settings instead of running them, and
+    /// report them as successful
+    ///
+    /// Read-only commands such as `status` still run, so the client can be demoed or
+    /// tested without touching the system.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Keep the unparsed output of `warp-cli status` in [`WarpInfo::raw`], for
+    /// troubleshooting the parser
+    pub fn capture_raw_status(mut self, capture_raw_status: bool) -> Self {
+        self.capture_raw_status = capture_raw_status;
+        self
+    }
+
+    pub fn build(self) -> WarpClient {
+        WarpClient {
+            command_timeout: Arc::new(AtomicU64::new(duration_to_millis(self.command_timeout))),
+            binary_path: self.binary_path,
+            prefer_json: self.prefer_json,
+            max_output_lines: self.max_output_lines,
+            disconnect_on_drop: self.disconnect_on_drop,
+            dry_run: self.dry_run,
+            cancel: None,
+            capture_raw_status: Arc::new(AtomicBool::new(self.capture_raw_status)),
         }
     }
+}
+
+impl WarpClient {
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    /// Start configuring a client
+    pub fn builder() -> WarpClientBuilder {
+        WarpClientBuilder::default()
+    }
+
+    pub fn with_timeout(timeout_secs: u64) -> Self {
+        Self::builder()
+            .timeout(Duration::from_secs(timeout_secs))
+            .build()
+    }
+
+    /// Time a command may run before it's killed
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.command_timeout.load(Ordering::Relaxed))
+    }
+
+    /// Change the time a command may run before it's killed
+    ///
+    /// Clones share the timeout, so this applies to every clone of this client too, such
+    /// as the one a [`crate::warp::manager::WarpManager`] runs its commands with.
+    pub fn set_timeout(&self, timeout: Duration) {
+        self.command_timeout
+            .store(duration_to_millis(timeout), Ordering::Relaxed);
+    }
+
+    /// Is the unparsed status output kept in [`WarpInfo::raw`]?
+    pub fn captures_raw_status(&self) -> bool {
+        self.capture_raw_status.load(Ordering::Relaxed)
+    }
+
+    /// Start or stop keeping the unparsed status output in [`WarpInfo::raw`]
+    ///
+    /// Like the timeout, this applies to every clone of this client.
+    pub fn set_capture_raw_status(&self, capture_raw_status: bool) {
+        self.capture_raw_status
+            .store(capture_raw_status, Ordering::Relaxed);
+    }
+
+    /// warp-cli binary run by this client
+    pub fn binary_path(&self) -> &Path {
+        &self.binary_path
+    }
+
+    /// Are mutating commands only logged instead of run?
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Stop commands run by this client once `token` is cancelled
+    ///
+    /// The running warp-cli process is killed and it, and every command after it, fails
+    /// with [`WarpError::Cancelled`].
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Does this client try `warp-cli --json` before the plain output?
+    pub fn prefers_json(&self) -> bool {
+        self.prefer_json
+    }
+
+    /// A synchronous warp-cli command
+    fn command(&self) -> Command {
+        Command::new(&self.binary_path)
+    }
+
+    /// An asynchronous warp-cli command
+    fn async_command(&self) -> AsyncCommand {
+        AsyncCommand::new(&self.binary_path)
+    }
 
     /// Check if warp-cli is available in the system
     #[allow(dead_code)] // May be used in future async implementations
     pub async fn is_available(&self) -> bool {
-        match AsyncCommand::new("warp-cli")
-            .arg("--version")
-            .output()
-            .await
-        {
+        match self.async_command().arg("--version").output().await {
             Ok(output) => output.status.success(),
             Err(_) => false,
         }
@@ -63,71 +342,155 @@ impl WarpClient {
             return Err(WarpError::CommandNotFound);
         }
 
-        let command_future = AsyncCommand::new("warp-cli").args(args).output();
+        let command_future = self.async_command().args(args).output();
 
-        let output = timeout(self.command_timeout, command_future)
+        let command_timeout = self.timeout();
+        let result = timeout(command_timeout, command_future)
             .await
-            .map_err(|_| WarpError::CommandFailed("Command timed out".to_string()))?
-            .map_err(WarpError::IoError)?;
+            .map_err(|_| {
+                let command = format!("warp-cli {}", redact_args(args).join(" "));
+                WarpError::timed_out(&command, command_timeout)
+            })
+            .and_then(|output| output.map_err(WarpError::IoError));
+        log_command_result(args, &result);
+        let output = result?;
 
         if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(WarpError::CommandFailed(error_msg.to_string()));
+            return Err(WarpError::CommandFailed {
+                code: output.status.code(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         Ok(stdout.trim().to_string())
     }
 
+    /// Execute a warp-cli command that changes the WARP state or settings, only logging it
+    /// in dry-run mode
+    #[allow(dead_code)] // May be used in future async implementations
+    async fn execute_mutating(&self, args: &[&str]) -> WarpResult<String> {
+        if self.dry_run {
+            log_dry_run(args);
+            return Ok(String::new());
+        }
+        self.execute_command(args).await
+    }
+
     /// Get current warp status and information
     #[allow(dead_code)] // May be used in future async implementations
     pub async fn get_status(&self) -> WarpResult<WarpInfo> {
+        if self.prefer_json {
+            match self.execute_command(&["--json", "status"]).await {
+                Ok(output) => {
+                    if let Ok(info) = self.parse_status_json(&output) {
+                        return self
+                            .with_operation_mode(info)
+                            .map(|info| self.with_raw_status(info, output));
+                    }
+                }
+                Err(WarpError::CommandNotFound) => return Err(WarpError::CommandNotFound),
+                // Older warp-cli versions don't support --json, fall back to plain output
+                Err(_) => {}
+            }
+        }
+
         let output = self.execute_command(&["status"]).await?;
         self.parse_status_output(&output)
+            .map(|info| self.with_raw_status(info, output))
+    }
+
+    /// Get the status, along with the connection statistics while connected
+    ///
+    /// Statistics that can't be fetched are left out instead of failing the status, see
+    /// [`WarpClient::get_status_with_stats_sync`].
+    pub async fn get_status_with_stats(&self) -> WarpResult<(WarpInfo, Option<WarpStats>)> {
+        let info = self.get_status().await?;
+        if !info.is_connected() {
+            return Ok((info, None));
+        }
+
+        let stats = match self.get_warp_stats().await {
+            Ok(stats) => Some(stats),
+            Err(e) => {
+                tracing::debug!(error = %e, "warp-stats unavailable");
+                None
+            }
+        };
+        Ok((info, stats))
+    }
+
+    /// Get the tunnel statistics of the current connection
+    ///
+    /// Returns [`WarpError::NotConnected`] when WARP isn't connected.
+    pub async fn get_warp_stats(&self) -> WarpResult<WarpStats> {
+        match self.execute_command(&["--json", "warp-stats"]).await {
+            Ok(output) => self.parse_warp_stats(&output),
+            Err(WarpError::CommandFailed { stderr: msg, .. }) if is_not_connected(&msg) => {
+                Err(WarpError::NotConnected)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Poll the status every `interval`, starting right away, and send each result
+    ///
+    /// Polling runs on a task spawned on the current tokio runtime and stops once the
+    /// receiver is dropped. A consumer that falls behind holds up the next poll instead
+    /// of letting results pile up.
+    ///
+    /// # Panics
+    ///
+    /// When called outside of a tokio runtime.
+    pub fn watch(&self, interval: Duration) -> tokio::sync::mpsc::Receiver<WarpResult<WarpInfo>> {
+        let client = self.clone();
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {}
+                    _ = sender.closed() => break,
+                }
+                if sender.send(client.get_status().await).await.is_err() {
+                    break;
+                }
+            }
+        });
+        receiver
     }
 
     /// Create a new registration
     #[allow(dead_code)] // May be used in future async implementations
     pub async fn create_registration(&self) -> WarpResult<RegistrationInfo> {
-        let output = self.execute_command(&["registration", "new"]).await?;
+        let output = self.execute_mutating(&["registration", "new"]).await?;
         self.parse_registration_output(&output)
     }
 
     /// Delete the current registration
     #[allow(dead_code)] // May be used in future async implementations
     pub async fn delete_registration(&self) -> WarpResult<()> {
-        self.execute_command(&["registration", "delete"]).await?;
+        self.execute_mutating(&["registration", "delete"]).await?;
         Ok(())
     }
 
     /// Connect to warp
     #[allow(dead_code)] // May be used in future async implementations
-    pub async fn connect(&self) -> WarpResult<()> {
-        match self.execute_command(&["connect"]).await {
-            Ok(_) => Ok(()),
-            Err(WarpError::CommandFailed(msg)) => {
-                if msg.contains("already connected") {
-                    Ok(()) // Already connected is not an error
-                } else {
-                    Err(WarpError::ConnectionFailed(msg))
-                }
-            }
+    pub async fn connect(&self) -> WarpResult<ConnectOutcome> {
+        match self.execute_mutating(&["connect"]).await {
+            Ok(_) => Ok(ConnectOutcome::Connected),
+            Err(WarpError::CommandFailed { stderr, .. }) => self.parse_connect_error(&stderr),
             Err(e) => Err(e),
         }
     }
 
     /// Disconnect from warp
     #[allow(dead_code)] // May be used in future async implementations
-    pub async fn disconnect(&self) -> WarpResult<()> {
-        match self.execute_command(&["disconnect"]).await {
-            Ok(_) => Ok(()),
-            Err(WarpError::CommandFailed(msg)) => {
-                if msg.contains("already disconnected") {
-                    Ok(()) // Already disconnected is not an error
-                } else {
-                    Err(WarpError::DisconnectionFailed(msg))
-                }
-            }
+    pub async fn disconnect(&self) -> WarpResult<DisconnectOutcome> {
+        match self.execute_mutating(&["disconnect"]).await {
+            Ok(_) => Ok(DisconnectOutcome::Disconnected),
+            Err(WarpError::CommandFailed { stderr, .. }) => self.parse_disconnect_error(&stderr),
             Err(e) => Err(e),
         }
     }
@@ -141,108 +504,532 @@ impl WarpClient {
     /// Set DNS mode
     #[allow(dead_code)] // May be used in future async implementations
     pub async fn set_mode(&self, mode: &str) -> WarpResult<()> {
-        self.execute_command(&["set-mode", mode]).await?;
+        self.execute_mutating(&["set-mode", mode]).await?;
         Ok(())
     }
 
     /// Set mode synchronously
+    ///
+    /// `mode` must be one of the modes in [`WarpMode::ALL`] (see [`WarpMode::from_cli_str`]
+    /// for the accepted spellings); anything else is rejected without invoking warp-cli.
     pub fn set_mode_sync(&self, mode: &str) -> WarpResult<()> {
-        let output = Command::new("warp-cli")
-            .args(["mode", mode])
-            .output()
-            .map_err(|e| {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    WarpError::CommandNotFound
-                } else {
-                    WarpError::IoError(e)
-                }
-            })?;
+        let warp_mode = WarpMode::from_cli_str(mode);
+        if warp_mode == WarpMode::Unknown {
+            let valid: Vec<&str> = WarpMode::ALL.iter().map(|m| m.as_cli_str()).collect();
+            return Err(WarpError::command_failed(format!(
+                "unknown mode: {} (expected one of: {})",
+                mode,
+                valid.join(", ")
+            )));
+        }
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(WarpError::CommandFailed(error_msg.to_string()));
+        self.execute_mutating_sync(&["mode", warp_mode.as_cli_str()])?;
+        Ok(())
+    }
+
+    /// Set the DNS families (parental control) mode
+    ///
+    /// `mode` must be one of `off`, `malware` or `full`; anything else is rejected
+    /// without invoking warp-cli.
+    pub fn set_families_mode_sync(&self, mode: &str) -> WarpResult<()> {
+        let families_mode = FamiliesMode::from_cli_str(mode).ok_or_else(|| {
+            let valid: Vec<&str> = FamiliesMode::ALL.iter().map(|m| m.as_cli_str()).collect();
+            WarpError::command_failed(format!(
+                "unknown families mode: {} (expected one of: {})",
+                mode,
+                valid.join(", ")
+            ))
+        })?;
+
+        self.execute_mutating_sync(&["dns", "families", families_mode.as_cli_str()])?;
+        Ok(())
+    }
+
+    /// Set the tunnel protocol
+    ///
+    /// `protocol` must be `WireGuard` or `MASQUE` (case-insensitive); anything else is
+    /// rejected without invoking warp-cli.
+    pub fn set_tunnel_protocol_sync(&self, protocol: &str) -> WarpResult<()> {
+        let tunnel_protocol = TunnelProtocol::from_cli_str(protocol).ok_or_else(|| {
+            let valid: Vec<&str> = TunnelProtocol::ALL.iter().map(|p| p.as_cli_str()).collect();
+            WarpError::command_failed(format!(
+                "unknown tunnel protocol: {} (expected one of: {})",
+                protocol,
+                valid.join(", ")
+            ))
+        })?;
+
+        self.execute_mutating_sync(&["tunnel", "protocol", "set", tunnel_protocol.as_cli_str()])?;
+        Ok(())
+    }
+
+    /// Enable or disable the WARP tunnel
+    ///
+    /// Returns [`WarpError::Unsupported`] if the installed warp-cli lacks the setting.
+    pub fn set_warp_enabled_sync(&self, enabled: bool) -> WarpResult<()> {
+        self.set_enabled_setting_sync("warp-enabled", enabled)
+    }
+
+    /// Enable or disable Gateway DNS filtering
+    ///
+    /// Returns [`WarpError::Unsupported`] if the installed warp-cli lacks the setting.
+    pub fn set_gateway_enabled_sync(&self, enabled: bool) -> WarpResult<()> {
+        self.set_enabled_setting_sync("gateway-enabled", enabled)
+    }
+
+    /// Run `warp-cli settings <setting> <true|false>`
+    fn set_enabled_setting_sync(&self, setting: &str, enabled: bool) -> WarpResult<()> {
+        let value = if enabled { "true" } else { "false" };
+        match self.execute_mutating_sync(&["settings", setting, value]) {
+            Ok(_) => Ok(()),
+            Err(WarpError::CommandFailed { stderr: msg, .. }) if is_unsupported_command(&msg) => {
+                Err(WarpError::Unsupported(format!(
+                    "settings {} ({})",
+                    setting,
+                    msg.trim()
+                )))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Add a Wi-Fi network to the trusted SSIDs, on which WARP disconnects automatically
+    pub fn add_trusted_ssid_sync(&self, ssid: &str) -> WarpResult<()> {
+        let ssid = validate_ssid(ssid)?;
+        self.execute_mutating_sync(&["settings", "add-trusted-ssid", ssid])?;
+        Ok(())
+    }
+
+    /// Remove a Wi-Fi network from the trusted SSIDs
+    pub fn remove_trusted_ssid_sync(&self, ssid: &str) -> WarpResult<()> {
+        let ssid = validate_ssid(ssid)?;
+        self.execute_mutating_sync(&["settings", "del-trusted-ssid", ssid])?;
+        Ok(())
+    }
+
+    /// SSID of the Wi-Fi network this device is on, read from the plain `warp-cli status`
+    /// output since the JSON status doesn't list networks
+    pub fn current_ssid_sync(&self) -> WarpResult<String> {
+        let output = self.execute_command_sync(&["status"])?;
+        self.current_ssid(&self.parse_status_text(&output))
+    }
+
+    /// The SSID of the connected Wi-Fi network in `info`
+    ///
+    /// Wired networks have no SSID, so being connected only over Ethernet is an error
+    /// naming the interface, as is having no connected network at all.
+    pub fn current_ssid(&self, info: &WarpInfo) -> WarpResult<String> {
+        let wifi = info
+            .networks
+            .iter()
+            .find(|network| network.network_type == NetworkType::Wifi)
+            .or_else(|| {
+                info.networks
+                    .iter()
+                    .find(|network| network.network_type == NetworkType::Unknown)
+            });
+        match (wifi, info.networks.first()) {
+            (Some(network), _) => Ok(network.name.clone()),
+            (None, Some(wired)) => Err(WarpError::command_failed(format!(
+                "connected over the wired network {}, only Wi-Fi networks can be trusted",
+                wired.name
+            ))),
+            (None, None) => Err(WarpError::command_failed(
+                "no connected Wi-Fi network found",
+            )),
         }
+    }
+
+    /// Add the Wi-Fi network this device is on to the trusted SSIDs, returning its SSID
+    pub fn trust_current_network_sync(&self) -> WarpResult<String> {
+        let ssid = self.current_ssid_sync()?;
+        self.add_trusted_ssid_sync(&ssid)?;
+        Ok(ssid)
+    }
+
+    /// Remove the Wi-Fi network this device is on from the trusted SSIDs, returning its SSID
+    pub fn untrust_current_network_sync(&self) -> WarpResult<String> {
+        let ssid = self.current_ssid_sync()?;
+        self.remove_trusted_ssid_sync(&ssid)?;
+        Ok(ssid)
+    }
+
+    /// List the trusted SSIDs from warp-cli settings
+    pub fn list_trusted_ssids_sync(&self) -> WarpResult<Vec<String>> {
+        let output = self.execute_command_sync(&["settings"])?;
+        Ok(self.parse_trusted_ssids(&output))
+    }
+
+    /// Parse the trusted SSIDs from the plain `warp-cli settings` output.
+    ///
+    /// SSIDs are listed on a `Trusted SSIDs:` line, either quoted (`"Home Wifi", "Office"`)
+    /// or as a plain comma separated list.
+    pub fn parse_trusted_ssids(&self, output: &str) -> Vec<String> {
+        let Some(value) = output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case("trusted ssids")
+                .then(|| value.trim())
+        }) else {
+            return Vec::new();
+        };
+
+        let ssids: Vec<String> = if value.contains('"') {
+            // Every other segment between quotes is an SSID
+            value
+                .split('"')
+                .skip(1)
+                .step_by(2)
+                .map(str::to_string)
+                .collect()
+        } else {
+            value.split(',').map(|s| s.trim().to_string()).collect()
+        };
+
+        ssids
+            .into_iter()
+            .filter(|ssid| !ssid.trim().is_empty())
+            .collect()
+    }
 
+    /// Exclude an IP range (in CIDR notation) from the WARP tunnel
+    pub fn add_excluded_route_sync(&self, cidr: &str) -> WarpResult<()> {
+        validate_cidr(cidr)?;
+        self.execute_mutating_sync(&["tunnel", "ip", "add", cidr.trim()])?;
         Ok(())
     }
 
+    /// Remove an IP range (in CIDR notation) from the excluded routes
+    pub fn remove_excluded_route_sync(&self, cidr: &str) -> WarpResult<()> {
+        validate_cidr(cidr)?;
+        self.execute_mutating_sync(&["tunnel", "ip", "remove", cidr.trim()])?;
+        Ok(())
+    }
+
+    /// List the IP ranges excluded from the WARP tunnel
+    pub fn list_excluded_routes_sync(&self) -> WarpResult<Vec<String>> {
+        let output = self.execute_command_sync(&["tunnel", "ip", "list"])?;
+        Ok(self.parse_excluded_routes(&output))
+    }
+
+    /// Parse the routes from the `warp-cli tunnel ip list` output.
+    ///
+    /// Each route starts its line and may be followed by a description; other lines are skipped.
+    pub fn parse_excluded_routes(&self, output: &str) -> Vec<String> {
+        output
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter(|route| validate_cidr(route).is_ok())
+            .map(str::to_string)
+            .collect()
+    }
+
     /// Get the current operation mode from warp-cli settings
     pub fn get_operation_mode(&self) -> WarpResult<WarpMode> {
-        let output = Command::new("warp-cli")
-            .args(["--json", "settings"])
-            .output()
-            .map_err(|e| WarpError::CommandFailed(e.to_string()))?;
-
-        if !output.status.success() {
-            return Err(WarpError::CommandFailed(
-                String::from_utf8_lossy(&output.stderr).to_string(),
-            ));
+        if self.prefer_json {
+            match self.execute_command_sync(&["--json", "settings"]) {
+                Ok(output) => {
+                    if let Ok(mode) = self.parse_mode_from_json(&output) {
+                        return Ok(mode);
+                    }
+                }
+                Err(WarpError::CommandNotFound) => return Err(WarpError::CommandNotFound),
+                // Older warp-cli versions don't support --json, fall back to plain output
+                Err(_) => {}
+            }
         }
 
-        let settings: WarpSettings = serde_json::from_slice(&output.stdout)
-            .map_err(|e| WarpError::ParseError(e.to_string()))?;
+        let output = self.execute_command_sync(&["settings"])?;
+        self.parse_mode_from_text(&output).ok_or_else(|| {
+            WarpError::ParseError("no mode in the warp-cli settings output".to_string())
+        })
+    }
+
+    /// Parse the operation mode from the `warp-cli --json settings` output
+    ///
+    /// Versions of warp-cli nest the mode differently, so every known location is tried,
+    /// see [`OPERATION_MODE_PATHS`]. The value may be a string or an object with a
+    /// `value` string. Valid JSON without a mode anywhere yields [`WarpMode::Unknown`].
+    pub fn parse_mode_from_json(&self, output: &str) -> WarpResult<WarpMode> {
+        let settings: serde_json::Value =
+            serde_json::from_str(output).map_err(|e| WarpError::ParseError(e.to_string()))?;
+
+        let mode = OPERATION_MODE_PATHS
+            .iter()
+            .filter_map(|path| settings.pointer(path))
+            .find_map(|value| {
+                value
+                    .as_str()
+                    .or_else(|| value.get("value").and_then(serde_json::Value::as_str))
+            });
+        Ok(mode.map_or(WarpMode::Unknown, WarpMode::from_cli_str))
+    }
 
-        Ok(match settings.settings.operation_mode.as_str() {
-            "warp+dot" => WarpMode::WarpDoT,
-            "dot" => WarpMode::DoT,
-            "doh" => WarpMode::DoH,
-            "warp+doh" => WarpMode::WarpDoH,
-            _ => WarpMode::Unknown,
+    /// Parse the operation mode from the plain `warp-cli settings` output
+    ///
+    /// The mode is on a line like `Mode: warp+doh`, which newer versions prefix with where
+    /// the setting comes from, e.g. `(default) Mode: WarpWithDnsOverHttps`. Returns `None`
+    /// if there's no such line.
+    pub fn parse_mode_from_text(&self, output: &str) -> Option<WarpMode> {
+        output.lines().find_map(|line| {
+            let line = line.trim();
+            let line = match line.strip_prefix('(') {
+                Some(rest) => rest.split_once(')').map_or(rest, |(_, line)| line),
+                None => line,
+            };
+            let (label, value) = line.split_once(':')?;
+            label
+                .trim()
+                .eq_ignore_ascii_case("mode")
+                .then(|| WarpMode::from_cli_str(value))
         })
     }
 
     /// Get the current operation mode asynchronously
     pub async fn get_operation_mode_async(&self) -> WarpResult<WarpMode> {
+        let command_timeout = self.timeout();
         let output = timeout(
-            self.command_timeout,
-            AsyncCommand::new("warp-cli")
-                .args(["--json", "settings"])
-                .output(),
+            command_timeout,
+            self.async_command().args(["--json", "settings"]).output(),
         )
         .await
-        .map_err(|e| WarpError::Timeout(e.to_string()))??;
+        .map_err(|_| WarpError::timed_out("warp-cli --json settings", command_timeout))??;
 
         if !output.status.success() {
-            return Err(WarpError::CommandFailed(
-                String::from_utf8_lossy(&output.stderr).to_string(),
-            ));
+            return Err(WarpError::CommandFailed {
+                code: output.status.code(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
         }
 
-        let settings: WarpSettings = serde_json::from_slice(&output.stdout)
-            .map_err(|e| WarpError::ParseError(e.to_string()))?;
+        self.parse_mode_from_json(&String::from_utf8_lossy(&output.stdout))
+    }
 
-        Ok(match settings.settings.operation_mode.as_str() {
-            "warp+dot" => WarpMode::WarpDoT,
-            "dot" => WarpMode::DoT,
-            "doh" => WarpMode::DoH,
-            "warp+doh" => WarpMode::WarpDoH,
-            _ => WarpMode::Unknown,
-        })
+    /// Apply every setting of `batch`, restoring the previous values if one of them fails
+    ///
+    /// The current settings are read first. When a step fails, the steps before it are
+    /// undone in reverse order and [`WarpError::BatchFailed`] names the step and whether
+    /// undoing them worked. A setting warp-cli didn't report a value for can't be restored,
+    /// which counts as a failed rollback.
+    pub fn apply_settings_batch(&self, batch: &SettingsBatch) -> WarpResult<()> {
+        let previous = self.get_settings_parsed()?;
+        let mut applied = Vec::new();
+        for change in batch.changes() {
+            if let Err(e) = self.apply_setting_change(&change) {
+                let rolled_back = self.roll_back(&applied, &previous);
+                return Err(WarpError::BatchFailed {
+                    step: change.to_string(),
+                    source: Box::new(e),
+                    rolled_back,
+                });
+            }
+            applied.push(change);
+        }
+        Ok(())
+    }
+
+    /// Undo `applied` in reverse order, returning whether every setting was restored
+    fn roll_back(&self, applied: &[SettingChange], previous: &WarpSettingsInfo) -> bool {
+        let mut restored = true;
+        for change in applied.iter().rev() {
+            let result = match change.previous(previous) {
+                Some(undo) => self.apply_setting_change(&undo),
+                None => Err(WarpError::command_failed("previous value unknown")),
+            };
+            if let Err(e) = result {
+                tracing::warn!(error = %e, "failed to undo {}", change);
+                restored = false;
+            }
+        }
+        restored
+    }
+
+    fn apply_setting_change(&self, change: &SettingChange) -> WarpResult<()> {
+        match change {
+            SettingChange::Mode(mode) => self.set_mode_sync(mode.as_cli_str()),
+            SettingChange::FamiliesMode(mode) => self.set_families_mode_sync(mode.as_cli_str()),
+            SettingChange::TunnelProtocol(protocol) => {
+                self.set_tunnel_protocol_sync(protocol.as_cli_str())
+            }
+        }
+    }
+
+    /// Get the full warp-cli settings as a typed struct
+    pub fn get_settings_parsed(&self) -> WarpResult<WarpSettingsInfo> {
+        let output = self.execute_command_sync(&["--json", "settings"])?;
+        self.parse_settings_output(&output)
+    }
+
+    /// Parse the `warp-cli --json settings` output into WarpSettingsInfo struct
+    pub fn parse_settings_output(&self, output: &str) -> WarpResult<WarpSettingsInfo> {
+        let settings: WarpSettingsOutput =
+            serde_json::from_str(output).map_err(|e| WarpError::ParseError(e.to_string()))?;
+        Ok(settings.settings)
     }
 
     /// Parse the status command output into WarpInfo struct
     pub fn parse_status_output(&self, output: &str) -> WarpResult<WarpInfo> {
+        self.with_operation_mode(self.parse_status_text(output))
+    }
+
+    /// Fill in the operation mode, which the status command doesn't report
+    fn with_operation_mode(&self, info: WarpInfo) -> WarpResult<WarpInfo> {
         let mode = Some(self.get_operation_mode()?);
-        let mut info = WarpInfo {
-            mode,
-            ..Default::default()
-        };
+        Ok(WarpInfo { mode, ..info })
+    }
 
-        for line in output.lines() {
-            let line = line.trim();
+    /// Keep `output` as the raw status if enabled with [`WarpClient::set_capture_raw_status`]
+    fn with_raw_status(&self, info: WarpInfo, output: String) -> WarpInfo {
+        if self.captures_raw_status() {
+            WarpInfo {
+                raw: Some(output),
+                ..info
+            }
+        } else {
+            info
+        }
+    }
+
+    /// Parse the `warp-cli --json status` output without looking up the operation mode
+    pub fn parse_status_json(&self, output: &str) -> WarpResult<WarpInfo> {
+        let status: StatusJson =
+            serde_json::from_str(output).map_err(|e| WarpError::ParseError(e.to_string()))?;
+
+        // Same order as the plain output: connected networks, then the trusted ones
+        let mut connected_networks = Vec::new();
+        let mut networks = Vec::new();
+        let connected = [
+            (status.network.as_deref(), None),
+            (status.ssid.as_deref(), Some(NetworkType::Wifi)),
+        ];
+        for (network, network_type) in connected {
+            if let Some(network) = network {
+                push_network(&mut connected_networks, network);
+                push_network_info(&mut networks, network, false, network_type);
+            }
+        }
+        for network in &status.trusted_networks {
+            push_network(&mut connected_networks, network);
+            push_network_info(&mut networks, network, true, None);
+        }
 
-            if line.starts_with("Status update:") || line.contains("Status:") {
+        Ok(WarpInfo {
+            status: status_from_str(&status.status),
+            reason: status.reason.filter(|reason| !reason.is_empty()),
+            account_type: status.account_type,
+            warp_enabled: status.warp_enabled,
+            gateway_enabled: status.gateway_enabled,
+            connected_networks,
+            networks,
+            endpoint: status.endpoint,
+            tunnel_ip: status.tunnel_ip,
+            override_state: status.override_state.filter(|state| !state.is_empty()),
+            ..WarpInfo::default()
+        })
+    }
+
+    /// Parse the status command output without looking up the operation mode
+    pub fn parse_status_text(&self, output: &str) -> WarpInfo {
+        let mut info = WarpInfo::default();
+        // Header of the block being parsed and its indentation; lines indented deeper
+        // than the header belong to it
+        let mut section: Option<(StatusSection, usize)> = None;
+
+        for raw_line in output.lines() {
+            let line = raw_line.trim();
+            let indent = raw_line.len() - raw_line.trim_start().len();
+
+            if let Some((current, header_indent)) = section {
+                let nested = indent > header_indent && !line.is_empty();
+                match current {
+                    // Items of a "Trusted networks:" block are listed one per line,
+                    // either as "- <name>" or indented beneath the header
+                    StatusSection::TrustedNetworks => {
+                        let item = line
+                            .strip_prefix('-')
+                            .or_else(|| line.strip_prefix('*'))
+                            .or(nested.then_some(line));
+                        if let Some(item) = item {
+                            push_network(&mut info.connected_networks, item);
+                            push_network_info(&mut info.networks, item, true, None);
+                            continue;
+                        }
+                    }
+                    // Account sub-fields such as "Status:" must not be taken for the
+                    // connection's own fields
+                    StatusSection::Account if nested => {
+                        self.parse_account_field(&mut info, line);
+                        continue;
+                    }
+                    StatusSection::Account => {}
+                }
+                section = None;
+            }
+
+            if line.starts_with("Account:") {
+                section = Some((StatusSection::Account, indent));
+            } else if line.starts_with("Status update:") || line.contains("Status:") {
                 info.status = self.parse_status_line(line);
+            } else if line.starts_with("Reason:") {
+                info.reason = self.extract_value_after_colon(line);
             } else if line.contains("Account type:") {
                 info.account_type = self.extract_value_after_colon(line);
+                section = Some((StatusSection::Account, indent));
             } else if line.contains("Warp enabled:") {
                 info.warp_enabled = line.contains("true");
             } else if line.contains("Gateway enabled:") {
                 info.gateway_enabled = line.contains("true");
+            } else if line.starts_with("Network:") || line.starts_with("SSID:") {
+                if let Some(network) = self.extract_value_after_colon(line) {
+                    push_network(&mut info.connected_networks, &network);
+                    let network_type = line.starts_with("SSID:").then_some(NetworkType::Wifi);
+                    push_network_info(&mut info.networks, &network, false, network_type);
+                }
+            } else if line.starts_with("Endpoint") || line.starts_with("Tunnel IP") {
+                // "Endpoint:", "Endpoint IPv6:", "Tunnel IP:", "Tunnel IPv4:" and so on.
+                // Addresses contain colons, so only the label is split off.
+                if let Some((_, value)) = line.split_once(':') {
+                    let field = if line.starts_with("Endpoint") {
+                        &mut info.endpoint
+                    } else {
+                        &mut info.tunnel_ip
+                    };
+                    append_address(field, value);
+                }
+            } else if line.starts_with("Override:") {
+                info.override_state = self.extract_value_after_colon(line);
+            } else if line.starts_with("Trusted networks:") {
+                section = Some((StatusSection::TrustedNetworks, indent));
+                // Networks may also be listed inline, separated by commas
+                if let Some(networks) = self.extract_value_after_colon(line) {
+                    for network in networks.split(',') {
+                        push_network(&mut info.connected_networks, network);
+                        push_network_info(&mut info.networks, network, true, None);
+                    }
+                }
             }
         }
 
-        Ok(info)
+        info
+    }
+
+    /// Parse a line indented beneath an account header, such as "Type: Team"
+    ///
+    /// Fields that only describe the account, like its status or device ID, are ignored.
+    fn parse_account_field(&self, info: &mut WarpInfo, line: &str) {
+        let Some((key, _)) = line.split_once(':') else {
+            return;
+        };
+        match key.trim().to_lowercase().as_str() {
+            "type" | "account type" => {
+                if let Some(account_type) = self.extract_value_after_colon(line) {
+                    info.account_type = Some(account_type);
+                }
+            }
+            "warp enabled" => info.warp_enabled = line.contains("true"),
+            "gateway enabled" => info.gateway_enabled = line.contains("true"),
+            _ => {}
+        }
     }
 
     /// Parse status from a status line
@@ -251,14 +1038,7 @@ impl WarpClient {
 
         // First try the new "Status update:" format
         if line_lower.starts_with("status update:") {
-            let status_part = line_lower.strip_prefix("status update:").unwrap().trim();
-            match status_part {
-                "connected" => WarpStatus::Connected,
-                "disconnected" => WarpStatus::Disconnected,
-                "connecting" => WarpStatus::Connecting,
-                "disconnecting" => WarpStatus::Disconnecting,
-                _ => WarpStatus::Unknown,
-            }
+            status_from_str(line_lower.strip_prefix("status update:").unwrap())
         } else if line_lower.contains("status:") {
             // Handle the old "Status:" format
             let status_part = line_lower.split("status:").nth(1).unwrap_or("").trim();
@@ -278,19 +1058,24 @@ impl WarpClient {
         }
     }
 
-    // Operation mode is now handled by get_operation_mode() which uses the JSON output
+    // Operation mode is now handled by get_operation_mode(), which prefers the JSON output
 
-    /// Extract value after colon from a line
+    /// Extract the value after the first colon of a line
+    ///
+    /// The value itself may contain colons, e.g. an IPv6 address or a URL.
     fn extract_value_after_colon(&self, line: &str) -> Option<String> {
-        line.split(':')
-            .nth(1)
-            .map(|s| s.trim().to_string())
+        // Trimming also drops the '\r' of the CRLF line endings warp-cli prints on Windows
+        line.split_once(':')
+            .map(|(_, value)| value.trim().to_string())
             .filter(|s| !s.is_empty())
     }
 
     /// Parse registration command output
-    #[allow(dead_code)] // May be used in future async implementations
-    fn parse_registration_output(&self, output: &str) -> WarpResult<RegistrationInfo> {
+    ///
+    /// warp-cli output is decoded lossily, so invalid UTF-8 shows up as U+FFFD. A device ID
+    /// or license key containing one is corrupted and yields [`WarpError::ParseError`]
+    /// instead of a value that looks valid but isn't.
+    pub fn parse_registration_output(&self, output: &str) -> WarpResult<RegistrationInfo> {
         let mut info = RegistrationInfo {
             device_id: None,
             organization: None,
@@ -312,80 +1097,994 @@ impl WarpClient {
             }
         }
 
+        reject_replacement_chars("Device ID", info.device_id.as_deref())?;
+        reject_replacement_chars("License key", info.license_key.as_deref())?;
         Ok(info)
     }
 
-    /// Synchronous version of get_status for non-async contexts
-    pub fn get_status_sync(&self) -> WarpResult<WarpInfo> {
-        let output = Command::new("warp-cli")
-            .arg("status")
-            .output()
-            .map_err(|e| {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    WarpError::CommandNotFound
-                } else {
-                    WarpError::IoError(e)
+    /// Run warp-cli with arguments, killing it if it exceeds the command timeout
+    ///
+    /// Unlike the typed methods, a non-zero exit status is not an error: stdout, stderr
+    /// and the exit code are returned as warp-cli produced them.
+    pub fn run_raw_sync(&self, args: &[&str]) -> WarpResult<CommandOutput> {
+        self.run_raw_with_timeout_sync(args, self.timeout())
+    }
+
+    /// [`Self::run_raw_sync`] with a timeout other than the configured one
+    fn run_raw_with_timeout_sync(
+        &self,
+        args: &[&str],
+        command_timeout: Duration,
+    ) -> WarpResult<CommandOutput> {
+        let mut command = self.command();
+        command.args(args);
+        let result =
+            run_cancellable(command, command_timeout, self.cancel.as_ref()).map_err(|e| match e {
+                WarpError::Timeout(_) => WarpError::timed_out(
+                    &format!("warp-cli {}", redact_args(args).join(" ")),
+                    command_timeout,
+                ),
+                e => e,
+            });
+        log_command_result(args, &result);
+        result.map(|output| CommandOutput {
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            exit_code: output.status.code(),
+        })
+    }
+
+    /// Run a warp-cli command that changes the WARP state or settings
+    ///
+    /// In dry-run mode the command is only logged and reported as successful with no output.
+    fn run_mutating_sync(&self, args: &[&str]) -> WarpResult<CommandOutput> {
+        if self.dry_run {
+            log_dry_run(args);
+            return Ok(CommandOutput {
+                exit_code: Some(0),
+                ..CommandOutput::default()
+            });
+        }
+        self.run_raw_sync(args)
+    }
+
+    /// Execute a warp-cli command synchronously and return its stdout
+    fn execute_command_sync(&self, args: &[&str]) -> WarpResult<String> {
+        stdout_or_error(self.run_raw_sync(args)?)
+    }
+
+    /// [`Self::execute_command_sync`] for a command that changes the WARP state or settings,
+    /// see [`Self::run_mutating_sync`]
+    fn execute_mutating_sync(&self, args: &[&str]) -> WarpResult<String> {
+        stdout_or_error(self.run_mutating_sync(args)?)
+    }
+
+    /// Register with a Zero Trust license key or enrollment token, returning the new
+    /// registration
+    ///
+    /// Tokens (`com.cloudflare.warp://...` URLs) are passed to `registration token`, anything
+    /// else to `registration license`, which updates the existing registration. An empty key
+    /// is rejected without invoking warp-cli. The key is masked in logs.
+    pub fn register_with_key_sync(&self, key: &str) -> WarpResult<RegistrationInfo> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(WarpError::command_failed("license key must not be empty"));
+        }
+
+        let subcommand = if key.contains("://") {
+            "token"
+        } else {
+            "license"
+        };
+        self.execute_mutating_sync(&["registration", subcommand, key])?;
+        self.get_registration_sync()
+    }
+
+    /// Register this device with a new consumer WARP registration, returning it
+    pub fn create_registration_sync(&self) -> WarpResult<RegistrationInfo> {
+        self.execute_mutating_sync(&["registration", "new"])?;
+        self.get_registration_sync()
+    }
+
+    /// Apply an admin override policy on a Zero Trust managed device
+    ///
+    /// Runs `warp-cli override <policy>`. Overrides relax the policy set by the
+    /// organization, so callers should confirm with the user first. An empty policy is
+    /// rejected without invoking warp-cli.
+    pub fn set_override_sync(&self, policy: &str) -> WarpResult<()> {
+        let policy = policy.trim();
+        if policy.is_empty() {
+            return Err(WarpError::command_failed(
+                "override policy must not be empty",
+            ));
+        }
+
+        self.execute_mutating_sync(&["override", policy])?;
+        Ok(())
+    }
+
+    /// Clear the admin override, restoring the organization's policy
+    pub fn clear_override_sync(&self) -> WarpResult<()> {
+        self.execute_mutating_sync(&["override", "clear"])?;
+        Ok(())
+    }
+
+    /// Reset the WARP settings to their defaults with `warp-cli settings reset`
+    ///
+    /// This throws away every customization, so callers should confirm with the user
+    /// first. Without a registration there's nothing to reset, so warp-cli complaining
+    /// about a missing one counts as success.
+    pub fn reset_settings_sync(&self) -> WarpResult<()> {
+        match self.execute_mutating_sync(&["settings", "reset"]) {
+            Ok(_) | Err(WarpError::NoRegistration) => Ok(()),
+            Err(WarpError::CommandFailed { stderr: msg, .. }) if is_missing_registration(&msg) => {
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the details of the current registration
+    pub fn get_registration_sync(&self) -> WarpResult<RegistrationInfo> {
+        match self.execute_command_sync(&["registration", "show"]) {
+            Ok(output) if is_missing_registration(&output) => Err(WarpError::NoRegistration),
+            Ok(output) => self.parse_registration_output(&output),
+            Err(WarpError::CommandFailed { stderr: msg, .. }) if is_missing_registration(&msg) => {
+                Err(WarpError::NoRegistration)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the account details of the current registration
+    pub fn get_account_sync(&self) -> WarpResult<AccountInfo> {
+        if self.prefer_json {
+            match self.execute_command_sync(&["--json", "account"]) {
+                Ok(output) => {
+                    if let Ok(info) = self.parse_account_json(&output) {
+                        return Ok(info);
+                    }
                 }
-            })?;
+                Err(WarpError::CommandFailed { stderr: msg, .. })
+                    if is_missing_registration(&msg) =>
+                {
+                    return Err(WarpError::NoRegistration);
+                }
+                Err(WarpError::CommandNotFound) => return Err(WarpError::CommandNotFound),
+                // Older warp-cli versions don't support --json, fall back to plain output
+                Err(_) => {}
+            }
+        }
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            return Err(WarpError::CommandFailed(error_msg.to_string()));
+        match self.execute_command_sync(&["account"]) {
+            Ok(output) => self.parse_account_output(&output),
+            Err(WarpError::CommandFailed { stderr: msg, .. }) if is_missing_registration(&msg) => {
+                Err(WarpError::NoRegistration)
+            }
+            Err(e) => Err(e),
         }
+    }
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        self.parse_status_output(stdout.trim())
+    /// Parse the `warp-cli --json account` output into AccountInfo struct
+    pub fn parse_account_json(&self, output: &str) -> WarpResult<AccountInfo> {
+        serde_json::from_str(output).map_err(|e| WarpError::ParseError(e.to_string()))
     }
 
-    /// Synchronous version of connect for non-async contexts
-    pub fn connect_sync(&self) -> WarpResult<()> {
-        let output = Command::new("warp-cli")
-            .arg("connect")
-            .output()
-            .map_err(|e| {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    WarpError::CommandNotFound
-                } else {
-                    WarpError::IoError(e)
+    /// Parse the plain `warp-cli account` output into AccountInfo struct
+    pub fn parse_account_output(&self, output: &str) -> WarpResult<AccountInfo> {
+        if is_missing_registration(output) {
+            return Err(WarpError::NoRegistration);
+        }
+
+        let mut info = AccountInfo::default();
+
+        for line in output.lines() {
+            let line = line.trim();
+
+            if line.contains("Account type:") {
+                info.account_type = self.extract_value_after_colon(line);
+            } else if line.contains("Device ID:") {
+                info.device_id = self.extract_value_after_colon(line);
+            } else if line.contains("Public key:") {
+                info.public_key = self.extract_value_after_colon(line);
+            } else if line.contains("Quota:") {
+                info.quota = self
+                    .extract_value_after_colon(line)
+                    .and_then(|v| v.parse().ok());
+            } else if line.contains("Premium data:") {
+                info.premium_data = self
+                    .extract_value_after_colon(line)
+                    .and_then(|v| v.parse().ok());
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// Run `warp-cli debug connectivity-check`, keeping at most the configured number of lines
+    ///
+    /// The check can take several seconds; don't call this from the render thread.
+    pub fn run_connectivity_check_sync(&self) -> WarpResult<String> {
+        let output = self.run_raw_sync(&["debug", "connectivity-check"])?;
+
+        if !output.success() {
+            let error_msg = if output.stderr.trim().is_empty() {
+                &output.stdout
+            } else {
+                &output.stderr
+            };
+            return Err(WarpError::CommandFailed {
+                code: output.exit_code,
+                stderr: error_msg.trim().to_string(),
+            });
+        }
+
+        Ok(truncate_output(
+            output.stdout.trim_end(),
+            self.max_output_lines,
+        ))
+    }
+
+    /// Get the tunnel statistics of the current connection
+    ///
+    /// Returns [`WarpError::NotConnected`] when WARP isn't connected.
+    pub fn get_warp_stats_sync(&self) -> WarpResult<WarpStats> {
+        match self.execute_command_sync(&["--json", "warp-stats"]) {
+            Ok(output) => self.parse_warp_stats(&output),
+            Err(WarpError::CommandFailed { stderr: msg, .. }) if is_not_connected(&msg) => {
+                Err(WarpError::NotConnected)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parse the `warp-cli --json warp-stats` output into WarpStats struct
+    ///
+    /// Output that isn't JSON may be warp-cli saying WARP isn't connected.
+    pub fn parse_warp_stats(&self, output: &str) -> WarpResult<WarpStats> {
+        serde_json::from_str(output).map_err(|e| {
+            if is_not_connected(output) {
+                WarpError::NotConnected
+            } else {
+                WarpError::ParseError(e.to_string())
+            }
+        })
+    }
+
+    /// Get the counters of the WARP tunnel from `warp-cli --json tunnel stats`
+    ///
+    /// Returns [`WarpError::NotConnected`] when WARP isn't connected.
+    pub fn get_tunnel_stats_sync(&self) -> WarpResult<TunnelStats> {
+        match self.execute_command_sync(&["--json", "tunnel", "stats"]) {
+            Ok(output) => self.parse_tunnel_stats_json(&output, SystemTime::now()),
+            Err(WarpError::CommandFailed { stderr: msg, .. }) if is_not_connected(&msg) => {
+                Err(WarpError::NotConnected)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parse the `warp-cli --json tunnel stats` output into TunnelStats struct
+    ///
+    /// [`TunnelStats::since_last_handshake`] is measured up to `now`. A handshake timestamp
+    /// of 0 means the tunnel never completed one, like a missing timestamp. Output that
+    /// isn't JSON may be warp-cli saying WARP isn't connected.
+    pub fn parse_tunnel_stats_json(
+        &self,
+        output: &str,
+        now: SystemTime,
+    ) -> WarpResult<TunnelStats> {
+        let mut stats: TunnelStats = serde_json::from_str(output).map_err(|e| {
+            if is_not_connected(output) {
+                WarpError::NotConnected
+            } else {
+                WarpError::ParseError(e.to_string())
+            }
+        })?;
+        stats.last_handshake = stats.last_handshake.filter(|&timestamp| timestamp > 0);
+        stats.since_last_handshake = stats.last_handshake.map(|timestamp| {
+            let handshake = UNIX_EPOCH + Duration::from_secs(timestamp);
+            // A handshake "in the future" means the clocks disagree, not a negative time
+            now.duration_since(handshake).unwrap_or_default()
+        });
+        Ok(stats)
+    }
+
+    /// Get the DNS query statistics
+    ///
+    /// Returns [`WarpError::DnsStatsDisabled`] when DNS statistics are turned off in the
+    /// settings.
+    pub fn get_dns_stats_sync(&self) -> WarpResult<DnsStats> {
+        if self.prefer_json {
+            match self.execute_command_sync(&["--json", "dns", "stats"]) {
+                Ok(output) => {
+                    if let Ok(stats) = self.parse_dns_stats_json(&output) {
+                        return Ok(stats);
+                    }
                 }
-            })?;
+                Err(WarpError::CommandFailed { stderr: msg, .. })
+                    if is_dns_stats_disabled(&msg) =>
+                {
+                    return Err(WarpError::DnsStatsDisabled);
+                }
+                Err(WarpError::CommandNotFound) => return Err(WarpError::CommandNotFound),
+                Err(e @ WarpError::Timeout(_)) => return Err(e),
+                // Older warp-cli versions don't support --json, fall back to plain output
+                Err(_) => {}
+            }
+        }
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            if error_msg.contains("already connected") {
-                Ok(()) // Already connected is not an error
+        match self.execute_command_sync(&["dns", "stats"]) {
+            Ok(output) => self.parse_dns_stats_output(&output),
+            Err(WarpError::CommandFailed { stderr: msg, .. }) if is_dns_stats_disabled(&msg) => {
+                Err(WarpError::DnsStatsDisabled)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parse the `warp-cli --json dns stats` output into DnsStats struct
+    ///
+    /// Output that isn't JSON may be warp-cli saying DNS statistics are turned off.
+    pub fn parse_dns_stats_json(&self, output: &str) -> WarpResult<DnsStats> {
+        serde_json::from_str(output).map_err(|e| {
+            if is_dns_stats_disabled(output) {
+                WarpError::DnsStatsDisabled
+            } else {
+                WarpError::ParseError(e.to_string())
+            }
+        })
+    }
+
+    /// Parse the plain `warp-cli dns stats` output into DnsStats struct
+    ///
+    /// Output without any of the expected fields is a parse error, unless it says DNS
+    /// statistics are turned off.
+    pub fn parse_dns_stats_output(&self, output: &str) -> WarpResult<DnsStats> {
+        let mut stats = DnsStats::default();
+        let mut found = false;
+
+        for line in output.lines() {
+            let line = line.trim();
+            let Some(value) = self.extract_value_after_colon(line) else {
+                continue;
+            };
+            // Counts and latencies may carry a unit, e.g. "12.5 ms"
+            let number = value.split_whitespace().next().unwrap_or_default();
+            let label = line.to_lowercase();
+
+            if label.starts_with("total queries:") || label.starts_with("queries:") {
+                stats.total_queries = number.parse().unwrap_or_default();
+            } else if label.starts_with("cached:") || label.starts_with("cache hits:") {
+                stats.cached = number.parse().unwrap_or_default();
+            } else if label.starts_with("blocked:") {
+                stats.blocked = number.parse().unwrap_or_default();
+            } else if label.contains("latency:") {
+                stats.latency = number.trim_end_matches("ms").parse().ok();
             } else {
-                Err(WarpError::ConnectionFailed(error_msg.to_string()))
+                continue;
+            }
+            found = true;
+        }
+
+        if !found && is_dns_stats_disabled(output) {
+            return Err(WarpError::DnsStatsDisabled);
+        }
+        if !found {
+            return Err(WarpError::ParseError(format!(
+                "no DNS statistics in {:?}",
+                output.trim()
+            )));
+        }
+        Ok(stats)
+    }
+
+    /// Get the version of the installed warp-cli, e.g. `2024.6.497`
+    pub fn get_version_sync(&self) -> WarpResult<String> {
+        let output = self.execute_command_sync(&["--version"])?;
+        self.parse_version(&output)
+    }
+
+    /// Extract the semantic version from the `warp-cli --version` output
+    pub fn parse_version(&self, output: &str) -> WarpResult<String> {
+        output
+            .split_whitespace()
+            .map(|word| word.trim_start_matches(['v', 'V']))
+            .find(|word| {
+                let parts: Vec<&str> = word.split('.').collect();
+                parts.len() >= 2
+                    && parts
+                        .iter()
+                        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(str::to_string)
+            .ok_or_else(|| WarpError::ParseError(format!("no version in {:?}", output.trim())))
+    }
+
+    /// Synchronous version of get_status for non-async contexts
+    ///
+    /// Uses `warp-cli --json status` when supported, falling back to scraping the plain output.
+    pub fn get_status_sync(&self) -> WarpResult<WarpInfo> {
+        if self.prefer_json {
+            match self.execute_command_sync(&["--json", "status"]) {
+                Ok(output) => {
+                    if let Ok(info) = self.parse_status_json(&output) {
+                        return self
+                            .with_operation_mode(info)
+                            .map(|info| self.with_raw_status(info, output));
+                    }
+                }
+                Err(WarpError::CommandNotFound) => return Err(WarpError::CommandNotFound),
+                Err(e @ WarpError::Timeout(_)) => return Err(e),
+                // Older warp-cli versions don't support --json, fall back to plain output
+                Err(_) => {}
             }
+        }
+
+        let output = self.execute_command_sync(&["status"])?;
+        self.parse_status_output(&output)
+            .map(|info| self.with_raw_status(info, output))
+    }
+
+    /// Get the status and, only when connected, the tunnel statistics in one go
+    ///
+    /// Failing to get the statistics isn't fatal: they're `None` so the status is still
+    /// shown.
+    pub fn get_status_with_stats_sync(&self) -> WarpResult<(WarpInfo, Option<WarpStats>)> {
+        let info = self.get_status_sync()?;
+        if !info.is_connected() {
+            return Ok((info, None));
+        }
+
+        let stats = match self.get_warp_stats_sync() {
+            Ok(stats) => Some(stats),
+            Err(e) => {
+                tracing::debug!(error = %e, "warp-stats unavailable");
+                None
+            }
+        };
+        Ok((info, stats))
+    }
+
+    /// Synchronous version of connect for non-async contexts
+    ///
+    /// Transient failures (see [`is_transient_error`]) are retried with exponential backoff.
+    pub fn connect_sync(&self) -> WarpResult<ConnectOutcome> {
+        retry_transient(|| self.connect_once_sync())
+    }
+
+    /// Run `warp-cli connect` once without retrying
+    fn connect_once_sync(&self) -> WarpResult<ConnectOutcome> {
+        let output = self.run_mutating_sync(&["connect"])?;
+
+        if !output.success() {
+            self.parse_connect_error(&output.stderr)
         } else {
+            Ok(ConnectOutcome::Connected)
+        }
+    }
+
+    /// Map the error output of a failed `warp-cli connect` to its outcome
+    ///
+    /// Being already connected is not an error.
+    pub fn parse_connect_error(&self, error_msg: &str) -> WarpResult<ConnectOutcome> {
+        if error_msg.to_lowercase().contains("already connected") {
+            Ok(ConnectOutcome::AlreadyConnected)
+        } else {
+            Err(WarpError::ConnectionFailed(error_msg.to_string()))
+        }
+    }
+
+    /// Check whether the warp-svc daemon answers a quick `warp-cli status`
+    ///
+    /// The check gives up after [`DAEMON_CHECK_TIMEOUT`]. A status that fails for other
+    /// reasons, e.g. a missing registration, still means the daemon is running.
+    pub fn daemon_status_sync(&self) -> WarpResult<DaemonStatus> {
+        match self.ping_daemon_sync() {
+            Ok(()) => Ok(DaemonStatus::Running),
+            Err(WarpError::DaemonUnresponsive) => Ok(DaemonStatus::Unresponsive),
+            Err(WarpError::CommandNotFound) => Ok(DaemonStatus::NotFound),
+            Err(WarpError::CommandFailed { stderr, .. }) if is_daemon_down_error(&stderr) => {
+                Ok(DaemonStatus::NotFound)
+            }
+            Err(WarpError::CommandFailed { .. }) => Ok(DaemonStatus::Running),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Run `warp-cli status` once, reporting a timeout as [`WarpError::DaemonUnresponsive`]
+    fn ping_daemon_sync(&self) -> WarpResult<()> {
+        let output = self
+            .run_raw_with_timeout_sync(&["status"], self.timeout().min(DAEMON_CHECK_TIMEOUT))
+            .map_err(|e| match e {
+                WarpError::Timeout(_) => WarpError::DaemonUnresponsive,
+                e => e,
+            })?;
+
+        if output.success() {
             Ok(())
+        } else {
+            // Some versions print the error on stdout
+            Err(WarpError::CommandFailed {
+                code: output.exit_code,
+                stderr: format!("{}{}", output.stderr, output.stdout),
+            })
         }
     }
 
     /// Synchronous version of disconnect for non-async contexts
-    pub fn disconnect_sync(&self) -> WarpResult<()> {
-        let output = Command::new("warp-cli")
-            .arg("disconnect")
-            .output()
-            .map_err(|e| {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    WarpError::CommandNotFound
-                } else {
-                    WarpError::IoError(e)
-                }
-            })?;
+    ///
+    /// Transient failures (see [`is_transient_error`]) are retried with exponential backoff.
+    pub fn disconnect_sync(&self) -> WarpResult<DisconnectOutcome> {
+        retry_transient(|| self.disconnect_once_sync())
+    }
 
-        if !output.status.success() {
-            let error_msg = String::from_utf8_lossy(&output.stderr);
-            if error_msg.contains("already disconnected") {
-                Ok(()) // Already disconnected is not an error
-            } else {
-                Err(WarpError::DisconnectionFailed(error_msg.to_string()))
+    /// Run `warp-cli disconnect` once without retrying
+    fn disconnect_once_sync(&self) -> WarpResult<DisconnectOutcome> {
+        let output = self.run_mutating_sync(&["disconnect"])?;
+
+        if !output.success() {
+            self.parse_disconnect_error(&output.stderr)
+        } else {
+            Ok(DisconnectOutcome::Disconnected)
+        }
+    }
+
+    /// Disconnect and wait until warp-cli reports [`WarpStatus::Disconnected`]
+    ///
+    /// `warp-cli disconnect` returns while the tunnel may still be tearing down. The
+    /// status is polled every [`STATUS_POLL_INTERVAL`] until it's disconnected or `timeout`
+    /// elapses, and the last status seen is returned either way.
+    pub fn disconnect_and_wait_sync(&self, timeout: Duration) -> WarpResult<WarpStatus> {
+        self.disconnect_sync()?;
+        if self.dry_run {
+            // Nothing was disconnected, so there's nothing to wait for
+            return self.get_status_sync().map(|info| info.status);
+        }
+        self.wait_for_status_sync(&WarpStatus::Disconnected, timeout, STATUS_POLL_INTERVAL)
+    }
+
+    /// Poll the status every `interval` until it's `target` or `timeout` elapses,
+    /// returning the last status seen
+    pub fn wait_for_status_sync(
+        &self,
+        target: &WarpStatus,
+        timeout: Duration,
+        interval: Duration,
+    ) -> WarpResult<WarpStatus> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let status = self.get_status_sync()?.status;
+            let now = Instant::now();
+            if status == *target || now >= deadline {
+                return Ok(status);
             }
+            thread::sleep(interval.min(deadline - now));
+        }
+    }
+
+    /// Map the error output of a failed `warp-cli disconnect` to its outcome
+    ///
+    /// Being already disconnected is not an error.
+    pub fn parse_disconnect_error(&self, error_msg: &str) -> WarpResult<DisconnectOutcome> {
+        if error_msg.to_lowercase().contains("already disconnected") {
+            Ok(DisconnectOutcome::AlreadyDisconnected)
         } else {
-            Ok(())
+            Err(WarpError::DisconnectionFailed(error_msg.to_string()))
         }
     }
 
     // ...existing code...
 }
+
+/// How often a running child process is checked for completion
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Flag to stop a command running on another thread
+///
+/// Clones share the flag, so one can be handed to the command and the other kept to
+/// cancel it.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the command to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Run `command` to completion, collecting its output.
+///
+/// The child is killed and [`WarpError::Timeout`] returned if it doesn't exit
+/// within `timeout`. A missing executable is reported as [`WarpError::CommandNotFound`].
+pub fn run_with_timeout(command: Command, timeout: Duration) -> WarpResult<Output> {
+    run_cancellable(command, timeout, None)
+}
+
+/// [`run_with_timeout`] that also kills the child and returns [`WarpError::Cancelled`]
+/// once `cancel` is cancelled
+///
+/// A token cancelled before the call fails it without starting the child.
+pub fn run_cancellable(
+    mut command: Command,
+    timeout: Duration,
+    cancel: Option<&CancelToken>,
+) -> WarpResult<Output> {
+    let is_cancelled = || cancel.is_some_and(CancelToken::is_cancelled);
+    if is_cancelled() {
+        return Err(WarpError::Cancelled);
+    }
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                WarpError::CommandNotFound
+            } else {
+                WarpError::IoError(e)
+            }
+        })?;
+
+    // Drain the pipes while waiting so a chatty child can't block on a full buffer
+    let stdout = read_to_end_in_background(child.stdout.take());
+    let stderr = read_to_end_in_background(child.stderr.take());
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(WarpError::Cancelled);
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(WarpError::timed_out("process", timeout));
+        }
+        thread::sleep(CHILD_POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn read_to_end_in_background(
+    pipe: Option<impl Read + Send + 'static>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+/// `duration` in milliseconds, saturating at `u64::MAX`
+fn duration_to_millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// The binary named by [`BINARY_PATH_ENV`], or the platform default when it's unset
+fn default_binary_path() -> PathBuf {
+    match std::env::var_os(BINARY_PATH_ENV) {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => platform_binary_path(),
+    }
+}
+
+/// `warp-cli` from `PATH`
+#[cfg(not(windows))]
+fn platform_binary_path() -> PathBuf {
+    PathBuf::from(DEFAULT_BINARY)
+}
+
+/// warp-cli in the standard install location, or `warp-cli` from `PATH` if it isn't
+/// there; the installer doesn't add it to `PATH`
+#[cfg(windows)]
+fn platform_binary_path() -> PathBuf {
+    let installed = windows_install_path(std::env::var_os("ProgramFiles").as_deref());
+    if installed.is_file() {
+        installed
+    } else {
+        PathBuf::from(DEFAULT_BINARY)
+    }
+}
+
+/// Where the WARP installer puts warp-cli under `program_files`, `C:\Program Files` if
+/// it's unknown
+#[cfg(windows)]
+pub fn windows_install_path(program_files: Option<&std::ffi::OsStr>) -> PathBuf {
+    let program_files = match program_files {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(r"C:\Program Files"),
+    };
+    program_files
+        .join("Cloudflare")
+        .join("Cloudflare WARP")
+        .join("warp-cli.exe")
+}
+
+/// Log a finished warp-cli command: its exit status at debug level, failures at warn level
+fn log_command_result(args: &[&str], result: &WarpResult<Output>) {
+    let args = redact_args(args);
+    match result {
+        Ok(output) => {
+            tracing::debug!(?args, status = %output.status, "warp-cli finished");
+            if !output.status.success() {
+                tracing::warn!(
+                    ?args,
+                    status = %output.status,
+                    stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+                    "warp-cli failed"
+                );
+            }
+        }
+        Err(e) => tracing::warn!(?args, error = %e, "warp-cli failed to run"),
+    }
+}
+
+/// Arguments with license keys and enrollment tokens masked, safe to log
+pub fn redact_args(args: &[&str]) -> Vec<String> {
+    args.iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let is_secret = i > 0 && matches!(args[i - 1], "license" | "token");
+            if is_secret {
+                mask_license_key(arg)
+            } else {
+                arg.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Keep the first `max_lines` lines of `output`, noting how many were dropped
+pub fn truncate_output(output: &str, max_lines: usize) -> String {
+    let total = output.lines().count();
+    if total <= max_lines {
+        return output.to_string();
+    }
+
+    let mut truncated = output
+        .lines()
+        .take(max_lines)
+        .collect::<Vec<_>>()
+        .join("\n");
+    if max_lines > 0 {
+        truncated.push('\n');
+    }
+    truncated.push_str(&format!("... ({} more lines truncated)", total - max_lines));
+    truncated
+}
+
+/// Map a status such as "Connected" to [`WarpStatus`]
+fn status_from_str(status: &str) -> WarpStatus {
+    match status.trim().to_lowercase().as_str() {
+        "connected" => WarpStatus::Connected,
+        "disconnected" => WarpStatus::Disconnected,
+        "connecting" => WarpStatus::Connecting,
+        "disconnecting" => WarpStatus::Disconnecting,
+        _ => WarpStatus::Unknown,
+    }
+}
+
+/// Check whether warp-cli output reports that the device isn't registered
+fn is_missing_registration(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    msg.contains("missing registration")
+        || msg.contains("registration missing")
+        || msg.contains("no registration")
+}
+
+/// Check that `cidr` is an IPv4 or IPv6 address with an in-range `/prefix`,
+/// e.g. `10.0.0.0/8` or `fd00::/8`
+pub fn validate_cidr(cidr: &str) -> WarpResult<()> {
+    let invalid =
+        |reason: &str| WarpError::command_failed(format!("invalid CIDR {:?}: {}", cidr, reason));
+
+    let (address, prefix) = cidr
+        .trim()
+        .split_once('/')
+        .ok_or_else(|| invalid("missing /prefix"))?;
+    let address: IpAddr = address.parse().map_err(|_| invalid("not an IP address"))?;
+    let prefix: u8 = prefix
+        .parse()
+        .map_err(|_| invalid("prefix is not a number"))?;
+
+    let max_prefix = match address {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+    if prefix > max_prefix {
+        return Err(invalid(&format!("prefix must be at most {}", max_prefix)));
+    }
+
+    Ok(())
+}
+
+/// Reject empty or whitespace-only SSIDs before invoking warp-cli
+fn validate_ssid(ssid: &str) -> WarpResult<&str> {
+    if ssid.trim().is_empty() {
+        return Err(WarpError::command_failed("SSID must not be empty"));
+    }
+    Ok(ssid)
+}
+
+/// Did warp-cli reject the command line itself, e.g. because the subcommand doesn't exist
+/// in the installed version?
+pub fn is_unsupported_command(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    [
+        "unrecognized subcommand",
+        "unexpected argument",
+        "found argument",
+        "unknown command",
+        "invalid value",
+    ]
+    .iter()
+    .any(|pattern| msg.contains(pattern))
+}
+
+/// Check whether warp-cli output reports that WARP isn't connected
+fn is_not_connected(msg: &str) -> bool {
+    msg.to_lowercase().contains("not connected")
+}
+
+/// Check whether warp-cli refused to report DNS statistics because they're turned off
+fn is_dns_stats_disabled(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    msg.contains("disabled") || msg.contains("not enabled")
+}
+
+/// Add an address parsed from status output, comma-separating it from earlier ones
+fn append_address(field: &mut Option<String>, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        return;
+    }
+    match field {
+        Some(addresses) => {
+            addresses.push_str(", ");
+            addresses.push_str(value);
+        }
+        None => *field = Some(value.to_string()),
+    }
+}
+
+/// Block of the status output whose indented lines are parsed as its fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusSection {
+    /// "Account type:" or "Account:" and the account details beneath it
+    Account,
+    /// "Trusted networks:" and the networks listed beneath it
+    TrustedNetworks,
+}
+
+/// Add a network name parsed from status output, skipping blanks and duplicates
+fn push_network(networks: &mut Vec<String>, name: &str) {
+    let name = name.trim().trim_matches('"').trim();
+    if !name.is_empty() && !networks.iter().any(|n| n == name) {
+        networks.push(name.to_string());
+    }
+}
+
+/// Add or update the typed entry of a network parsed from status output, mirroring
+/// [`push_network`]
+///
+/// A network listed both as connected and as trusted is merged into one trusted entry.
+/// Without a known `network_type` it's guessed from the name.
+fn push_network_info(
+    networks: &mut Vec<NetworkInfo>,
+    name: &str,
+    trusted: bool,
+    network_type: Option<NetworkType>,
+) {
+    let name = name.trim().trim_matches('"').trim();
+    if name.is_empty() {
+        return;
+    }
+    let network_type = network_type.unwrap_or_else(|| NetworkType::from_interface_name(name));
+    match networks.iter_mut().find(|network| network.name == name) {
+        Some(network) => {
+            network.trusted |= trusted;
+            if network.network_type == NetworkType::Unknown {
+                network.network_type = network_type;
+            }
+        }
+        None => networks.push(NetworkInfo {
+            name: name.to_string(),
+            trusted,
+            network_type,
+        }),
+    }
+}
+
+/// Check whether a warp-cli error message describes a transient failure
+/// that is likely to succeed when retried
+pub fn is_transient_error(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    TRANSIENT_ERROR_PATTERNS
+        .iter()
+        .any(|pattern| msg.contains(pattern))
+}
+
+/// Fail if `value` contains U+FFFD, i.e. invalid UTF-8 was replaced while decoding
+///
+/// The value itself is left out of the error since it may be a secret.
+fn reject_replacement_chars(field: &str, value: Option<&str>) -> WarpResult<()> {
+    if value.is_some_and(|value| value.contains(char::REPLACEMENT_CHARACTER)) {
+        return Err(WarpError::ParseError(format!(
+            "{} contains invalid UTF-8",
+            field
+        )));
+    }
+    Ok(())
+}
+
+/// The stdout of a successful command, or its stderr and exit code as an error
+fn stdout_or_error(output: CommandOutput) -> WarpResult<String> {
+    if !output.success() {
+        return Err(WarpError::CommandFailed {
+            code: output.exit_code,
+            stderr: output.stderr,
+        });
+    }
+
+    Ok(output.stdout.trim().to_string())
+}
+
+/// Log a mutating command skipped in dry-run mode
+fn log_dry_run(args: &[&str]) {
+    let args = redact_args(args);
+    tracing::info!(?args, "dry run, skipped warp-cli");
+}
+
+/// Check whether a warp-cli error message says the daemon isn't running
+pub fn is_daemon_down_error(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    DAEMON_DOWN_PATTERNS
+        .iter()
+        .any(|pattern| msg.contains(pattern))
+}
+
+/// Run `op`, retrying with [`RETRY_BACKOFF`] while it fails with a transient error
+fn retry_transient<T>(mut op: impl FnMut() -> WarpResult<T>) -> WarpResult<T> {
+    let mut backoff = RETRY_BACKOFF.iter();
+    loop {
+        match op() {
+            Err(e) if is_transient(&e) => match backoff.next() {
+                Some(delay) => std::thread::sleep(*delay),
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
+}
+
+fn is_transient(error: &WarpError) -> bool {
+    match error {
+        WarpError::CommandFailed { stderr: msg, .. }
+        | WarpError::ConnectionFailed(msg)
+        | WarpError::DisconnectionFailed(msg) => is_transient_error(msg),
+        _ => false,
+    }
+}
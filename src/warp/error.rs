@@ -1,20 +1,31 @@
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum WarpError {
-    #[error("Command execution failed: {0}")]
-    CommandFailed(String),
+    #[error("Command execution failed{}: {stderr}", exit_code_suffix(.code))]
+    CommandFailed {
+        /// Exit code of warp-cli, `None` if it didn't run or was killed by a signal
+        code: Option<i32>,
+        stderr: String,
+    },
 
     #[error("Command not found: warp-cli is not installed or not in PATH")]
     CommandNotFound,
 
     #[error("Failed to parse command output: {0}")]
-    #[allow(dead_code)] // May be used in future implementations
     ParseError(String),
 
-    #[error("Command timed out: {0}")]
+    /// A command that ran out of time, see [`WarpError::timed_out`]
+    #[error("{0}")]
     Timeout(String),
 
+    #[error("The WARP daemon is not responding")]
+    DaemonUnresponsive,
+
+    #[error("Command was aborted")]
+    Cancelled,
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -26,14 +37,75 @@ pub enum WarpError {
     RegistrationExists,
 
     #[error("No registration found")]
-    #[allow(dead_code)] // May be used in future implementations
     NoRegistration,
 
+    #[error("Not connected to WARP")]
+    NotConnected,
+
+    #[error("DNS statistics are disabled; enable DNS logging in the WARP settings to collect them")]
+    DnsStatsDisabled,
+
+    #[error("Not supported by the installed warp-cli: {0}")]
+    Unsupported(String),
+
     #[error("Connection failed: {0}")]
     ConnectionFailed(String),
 
     #[error("Disconnection failed: {0}")]
     DisconnectionFailed(String),
+
+    /// A step of a settings batch failed, see [`crate::warp::client::WarpClient::apply_settings_batch`]
+    #[error("Failed to {step}: {source}; {}", rollback_outcome(*.rolled_back))]
+    BatchFailed {
+        /// The step that failed, e.g. "set families mode malware"
+        step: String,
+        source: Box<WarpError>,
+        /// Were the steps applied before it undone?
+        rolled_back: bool,
+    },
+}
+
+impl WarpError {
+    /// A failure without an exit code, e.g. input rejected before running warp-cli
+    pub fn command_failed(message: impl Into<String>) -> Self {
+        WarpError::CommandFailed {
+            code: None,
+            stderr: message.into(),
+        }
+    }
+
+    /// `command` was killed after running for `timeout`, e.g. "warp-cli status timed out
+    /// after 30s"
+    pub fn timed_out(command: &str, timeout: Duration) -> Self {
+        let timeout = if timeout.subsec_millis() == 0 {
+            format!("{}s", timeout.as_secs())
+        } else {
+            format!("{}ms", timeout.as_millis())
+        };
+        WarpError::Timeout(format!("{} timed out after {}", command, timeout))
+    }
+
+    /// Exit code of the failed warp-cli command, if there was one
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            WarpError::CommandFailed { code, .. } => *code,
+            _ => None,
+        }
+    }
+}
+
+fn rollback_outcome(rolled_back: bool) -> &'static str {
+    if rolled_back {
+        "the previous settings were restored"
+    } else {
+        "restoring the previous settings failed, some changes may remain"
+    }
+}
+
+/// " (exit code N)" when the exit code is known
+fn exit_code_suffix(code: &Option<i32>) -> String {
+    code.map(|code| format!(" (exit code {})", code))
+        .unwrap_or_default()
 }
 
 pub type WarpResult<T> = Result<T, WarpError>;
@@ -1,50 +1,168 @@
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{Mutex, mpsc};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tokio::time::interval;
 
-use crate::warp::{WarpClient, WarpInfo, WarpResult};
+use crate::warp::{WarpClient, WarpInfo, WarpResult, WarpStats, WarpStatus};
 
 #[derive(Debug, Clone)]
-#[allow(dead_code)] // Future use for async message-based architecture
 pub enum WarpMessage {
     Connect,
     Disconnect,
     Refresh,
     CreateRegistration,
     DeleteRegistration,
-    StatusUpdate(WarpInfo),
+    /// A fetched status, with the connection statistics while connected
+    StatusUpdate(WarpInfo, Option<Box<WarpStats>>),
     Error(String),
+    /// Informational message worth showing in the log
+    Log(String),
 }
 
-#[allow(dead_code)] // Future use for async message-based architecture
+/// Refreshes completed this recently are considered current, so another one is dropped
+pub const REFRESH_COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Times an Unknown status from a successful warp-cli call is fetched again
+pub const MAX_STATUS_RETRIES: u32 = 1;
+
+/// Delay before fetching an Unknown status again
+pub const STATUS_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Should a status fetch be repeated after `retries` retries?
+///
+/// Only a successful command whose output parsed as [`WarpStatus::Unknown`] is retried,
+/// up to [`MAX_STATUS_RETRIES`] times. Errors are real failures and reported as they are.
+pub fn should_retry_status<T>(result: &WarpResult<(WarpInfo, T)>, retries: u32) -> bool {
+    retries < MAX_STATUS_RETRIES
+        && matches!(result, Ok((info, _)) if info.status == WarpStatus::Unknown)
+}
+
+/// Decides whether a status refresh is worth running, so a burst of refresh requests
+/// and the periodic refresh don't spawn warp-cli for the same status over and over.
+#[derive(Debug, Default)]
+pub struct RefreshCoalescer {
+    in_flight: bool,
+    last_completed: Option<Instant>,
+}
+
+impl RefreshCoalescer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a refresh at `now` unless one is in flight or completed within
+    /// [`REFRESH_COALESCE_WINDOW`]. Returns whether the refresh should run; if it does,
+    /// [`RefreshCoalescer::finish`] must be called once it's done.
+    pub fn try_begin(&mut self, now: Instant) -> bool {
+        let recent = self.last_completed.is_some_and(|completed| {
+            now.saturating_duration_since(completed) < REFRESH_COALESCE_WINDOW
+        });
+        if self.in_flight || recent {
+            return false;
+        }
+        self.in_flight = true;
+        true
+    }
+
+    /// Start a refresh that must run, e.g. because a command just changed the status
+    pub fn begin_forced(&mut self) {
+        self.in_flight = true;
+    }
+
+    /// Record that the refresh started by `try_begin` or `begin_forced` completed at `now`
+    pub fn finish(&mut self, now: Instant) {
+        self.in_flight = false;
+        self.last_completed = Some(now);
+    }
+}
+
+/// Fetch the status with statistics, fetching it again after [`STATUS_RETRY_DELAY`]
+/// while [`should_retry_status`] says it was caught mid-update
+async fn fetch_status(client: &WarpClient) -> WarpResult<(WarpInfo, Option<WarpStats>)> {
+    let mut result = client.get_status_with_stats().await;
+    let mut retries = 0;
+    while should_retry_status(&result, retries) {
+        retries += 1;
+        tokio::time::sleep(STATUS_RETRY_DELAY).await;
+        result = client.get_status_with_stats().await;
+    }
+    result
+}
+
+/// Runs warp-cli commands off the UI thread.
+///
+/// Commands are sent through [`WarpManager::get_sender`] and processed by
+/// [`WarpManager::process_messages`]. Results (`StatusUpdate` and `Error`) are
+/// published on a separate event channel so a consumer such as the TUI can
+/// receive them without competing with the command queue.
+#[derive(Debug)]
 pub struct WarpManager {
     client: WarpClient,
     sender: mpsc::UnboundedSender<WarpMessage>,
-    receiver: Arc<Mutex<mpsc::UnboundedReceiver<WarpMessage>>>,
+    /// Taken by the first call to [`WarpManager::process_messages`]
+    receiver: std::sync::Mutex<Option<mpsc::UnboundedReceiver<WarpMessage>>>,
+    event_sender: mpsc::UnboundedSender<WarpMessage>,
+    event_receiver: Option<mpsc::UnboundedReceiver<WarpMessage>>,
+    /// Shared with the periodic refresh task
+    refresh: Arc<std::sync::Mutex<RefreshCoalescer>>,
+    /// Skips the periodic refreshes while set, see [`WarpManager::set_paused`]
+    paused: Arc<AtomicBool>,
 }
 
 impl WarpManager {
     pub fn new() -> Self {
-        let client = WarpClient::new();
+        Self::with_client(WarpClient::new())
+    }
+
+    /// Create a manager running its commands with `client`
+    pub fn with_client(client: WarpClient) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
 
         Self {
             client,
             sender,
-            receiver: Arc::new(Mutex::new(receiver)),
+            receiver: std::sync::Mutex::new(Some(receiver)),
+            event_sender,
+            event_receiver: Some(event_receiver),
+            refresh: Arc::new(std::sync::Mutex::new(RefreshCoalescer::new())),
+            paused: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    #[allow(dead_code)] // Future use for async message-based architecture
+    /// Stop or resume the periodic status refreshes; refreshes sent as commands still run
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Are the periodic status refreshes stopped?
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Sender used to enqueue commands for the manager
     pub fn get_sender(&self) -> mpsc::UnboundedSender<WarpMessage> {
         self.sender.clone()
     }
 
-    #[allow(dead_code)] // Future use for async message-based architecture
+    /// Sender used to publish status updates and errors
+    pub fn get_event_sender(&self) -> mpsc::UnboundedSender<WarpMessage> {
+        self.event_sender.clone()
+    }
+
+    /// Take the receiving end of the event channel.
+    ///
+    /// Returns `None` if the receiver has already been taken.
+    pub fn take_event_receiver(&mut self) -> Option<mpsc::UnboundedReceiver<WarpMessage>> {
+        self.event_receiver.take()
+    }
+
     pub async fn start_background_tasks(&self) {
         let client = self.client.clone();
-        let sender = self.sender.clone();
+        let sender = self.event_sender.clone();
+        let refresh = self.refresh.clone();
+        let paused = self.paused.clone();
 
         // Start periodic status updates
         tokio::spawn(async move {
@@ -53,9 +171,17 @@ impl WarpManager {
             loop {
                 interval.tick().await;
 
-                match client.get_status().await {
-                    Ok(info) => {
-                        let _ = sender.send(WarpMessage::StatusUpdate(info));
+                if paused.load(Ordering::Relaxed)
+                    || !refresh.lock().unwrap().try_begin(Instant::now())
+                {
+                    continue;
+                }
+                let result = fetch_status(&client).await;
+                refresh.lock().unwrap().finish(Instant::now());
+
+                match result {
+                    Ok((info, stats)) => {
+                        let _ = sender.send(WarpMessage::StatusUpdate(info, stats.map(Box::new)));
                     }
                     Err(e) => {
                         let _ =
@@ -66,36 +192,61 @@ impl WarpManager {
         });
     }
 
-    #[allow(dead_code)] // Future use for async message-based architecture
+    /// Fetch the status and publish it, unless a refresh is in flight or just completed
+    async fn refresh_status(&self) -> WarpResult<()> {
+        if !self.refresh.lock().unwrap().try_begin(Instant::now()) {
+            return Ok(());
+        }
+        self.publish_status().await
+    }
+
+    /// Fetch the status after a command changed it and publish it
+    async fn refresh_status_forced(&self) -> WarpResult<()> {
+        self.refresh.lock().unwrap().begin_forced();
+        self.publish_status().await
+    }
+
+    /// Run a refresh started on the coalescer and publish the result
+    async fn publish_status(&self) -> WarpResult<()> {
+        let result = fetch_status(&self.client).await;
+        self.refresh.lock().unwrap().finish(Instant::now());
+        let (info, stats) = result?;
+        let _ = self
+            .event_sender
+            .send(WarpMessage::StatusUpdate(info, stats.map(Box::new)));
+        Ok(())
+    }
+
     pub async fn handle_message(&self, message: WarpMessage) -> WarpResult<()> {
         match message {
             WarpMessage::Connect => {
-                self.client.connect().await?;
+                let outcome = self.client.connect().await?;
+                let _ = self
+                    .event_sender
+                    .send(WarpMessage::Log(outcome.to_string()));
                 // Send status update after connection attempt
-                let info = self.client.get_status().await?;
-                let _ = self.sender.send(WarpMessage::StatusUpdate(info));
+                self.refresh_status_forced().await?;
             }
             WarpMessage::Disconnect => {
-                self.client.disconnect().await?;
+                let outcome = self.client.disconnect().await?;
+                let _ = self
+                    .event_sender
+                    .send(WarpMessage::Log(outcome.to_string()));
                 // Send status update after disconnection attempt
-                let info = self.client.get_status().await?;
-                let _ = self.sender.send(WarpMessage::StatusUpdate(info));
+                self.refresh_status_forced().await?;
             }
             WarpMessage::Refresh => {
-                let info = self.client.get_status().await?;
-                let _ = self.sender.send(WarpMessage::StatusUpdate(info));
+                self.refresh_status().await?;
             }
             WarpMessage::CreateRegistration => {
                 self.client.create_registration().await?;
-                let info = self.client.get_status().await?;
-                let _ = self.sender.send(WarpMessage::StatusUpdate(info));
+                self.refresh_status_forced().await?;
             }
             WarpMessage::DeleteRegistration => {
                 self.client.delete_registration().await?;
-                let info = self.client.get_status().await?;
-                let _ = self.sender.send(WarpMessage::StatusUpdate(info));
+                self.refresh_status_forced().await?;
             }
-            WarpMessage::StatusUpdate(_) | WarpMessage::Error(_) => {
+            WarpMessage::StatusUpdate(..) | WarpMessage::Error(_) | WarpMessage::Log(_) => {
                 // These are output messages, no action needed
             }
         }
@@ -103,17 +254,20 @@ impl WarpManager {
         Ok(())
     }
 
-    #[allow(dead_code)] // Future use for async message-based architecture
+    /// Handle the commands sent to the manager, in order, until every sender is dropped
+    ///
+    /// The first call takes the command queue for itself. Any later call returns right
+    /// away, so two processing tasks can't compete for commands.
     pub async fn process_messages(&self) {
-        let receiver = self.receiver.clone();
+        let Some(mut receiver) = self.receiver.lock().unwrap().take() else {
+            tracing::warn!("messages are already being processed");
+            return;
+        };
 
-        while let Some(message) = {
-            let mut recv = receiver.lock().await;
-            recv.recv().await
-        } {
+        while let Some(message) = receiver.recv().await {
             if let Err(e) = self.handle_message(message).await {
                 let _ = self
-                    .sender
+                    .event_sender
                     .send(WarpMessage::Error(format!("Command failed: {}", e)));
             }
         }
@@ -1,8 +1,10 @@
+pub mod backend;
 pub mod client;
 pub mod error;
 pub mod manager;
 pub mod types;
 
+pub use backend::WarpBackend;
 pub use client::WarpClient;
 pub use error::WarpResult;
 pub use types::*;
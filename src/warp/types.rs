@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WarpStatus {
@@ -21,15 +21,114 @@ impl std::fmt::Display for WarpStatus {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+impl WarpStatus {
+    /// Can the daemon go from this status to `next` in a single step?
+    ///
+    /// Connecting leads to Connected, or back to Disconnected when it fails, and
+    /// disconnecting always ends in Disconnected. A connection may drop or reconnect on
+    /// its own. Staying put is always possible, and Unknown can be followed or preceded
+    /// by anything since it says nothing about the actual state.
+    ///
+    /// Polling can miss the short-lived statuses, so two statuses seen in a row may be
+    /// several steps apart.
+    pub fn can_transition_to(&self, next: &WarpStatus) -> bool {
+        use WarpStatus::*;
+        match (self, next) {
+            (current, next) if current == next => true,
+            (Unknown, _) | (_, Unknown) => true,
+            (Disconnected, Connecting) => true,
+            (Connecting, Connected | Disconnected | Disconnecting) => true,
+            (Connected, Disconnecting | Disconnected | Connecting) => true,
+            (Disconnecting, Disconnected) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Serialized as its warp-cli mode string, e.g. `"warp+doh"`, see [`WarpMode::as_cli_str`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub enum WarpMode {
-    DoH,     // DNS over HTTPS
-    DoT,     // DNS over TLS
-    WarpDoH, // Warp + DNS over HTTPS
-    WarpDoT, // Warp + DNS over TLS
+    DoH,        // DNS over HTTPS
+    DoT,        // DNS over TLS
+    WarpDoH,    // Warp + DNS over HTTPS
+    WarpDoT,    // Warp + DNS over TLS
+    Warp,       // Warp without the DNS proxy
+    Proxy,      // Local SOCKS/HTTP proxy
+    TunnelOnly, // Warp tunnel only
+    DnsOnly,    // DNS filtering only
+    #[default]
     Unknown,
 }
 
+impl WarpMode {
+    /// Every mode warp-cli accepts, in the order shown in the TUI
+    pub const ALL: [WarpMode; 8] = [
+        WarpMode::DoH,
+        WarpMode::DoT,
+        WarpMode::WarpDoH,
+        WarpMode::WarpDoT,
+        WarpMode::Warp,
+        WarpMode::Proxy,
+        WarpMode::TunnelOnly,
+        WarpMode::DnsOnly,
+    ];
+
+    /// The mode string used by warp-cli, e.g. `warp+doh`
+    pub fn as_cli_str(&self) -> &'static str {
+        match self {
+            WarpMode::DoH => "doh",
+            WarpMode::DoT => "dot",
+            WarpMode::WarpDoH => "warp+doh",
+            WarpMode::WarpDoT => "warp+dot",
+            WarpMode::Warp => "warp",
+            WarpMode::Proxy => "proxy",
+            WarpMode::TunnelOnly => "tunnel_only",
+            WarpMode::DnsOnly => "dns_only",
+            WarpMode::Unknown => "unknown",
+        }
+    }
+
+    /// Parse a mode string used by warp-cli, falling back to [`WarpMode::Unknown`]
+    ///
+    /// Case, `_` and `-` are ignored, so `tunnel_only` and `TunnelOnly` are the same mode.
+    /// The long names shown by `warp-cli settings`, e.g. `WarpWithDnsOverHttps`, are
+    /// accepted too.
+    pub fn from_cli_str(mode: &str) -> Self {
+        match mode.trim().to_lowercase().replace(['_', '-'], "").as_str() {
+            "doh" | "dnsoverhttps" => WarpMode::DoH,
+            "dot" | "dnsovertls" => WarpMode::DoT,
+            // `warpdoh` and `warpdot` are the variant names older versions serialized
+            "warp+doh" | "warpwithdnsoverhttps" | "warpdoh" => WarpMode::WarpDoH,
+            "warp+dot" | "warpwithdnsovertls" | "warpdot" => WarpMode::WarpDoT,
+            "warp" => WarpMode::Warp,
+            "proxy" | "warpproxy" => WarpMode::Proxy,
+            "tunnelonly" => WarpMode::TunnelOnly,
+            "dnsonly" => WarpMode::DnsOnly,
+            _ => WarpMode::Unknown,
+        }
+    }
+}
+
+impl Serialize for WarpMode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_cli_str())
+    }
+}
+
+/// Parsed with [`WarpMode::from_cli_str`], so unrecognized modes become [`WarpMode::Unknown`]
+impl<'de> Deserialize<'de> for WarpMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mode = String::deserialize(deserializer)?;
+        Ok(WarpMode::from_cli_str(&mode))
+    }
+}
+
 impl std::fmt::Display for WarpMode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -37,38 +136,646 @@ impl std::fmt::Display for WarpMode {
             WarpMode::DoT => write!(f, "DoT"),
             WarpMode::WarpDoH => write!(f, "Warp+DoH"),
             WarpMode::WarpDoT => write!(f, "Warp+DoT"),
+            WarpMode::Warp => write!(f, "Warp"),
+            WarpMode::Proxy => write!(f, "Proxy"),
+            WarpMode::TunnelOnly => write!(f, "Tunnel only"),
+            WarpMode::DnsOnly => write!(f, "DNS only"),
             WarpMode::Unknown => write!(f, "Unknown"),
         }
     }
 }
 
+/// DNS filtering applied by `warp-cli dns families`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FamiliesMode {
+    Off,     // No filtering
+    Malware, // Block malware
+    Full,    // Block malware and adult content
+}
+
+impl FamiliesMode {
+    /// Every families mode, in the order shown in the TUI
+    pub const ALL: [FamiliesMode; 3] =
+        [FamiliesMode::Off, FamiliesMode::Malware, FamiliesMode::Full];
+
+    /// The argument passed to `warp-cli dns families`
+    pub fn as_cli_str(&self) -> &'static str {
+        match self {
+            FamiliesMode::Off => "off",
+            FamiliesMode::Malware => "malware",
+            FamiliesMode::Full => "full",
+        }
+    }
+
+    /// Parse a `warp-cli dns families` argument, ignoring case
+    pub fn from_cli_str(mode: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|m| m.as_cli_str().eq_ignore_ascii_case(mode.trim()))
+    }
+}
+
+impl std::fmt::Display for FamiliesMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FamiliesMode::Off => write!(f, "Off"),
+            FamiliesMode::Malware => write!(f, "Malware"),
+            FamiliesMode::Full => write!(f, "Full"),
+        }
+    }
+}
+
+/// Tunnel protocol set by `warp-cli tunnel protocol set`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TunnelProtocol {
+    WireGuard,
+    Masque,
+}
+
+impl TunnelProtocol {
+    /// Every tunnel protocol, in the order shown in the TUI
+    pub const ALL: [TunnelProtocol; 2] = [TunnelProtocol::WireGuard, TunnelProtocol::Masque];
+
+    /// The argument passed to `warp-cli tunnel protocol set`
+    pub fn as_cli_str(&self) -> &'static str {
+        match self {
+            TunnelProtocol::WireGuard => "WireGuard",
+            TunnelProtocol::Masque => "MASQUE",
+        }
+    }
+
+    /// Parse a `warp-cli tunnel protocol set` argument, ignoring case
+    pub fn from_cli_str(protocol: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.as_cli_str().eq_ignore_ascii_case(protocol.trim()))
+    }
+}
+
+impl std::fmt::Display for TunnelProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_cli_str())
+    }
+}
+
+/// Deserialize a tunnel protocol, mapping unrecognized names to `None`
+fn deserialize_tunnel_protocol<'de, D>(deserializer: D) -> Result<Option<TunnelProtocol>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let protocol = Option::<String>::deserialize(deserializer)?;
+    Ok(protocol.as_deref().and_then(TunnelProtocol::from_cli_str))
+}
+
+/// DNS statistics reported by `warp-cli dns stats`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DnsStats {
+    #[serde(default, alias = "queries")]
+    pub total_queries: u64,
+    /// Queries answered from the cache
+    #[serde(default, alias = "cache_hits")]
+    pub cached: u64,
+    #[serde(default, alias = "blocked_queries")]
+    pub blocked: u64,
+    /// Average query latency in milliseconds
+    #[serde(default, alias = "latency_ms")]
+    pub latency: Option<f64>,
+}
+
+impl DnsStats {
+    /// Share of queries answered from the cache, from 0.0 to 1.0; `None` before any query
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        (self.total_queries > 0).then(|| self.cached as f64 / self.total_queries as f64)
+    }
+}
+
+/// Everything a warp-cli invocation printed, along with how it exited
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Exit code, `None` if the process was terminated by a signal
+    pub exit_code: Option<i32>,
+}
+
+impl CommandOutput {
+    /// Did the command exit with status 0?
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Result of a successful `warp-cli connect`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectOutcome {
+    Connected,
+    AlreadyConnected,
+}
+
+impl std::fmt::Display for ConnectOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectOutcome::Connected => write!(f, "Connected"),
+            ConnectOutcome::AlreadyConnected => write!(f, "Already connected"),
+        }
+    }
+}
+
+/// Result of a successful `warp-cli disconnect`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectOutcome {
+    Disconnected,
+    AlreadyDisconnected,
+}
+
+impl std::fmt::Display for DisconnectOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisconnectOutcome::Disconnected => write!(f, "Disconnected"),
+            DisconnectOutcome::AlreadyDisconnected => write!(f, "Already disconnected"),
+        }
+    }
+}
+
+/// Whether the warp-svc daemon behind warp-cli answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonStatus {
+    Running,
+    /// warp-cli didn't get an answer in time
+    Unresponsive,
+    /// warp-cli is missing or couldn't reach the daemon at all
+    NotFound,
+}
+
+impl std::fmt::Display for DaemonStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DaemonStatus::Running => write!(f, "Running"),
+            DaemonStatus::Unresponsive => write!(f, "Unresponsive"),
+            DaemonStatus::NotFound => write!(f, "Not found"),
+        }
+    }
+}
+
+/// Kind of interface a network is reached through
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkType {
+    Wifi,
+    Ethernet,
+    #[default]
+    Unknown,
+}
+
+impl NetworkType {
+    /// Guess the type from an interface name such as `wlan0` or `enp3s0`
+    pub fn from_interface_name(name: &str) -> Self {
+        let name = name.trim().to_lowercase();
+        if name.starts_with("wl")
+            || ["wifi", "wi-fi", "wireless"]
+                .iter()
+                .any(|word| name.contains(word))
+        {
+            NetworkType::Wifi
+        } else if ["eth", "enp", "eno", "ens"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+            || name.contains("ethernet")
+        {
+            NetworkType::Ethernet
+        } else {
+            NetworkType::Unknown
+        }
+    }
+}
+
+impl std::fmt::Display for NetworkType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkType::Wifi => write!(f, "Wi-Fi"),
+            NetworkType::Ethernet => write!(f, "Ethernet"),
+            NetworkType::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// A network from the status output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkInfo {
+    /// SSID or interface name
+    pub name: String,
+    /// Is it one of the trusted networks, on which WARP disconnects automatically?
+    pub trusted: bool,
+    pub network_type: NetworkType,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WarpInfo {
     pub status: WarpStatus,
+    /// Why WARP is in its current status, e.g. "Settings Changed"
+    pub reason: Option<String>,
     pub mode: Option<WarpMode>,
     pub account_type: Option<String>,
     pub warp_enabled: bool,
     pub gateway_enabled: bool,
     pub connected_networks: Vec<String>,
+    /// The networks of `connected_networks` with their type and whether they're trusted
+    #[serde(default)]
+    pub networks: Vec<NetworkInfo>,
+    /// WARP endpoint the tunnel connects to; IPv4 and IPv6 endpoints are comma-separated
+    pub endpoint: Option<String>,
+    /// Address of this device inside the tunnel; IPv4 and IPv6 addresses are comma-separated
+    pub tunnel_ip: Option<String>,
+    /// Admin override in effect on a managed device, if warp-cli reports one
+    pub override_state: Option<String>,
+    /// Unparsed output of `warp-cli status`, only kept when enabled with
+    /// [`crate::warp::client::WarpClient::set_capture_raw_status`]
+    #[serde(skip)]
+    pub raw: Option<String>,
+}
+
+impl WarpInfo {
+    /// Is WARP connected?
+    pub fn is_connected(&self) -> bool {
+        self.status == WarpStatus::Connected
+    }
+
+    /// Is WARP connecting or disconnecting?
+    pub fn is_transitioning(&self) -> bool {
+        matches!(
+            self.status,
+            WarpStatus::Connecting | WarpStatus::Disconnecting
+        )
+    }
+
+    /// Is the status settled and known, so that connecting or disconnecting makes sense?
+    pub fn is_actionable(&self) -> bool {
+        matches!(
+            self.status,
+            WarpStatus::Connected | WarpStatus::Disconnected
+        )
+    }
+
+    /// What changed going from `self` to `other`, empty if nothing did
+    ///
+    /// The reason, endpoint and tunnel IP aren't compared since they change along with
+    /// the status.
+    pub fn diff(&self, other: &WarpInfo) -> Vec<WarpInfoChange> {
+        let mut changes = Vec::new();
+        if self.status != other.status {
+            changes.push(WarpInfoChange::StatusChanged(
+                self.status.clone(),
+                other.status.clone(),
+            ));
+        }
+        if self.mode != other.mode {
+            changes.push(WarpInfoChange::ModeChanged(
+                self.mode.clone(),
+                other.mode.clone(),
+            ));
+        }
+        if self.account_type != other.account_type {
+            changes.push(WarpInfoChange::AccountTypeChanged(
+                self.account_type.clone(),
+                other.account_type.clone(),
+            ));
+        }
+        if self.warp_enabled != other.warp_enabled {
+            changes.push(WarpInfoChange::WarpToggled(other.warp_enabled));
+        }
+        if self.gateway_enabled != other.gateway_enabled {
+            changes.push(WarpInfoChange::GatewayToggled(other.gateway_enabled));
+        }
+        if self.connected_networks != other.connected_networks {
+            changes.push(WarpInfoChange::NetworksChanged(
+                other.connected_networks.clone(),
+            ));
+        }
+        if self.override_state != other.override_state {
+            changes.push(WarpInfoChange::OverrideChanged(
+                other.override_state.clone(),
+            ));
+        }
+        changes
+    }
+
+    /// The status, mode and account type on one line, e.g. "Connected · Warp+DoH · Free"
+    ///
+    /// Unlike [`Display`](std::fmt::Display) it's meant for a status bar, so fields
+    /// warp-cli didn't report are left out instead of shown as N/A.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.status.to_string()];
+        if let Some(mode) = self
+            .mode
+            .as_ref()
+            .filter(|mode| **mode != WarpMode::Unknown)
+        {
+            parts.push(mode.to_string());
+        }
+        if let Some(account_type) = self
+            .account_type
+            .as_deref()
+            .map(str::trim)
+            .filter(|account_type| !account_type.is_empty())
+        {
+            parts.push(account_type.to_string());
+        }
+        parts.join(" · ")
+    }
+}
+
+/// A difference between two [`WarpInfo`]s, see [`WarpInfo::diff`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum WarpInfoChange {
+    /// The status went from the first to the second
+    StatusChanged(WarpStatus, WarpStatus),
+    /// The mode went from the first to the second
+    ModeChanged(Option<WarpMode>, Option<WarpMode>),
+    /// The account type went from the first to the second
+    AccountTypeChanged(Option<String>, Option<String>),
+    /// WARP was enabled (`true`) or disabled
+    WarpToggled(bool),
+    /// Gateway was enabled (`true`) or disabled
+    GatewayToggled(bool),
+    /// The connected networks are now these
+    NetworksChanged(Vec<String>),
+    /// The admin override is now this one, `None` if it was cleared
+    OverrideChanged(Option<String>),
+}
+
+impl std::fmt::Display for WarpInfoChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn or_na<T: std::fmt::Display>(value: &Option<T>) -> String {
+            value
+                .as_ref()
+                .map_or_else(|| "N/A".to_string(), T::to_string)
+        }
+
+        match self {
+            WarpInfoChange::StatusChanged(old, new) => write!(f, "Status: {} -> {}", old, new),
+            WarpInfoChange::ModeChanged(old, new) => {
+                write!(f, "Mode: {} -> {}", or_na(old), or_na(new))
+            }
+            WarpInfoChange::AccountTypeChanged(old, new) => {
+                write!(f, "Account type: {} -> {}", or_na(old), or_na(new))
+            }
+            WarpInfoChange::WarpToggled(true) => write!(f, "WARP enabled"),
+            WarpInfoChange::WarpToggled(false) => write!(f, "WARP disabled"),
+            WarpInfoChange::GatewayToggled(true) => write!(f, "Gateway enabled"),
+            WarpInfoChange::GatewayToggled(false) => write!(f, "Gateway disabled"),
+            WarpInfoChange::NetworksChanged(networks) if networks.is_empty() => {
+                write!(f, "Connected networks: None")
+            }
+            WarpInfoChange::NetworksChanged(networks) => {
+                write!(f, "Connected networks: {}", networks.join(", "))
+            }
+            WarpInfoChange::OverrideChanged(Some(state)) => write!(f, "Override: {}", state),
+            WarpInfoChange::OverrideChanged(None) => write!(f, "Override cleared"),
+        }
+    }
+}
+
+impl std::fmt::Display for WarpInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reason {
+            Some(reason) => writeln!(f, "Status: {} ({})", self.status, reason)?,
+            None => writeln!(f, "Status: {}", self.status)?,
+        }
+        match &self.mode {
+            Some(mode) => writeln!(f, "Mode: {}", mode)?,
+            None => writeln!(f, "Mode: N/A")?,
+        }
+        writeln!(
+            f,
+            "Account Type: {}",
+            self.account_type.as_deref().unwrap_or("N/A")
+        )?;
+        writeln!(f, "WARP Enabled: {}", yes_no(self.warp_enabled))?;
+        writeln!(f, "Gateway Enabled: {}", yes_no(self.gateway_enabled))?;
+        if self.connected_networks.is_empty() {
+            write!(f, "Connected Networks: None")
+        } else {
+            write!(f, "Connected Networks:")?;
+            for network in &self.connected_networks {
+                write!(f, "\n  - {}", network)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "Yes" } else { "No" }
 }
 
 impl Default for WarpInfo {
     fn default() -> Self {
         Self {
             status: WarpStatus::Unknown,
+            reason: None,
             mode: None,
             account_type: None,
             warp_enabled: false,
             gateway_enabled: false,
             connected_networks: Vec::new(),
+            networks: Vec::new(),
+            endpoint: None,
+            tunnel_ip: None,
+            override_state: None,
+            raw: None,
         }
     }
 }
 
+/// Version of the JSON schema of [`WarpInfoEnvelope`], bumped whenever a field of
+/// [`WarpInfo`] is renamed or removed or changes meaning
+///
+/// Version 2 writes `mode` as the warp-cli mode string, e.g. `warp+doh` instead of `WarpDoH`.
+pub const WARP_INFO_SCHEMA_VERSION: u32 = 2;
+
+/// [`WarpInfo`] tagged with the schema version, as printed by `--json` so scripts can
+/// tell which fields to expect
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarpInfoEnvelope {
+    pub version: u32,
+    pub info: WarpInfo,
+}
+
+impl From<WarpInfo> for WarpInfoEnvelope {
+    fn from(info: WarpInfo) -> Self {
+        Self {
+            version: WARP_INFO_SCHEMA_VERSION,
+            info,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RegistrationInfo {
     pub device_id: Option<String>,
     pub organization: Option<String>,
     pub account_type: Option<String>,
     pub license_key: Option<String>,
 }
+
+impl RegistrationInfo {
+    /// License key with everything but the last four characters hidden
+    pub fn masked_license_key(&self) -> Option<String> {
+        self.license_key.as_deref().map(mask_license_key)
+    }
+}
+
+/// Mask a license key as `****-****-XXXX`, keeping only the last four characters.
+///
+/// Keys of four characters or fewer are masked entirely so they are never revealed.
+pub fn mask_license_key(key: &str) -> String {
+    let key = key.trim();
+    if key.is_empty() {
+        return String::new();
+    }
+
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 4 {
+        return "****".to_string();
+    }
+
+    let suffix: String = chars[chars.len() - 4..].iter().collect();
+    format!("****-****-{}", suffix)
+}
+
+/// Settings reported under `settings` by `warp-cli --json settings`.
+///
+/// Every field has a default so output from older warp-cli versions with
+/// missing keys still parses.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarpSettingsInfo {
+    #[serde(default)]
+    pub always_on: bool,
+    #[serde(default)]
+    pub switch_locked: bool,
+    #[serde(default, rename = "operation_mode")]
+    pub mode: WarpMode,
+    #[serde(default)]
+    pub dns_log_enabled: bool,
+    #[serde(default, deserialize_with = "deserialize_tunnel_protocol")]
+    pub tunnel_protocol: Option<TunnelProtocol>,
+    #[serde(default)]
+    pub families_mode: Option<String>,
+}
+
+/// Settings changed together by [`crate::warp::client::WarpClient::apply_settings_batch`]
+///
+/// Settings left at `None` aren't touched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsBatch {
+    pub mode: Option<WarpMode>,
+    pub families_mode: Option<FamiliesMode>,
+    pub tunnel_protocol: Option<TunnelProtocol>,
+}
+
+impl SettingsBatch {
+    /// The changes of the batch, in the order they're applied
+    pub fn changes(&self) -> Vec<SettingChange> {
+        let mut changes = Vec::new();
+        if let Some(mode) = &self.mode {
+            changes.push(SettingChange::Mode(mode.clone()));
+        }
+        if let Some(families_mode) = self.families_mode {
+            changes.push(SettingChange::FamiliesMode(families_mode));
+        }
+        if let Some(protocol) = self.tunnel_protocol {
+            changes.push(SettingChange::TunnelProtocol(protocol));
+        }
+        changes
+    }
+}
+
+/// A single step of a [`SettingsBatch`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingChange {
+    Mode(WarpMode),
+    FamiliesMode(FamiliesMode),
+    TunnelProtocol(TunnelProtocol),
+}
+
+impl SettingChange {
+    /// The change restoring this setting to its value in `settings`, `None` if `settings`
+    /// doesn't say what it was
+    pub fn previous(&self, settings: &WarpSettingsInfo) -> Option<SettingChange> {
+        match self {
+            SettingChange::Mode(_) => (settings.mode != WarpMode::Unknown)
+                .then(|| SettingChange::Mode(settings.mode.clone())),
+            SettingChange::FamiliesMode(_) => settings
+                .families_mode
+                .as_deref()
+                .and_then(FamiliesMode::from_cli_str)
+                .map(SettingChange::FamiliesMode),
+            SettingChange::TunnelProtocol(_) => {
+                settings.tunnel_protocol.map(SettingChange::TunnelProtocol)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SettingChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingChange::Mode(mode) => write!(f, "set mode {}", mode.as_cli_str()),
+            SettingChange::FamiliesMode(mode) => {
+                write!(f, "set families mode {}", mode.as_cli_str())
+            }
+            SettingChange::TunnelProtocol(protocol) => {
+                write!(f, "set tunnel protocol {}", protocol.as_cli_str())
+            }
+        }
+    }
+}
+
+/// Account details reported by `warp-cli account`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountInfo {
+    #[serde(default)]
+    pub account_type: Option<String>,
+    #[serde(default, alias = "id")]
+    pub device_id: Option<String>,
+    #[serde(default)]
+    pub public_key: Option<String>,
+    #[serde(default)]
+    pub quota: Option<u64>,
+    #[serde(default)]
+    pub premium_data: Option<u64>,
+}
+
+/// Tunnel statistics reported by `warp-cli warp-stats`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WarpStats {
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default, alias = "latency")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, alias = "loss")]
+    pub loss_percent: Option<f64>,
+    #[serde(default, alias = "sent_bytes")]
+    pub bytes_sent: u64,
+    #[serde(default, alias = "received_bytes")]
+    pub bytes_received: u64,
+}
+
+/// Per-tunnel counters reported by `warp-cli tunnel stats`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TunnelStats {
+    #[serde(default)]
+    pub rx_bytes: u64,
+    #[serde(default)]
+    pub tx_bytes: u64,
+    #[serde(default)]
+    pub rx_packets: u64,
+    #[serde(default)]
+    pub tx_packets: u64,
+    /// Unix timestamp in seconds of the last handshake, `None` if there never was one
+    #[serde(default, alias = "last_handshake_time")]
+    pub last_handshake: Option<u64>,
+    /// Time since the last handshake when the statistics were parsed
+    #[serde(skip)]
+    pub since_last_handshake: Option<std::time::Duration>,
+}
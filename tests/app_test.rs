@@ -0,0 +1,1714 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use std::time::{Duration, Instant};
+use warp_tui::app::browser::{DOCS_URL, settings_url};
+use warp_tui::app::button::{Button, button_at, layout_buttons};
+use warp_tui::app::config::{Config, Profile};
+use warp_tui::app::favorites::{FavoriteAction, parse_function_key};
+use warp_tui::app::history::{LATENCY_HISTORY_CAPACITY, LatencyHistory};
+use warp_tui::app::keymap::{Action, KeyConflict, KeyMap};
+use warp_tui::app::log::{LOG_CAPACITY, LogBuffer};
+use warp_tui::app::notification::status_change_message;
+use warp_tui::app::signal::Shutdown;
+use warp_tui::app::tab::Tab;
+use warp_tui::app::{
+    App, COMPACT_HEIGHT, CONNECT_DEBOUNCE, ERROR_DISPLAY_DURATION, MAX_COMMAND_TIMEOUT_SECS,
+    MAX_REFRESH_INTERVAL_MS, MIN_COMMAND_TIMEOUT_SECS, MIN_REFRESH_INTERVAL_MS, PendingAction,
+    RECONNECT_COOLDOWN, adjust_command_timeout, adjust_refresh_interval, compact_status_line,
+    connect_command_allowed, error_visible, filter_modes, format_elapsed, is_compact, network_line,
+    next_index, previous_index, reconnect_allowed, refresh_countdown, spinner_char,
+    status_exit_code, version_label,
+};
+use warp_tui::warp::WarpClient;
+use warp_tui::warp::error::WarpError;
+use warp_tui::warp::manager::{MAX_STATUS_RETRIES, WarpMessage, should_retry_status};
+use warp_tui::warp::types::{
+    DaemonStatus, NetworkInfo, NetworkType, RegistrationInfo, WarpInfo, WarpMode, WarpStats,
+    WarpStatus,
+};
+
+#[test]
+fn test_status_update_through_channel() {
+    let mut app = App::new();
+    assert_eq!(app.warp_info().status, WarpStatus::Unknown);
+
+    let info = WarpInfo {
+        status: WarpStatus::Connected,
+        mode: Some(WarpMode::WarpDoH),
+        account_type: Some("Free".to_string()),
+        warp_enabled: true,
+        ..Default::default()
+    };
+    app.warp_manager()
+        .get_event_sender()
+        .send(WarpMessage::StatusUpdate(info, None))
+        .unwrap();
+
+    app.process_warp_messages();
+
+    assert_eq!(app.warp_info().status, WarpStatus::Connected);
+    assert_eq!(app.warp_info().mode, Some(WarpMode::WarpDoH));
+    assert_eq!(app.warp_info().account_type, Some("Free".to_string()));
+    assert!(app.warp_info().warp_enabled);
+}
+
+#[test]
+fn test_error_through_channel() {
+    let mut app = App::new();
+    let sender = app.warp_manager().get_event_sender();
+
+    sender
+        .send(WarpMessage::Error("Command failed: boom".to_string()))
+        .unwrap();
+    app.process_warp_messages();
+    assert_eq!(app.last_error(), Some("Command failed: boom"));
+
+    // A successful status update clears the previous error
+    sender
+        .send(WarpMessage::StatusUpdate(WarpInfo::default(), None))
+        .unwrap();
+    app.process_warp_messages();
+    assert_eq!(app.last_error(), None);
+}
+
+#[test]
+fn test_help_overlay_toggle() {
+    let mut app = App::new();
+    assert!(!app.is_help_visible());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('?')));
+    assert!(app.is_help_visible());
+
+    // Any key dismisses the overlay without triggering its normal action
+    app.on_key_event(KeyEvent::from(KeyCode::Char('m')));
+    assert!(!app.is_help_visible());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('?')));
+    app.on_key_event(KeyEvent::from(KeyCode::Esc));
+    assert!(!app.is_help_visible());
+}
+
+#[test]
+fn test_adjust_refresh_interval_clamps() {
+    assert_eq!(adjust_refresh_interval(1000, 250), 1250);
+    assert_eq!(adjust_refresh_interval(1000, -250), 750);
+
+    // Never drops below the minimum, even for large negative deltas
+    assert_eq!(
+        adjust_refresh_interval(MIN_REFRESH_INTERVAL_MS, -250),
+        MIN_REFRESH_INTERVAL_MS
+    );
+    assert_eq!(adjust_refresh_interval(0, -250), MIN_REFRESH_INTERVAL_MS);
+    assert_eq!(
+        adjust_refresh_interval(300, i64::MIN),
+        MIN_REFRESH_INTERVAL_MS
+    );
+
+    // Never exceeds the maximum
+    assert_eq!(
+        adjust_refresh_interval(MAX_REFRESH_INTERVAL_MS, 250),
+        MAX_REFRESH_INTERVAL_MS
+    );
+    assert_eq!(
+        adjust_refresh_interval(9900, i64::MAX),
+        MAX_REFRESH_INTERVAL_MS
+    );
+}
+
+#[test]
+fn test_refresh_interval_keys() {
+    let mut app = App::new();
+    assert_eq!(app.refresh_interval_ms(), 1000);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('+')));
+    assert_eq!(app.refresh_interval_ms(), 1250);
+
+    for _ in 0..10 {
+        app.on_key_event(KeyEvent::from(KeyCode::Char('-')));
+    }
+    assert_eq!(app.refresh_interval_ms(), MIN_REFRESH_INTERVAL_MS);
+}
+
+#[test]
+fn test_pause_auto_refresh() {
+    let mut app = App::new();
+    for _ in 0..10 {
+        app.on_key_event(KeyEvent::from(KeyCode::Char('-')));
+    }
+    std::thread::sleep(Duration::from_millis(MIN_REFRESH_INTERVAL_MS + 50));
+    assert!(app.should_auto_refresh());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char(' ')));
+    assert!(app.is_auto_refresh_paused());
+    assert!(!app.should_auto_refresh());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char(' ')));
+    assert!(!app.is_auto_refresh_paused());
+    assert!(app.should_auto_refresh());
+}
+
+#[test]
+fn test_paused_app_ignores_periodic_status_updates() {
+    let mut app = App::new();
+    let connected = WarpInfo {
+        status: WarpStatus::Connected,
+        ..Default::default()
+    };
+
+    // Pausing also stops the periodic refreshes of the manager
+    app.on_key_event(KeyEvent::from(KeyCode::Char(' ')));
+    assert!(app.warp_manager().is_paused());
+    app.handle_warp_message(WarpMessage::StatusUpdate(connected.clone(), None));
+    assert_eq!(app.warp_info().status, WarpStatus::Unknown);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char(' ')));
+    assert!(!app.warp_manager().is_paused());
+    app.handle_warp_message(WarpMessage::StatusUpdate(connected, None));
+    assert_eq!(app.warp_info().status, WarpStatus::Connected);
+}
+
+#[test]
+fn test_log_buffer_is_bounded() {
+    let mut log = LogBuffer::new();
+    for i in 0..LOG_CAPACITY + 100 {
+        log.push(format!("entry {}", i));
+    }
+    assert_eq!(log.len(), LOG_CAPACITY);
+
+    // The oldest entries were dropped
+    let first = log.entries().next().unwrap();
+    assert!(first.ends_with("entry 100"), "unexpected entry {}", first);
+    let last = log.entries().last().unwrap();
+    assert!(last.ends_with(&format!("entry {}", LOG_CAPACITY + 99)));
+
+    log.clear();
+    assert!(log.is_empty());
+}
+
+#[test]
+fn test_log_buffer_scrolling() {
+    let mut log = LogBuffer::new();
+    for i in 0..10 {
+        log.push(format!("entry {}", i));
+    }
+
+    let visible: Vec<&str> = log.visible(3).collect();
+    assert_eq!(visible.len(), 3);
+    assert!(visible[2].ends_with("entry 9"));
+
+    log.scroll_up(5);
+    let visible: Vec<&str> = log.visible(3).collect();
+    assert!(visible[2].ends_with("entry 4"));
+
+    // Scrolling is clamped to the available entries
+    log.scroll_up(100);
+    assert_eq!(log.scroll_offset(), 9);
+    let visible: Vec<&str> = log.visible(3).collect();
+    assert_eq!(visible.len(), 1);
+    assert!(visible[0].ends_with("entry 0"));
+
+    log.scroll_down(100);
+    assert_eq!(log.scroll_offset(), 0);
+}
+
+#[test]
+fn test_errors_are_logged_once() {
+    let mut app = App::new();
+    let sender = app.warp_manager().get_event_sender();
+
+    for _ in 0..3 {
+        sender
+            .send(WarpMessage::Error("Status update failed".to_string()))
+            .unwrap();
+    }
+    app.process_warp_messages();
+    assert_eq!(app.log().len(), 1);
+
+    // Ctrl-X only aborts a running command, it doesn't clear the log
+    app.on_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL));
+    assert_eq!(app.log().len(), 1);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('x')));
+    assert!(app.log().is_empty());
+}
+
+#[test]
+fn test_version_label() {
+    assert_eq!(
+        version_label(&Ok("2024.6.497".to_string())),
+        "warp-cli: 2024.6.497"
+    );
+    assert_eq!(
+        version_label(&Err(WarpError::CommandNotFound)),
+        "warp-cli: not found"
+    );
+    assert_eq!(
+        version_label(&Err(WarpError::ParseError("bad".to_string()))),
+        "warp-cli: unknown version"
+    );
+}
+
+#[test]
+fn test_spinner_wraps_around() {
+    assert_eq!(spinner_char(0), spinner_char(10));
+    assert_ne!(spinner_char(0), spinner_char(1));
+
+    let app = App::new();
+    assert_eq!(app.pending_operation(), None);
+}
+
+#[test]
+fn test_log_message_through_channel() {
+    let mut app = App::new();
+    app.warp_manager()
+        .get_event_sender()
+        .send(WarpMessage::Log("Already connected".to_string()))
+        .unwrap();
+
+    app.process_warp_messages();
+
+    assert_eq!(app.log().len(), 1);
+    assert!(
+        app.log()
+            .entries()
+            .any(|e| e.ends_with("Already connected"))
+    );
+    assert_eq!(app.last_error(), None);
+}
+
+#[test]
+fn test_vim_keys_in_mode_selection() {
+    let mut app = App::default();
+    let press = |app: &mut App, c: char| app.on_key_event(KeyEvent::from(KeyCode::Char(c)));
+
+    press(&mut app, 'm');
+    assert_eq!(app.selected_index(), Some(0));
+
+    press(&mut app, 'j');
+    press(&mut app, 'j');
+    assert_eq!(app.selected_index(), Some(2));
+    press(&mut app, 'k');
+    assert_eq!(app.selected_index(), Some(1));
+
+    press(&mut app, 'G');
+    assert_eq!(app.selected_index(), Some(7));
+
+    // A single 'g' doesn't move, and doesn't open the registration panel
+    press(&mut app, 'g');
+    assert_eq!(app.selected_index(), Some(7));
+    press(&mut app, 'g');
+    assert_eq!(app.selected_index(), Some(0));
+
+    // An interrupted "gg" starts over
+    press(&mut app, 'G');
+    press(&mut app, 'g');
+    press(&mut app, 'k');
+    press(&mut app, 'g');
+    assert_eq!(app.selected_index(), Some(6));
+
+    app.on_key_event(KeyEvent::from(KeyCode::Esc));
+    assert_eq!(app.selected_index(), None);
+}
+
+#[test]
+fn test_button_hit_testing() {
+    let buttons = layout_buttons(Rect::new(2, 10, 60, 3));
+    assert_eq!(buttons.len(), 3);
+    assert_eq!(buttons[0], (Button::Connect, Rect::new(2, 10, 14, 3)));
+
+    assert_eq!(button_at(&buttons, 2, 10), Some(Button::Connect));
+    assert_eq!(button_at(&buttons, 15, 12), Some(Button::Connect));
+    assert_eq!(button_at(&buttons, 17, 11), Some(Button::Disconnect));
+    assert_eq!(button_at(&buttons, 33, 11), Some(Button::Mode));
+    // The gap between buttons, and the rows around them
+    assert_eq!(button_at(&buttons, 16, 11), None);
+    assert_eq!(button_at(&buttons, 5, 13), None);
+    assert_eq!(button_at(&buttons, 5, 9), None);
+
+    // A narrower area shrinks the buttons instead of overflowing it
+    let buttons = layout_buttons(Rect::new(0, 0, 20, 3));
+    assert!(buttons.iter().all(|(_, area)| area.right() <= 20));
+}
+
+#[test]
+fn test_click_without_buttons_is_ignored() {
+    let mut app = App::new();
+    app.on_mouse_event(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: 5,
+        row: 5,
+        modifiers: KeyModifiers::NONE,
+    });
+    assert_eq!(app.selected_index(), None);
+    assert_eq!(app.pending_operation(), None);
+}
+
+#[test]
+fn test_diagnostics_run_in_background() {
+    let mut app = App::new();
+    app.on_key_event(KeyEvent::from(KeyCode::Char('t')));
+    assert_eq!(app.pending_operation(), Some("Running diagnostics…"));
+
+    let start = std::time::Instant::now();
+    while app.pending_operation().is_some() && start.elapsed() < Duration::from_secs(60) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+    assert_eq!(app.pending_operation(), None);
+
+    // Either the check ran, or its failure was reported
+    match app.diagnostics_output() {
+        Some(_) => {
+            app.on_key_event(KeyEvent::from(KeyCode::Esc));
+            assert_eq!(app.diagnostics_output(), None);
+        }
+        None => assert!(
+            app.last_error()
+                .is_some_and(|e| e.starts_with("Connectivity check failed"))
+        ),
+    }
+}
+
+#[test]
+fn test_missing_warp_cli_disables_commands() {
+    let mut app = App::default();
+    assert!(!app.is_cli_missing());
+
+    app.handle_status_result(Err(WarpError::CommandNotFound));
+    assert!(app.is_cli_missing());
+    assert_eq!(app.warp_info().status, WarpStatus::Unknown);
+
+    // Connect, disconnect and mode are disabled
+    for key in ['c', 'd', 'm'] {
+        app.on_key_event(KeyEvent::from(KeyCode::Char(key)));
+    }
+    assert_eq!(app.pending_operation(), None);
+    assert_eq!(app.selected_index(), None);
+
+    // Other failures don't mean warp-cli is missing
+    app.handle_status_result(Err(WarpError::command_failed("daemon")));
+    assert!(!app.is_cli_missing());
+
+    app.handle_status_result(Err(WarpError::CommandNotFound));
+    app.handle_status_result(Ok(WarpInfo::default()));
+    assert!(!app.is_cli_missing());
+}
+
+#[test]
+fn test_refresh_countdown() {
+    assert_eq!(
+        refresh_countdown(Duration::from_millis(300), 1000),
+        Duration::from_millis(700)
+    );
+    assert_eq!(
+        refresh_countdown(Duration::ZERO, 250),
+        Duration::from_millis(250)
+    );
+    // Clamped at zero once the refresh is due
+    assert_eq!(
+        refresh_countdown(Duration::from_secs(2), 1000),
+        Duration::ZERO
+    );
+}
+
+fn connected_app(confirm_quit_when_connected: bool) -> App {
+    let mut app = App::default().with_config(Config {
+        confirm_quit_when_connected,
+        ..Config::default()
+    });
+    app.handle_status_result(Ok(WarpInfo {
+        status: WarpStatus::Connected,
+        ..Default::default()
+    }));
+    app
+}
+
+#[test]
+fn test_quit_confirmation_when_connected() {
+    let mut app = connected_app(true);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('q')));
+    assert!(app.is_running());
+    assert_eq!(
+        app.pending_action(),
+        Some(PendingAction::QuitWhileConnected)
+    );
+
+    // Other keys are ignored while the dialog is open
+    app.on_key_event(KeyEvent::from(KeyCode::Char('m')));
+    assert_eq!(
+        app.pending_action(),
+        Some(PendingAction::QuitWhileConnected)
+    );
+
+    app.on_key_event(KeyEvent::from(KeyCode::Esc));
+    assert!(app.is_running());
+    assert_eq!(app.pending_action(), None);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('q')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('l')));
+    assert!(!app.is_running());
+    assert_eq!(app.pending_action(), None);
+}
+
+#[test]
+fn test_quit_without_confirmation() {
+    // Disabled in the config
+    let mut app = connected_app(false);
+    app.on_key_event(KeyEvent::from(KeyCode::Char('q')));
+    assert!(!app.is_running());
+
+    // Not connected
+    let mut app = App::default().with_config(Config {
+        confirm_quit_when_connected: true,
+        ..Config::default()
+    });
+    app.on_key_event(KeyEvent::from(KeyCode::Char('q')));
+    assert!(!app.is_running());
+    assert_eq!(app.pending_action(), None);
+
+    // Ctrl-C always quits immediately
+    let mut app = connected_app(true);
+    app.on_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL));
+    assert!(!app.is_running());
+    assert_eq!(app.pending_action(), None);
+}
+
+#[test]
+fn test_config_parsing() {
+    let (config, warnings) = Config::from_toml_str("confirm_quit_when_connected = true\n");
+    assert!(config.confirm_quit_when_connected);
+    assert!(warnings.is_empty());
+
+    let (config, warnings) = Config::from_toml_str("");
+    assert_eq!(config, Config::default());
+    assert!(config.wrap_selection);
+    assert!(warnings.is_empty());
+
+    assert_eq!(config.stale_transition_secs, 15);
+
+    let (config, _) = Config::from_toml_str("wrap_selection = false\n");
+    assert!(!config.wrap_selection);
+    assert!(!config.confirm_quit_when_connected);
+
+    let (config, warnings) = Config::from_toml_str("confirm_quit_when_connected = \"yes\"\n");
+    assert_eq!(config, Config::default());
+    assert_eq!(warnings.len(), 1);
+}
+
+#[test]
+fn test_profile_config_parsing() {
+    let (config, warnings) = Config::from_toml_str(
+        r#"
+[[profiles]]
+name = "Work"
+mode = "warp+doh"
+families_mode = "malware"
+
+[[profiles]]
+name = "DNS only"
+mode = "doh"
+"#,
+    );
+    assert!(warnings.is_empty());
+    assert_eq!(
+        config.profiles,
+        vec![
+            Profile {
+                name: "Work".to_string(),
+                mode: Some("warp+doh".to_string()),
+                families_mode: Some("malware".to_string()),
+            },
+            Profile {
+                name: "DNS only".to_string(),
+                mode: Some("doh".to_string()),
+                families_mode: None,
+            },
+        ]
+    );
+    assert_eq!(config.profile("DNS only"), Some(&config.profiles[1]));
+    assert_eq!(config.profile("Home"), None);
+
+    // A profile needs a name
+    let (config, warnings) = Config::from_toml_str("[[profiles]]\nmode = \"doh\"\n");
+    assert_eq!(config, Config::default());
+    assert_eq!(warnings.len(), 1);
+}
+
+#[test]
+fn test_profile_selection() {
+    let press = |app: &mut App, code: KeyCode| app.on_key_event(KeyEvent::from(code));
+
+    // Nothing to pick without profiles
+    let mut app = App::default();
+    press(&mut app, KeyCode::Char('o'));
+    assert_eq!(app.selected_index(), None);
+
+    let profile = |name: &str| Profile {
+        name: name.to_string(),
+        mode: None,
+        families_mode: None,
+    };
+    let mut app = App::default().with_config(Config {
+        profiles: vec![profile("Work"), profile("Home")],
+        ..Config::default()
+    });
+    press(&mut app, KeyCode::Char('o'));
+    assert_eq!(app.selected_index(), Some(0));
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.selected_index(), Some(1));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.selected_index(), None);
+
+    // Applying a profile without settings just makes it the active one
+    press(&mut app, KeyCode::Char('o'));
+    press(&mut app, KeyCode::Char('G'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.selected_index(), None);
+    assert_eq!(app.active_profile(), Some("Home"));
+    assert_eq!(app.session_state().last_profile.as_deref(), Some("Home"));
+
+    // The active profile is preselected
+    press(&mut app, KeyCode::Char('o'));
+    assert_eq!(app.selected_index(), Some(1));
+}
+
+#[test]
+fn test_tunnel_protocol_selection() {
+    let mut app = App::default();
+    let press = |app: &mut App, code: KeyCode| app.on_key_event(KeyEvent::from(code));
+
+    press(&mut app, KeyCode::Char('p'));
+    assert_eq!(app.selected_index(), Some(0));
+
+    press(&mut app, KeyCode::Char('j'));
+    assert_eq!(app.selected_index(), Some(1));
+    press(&mut app, KeyCode::Char('j'));
+    assert_eq!(app.selected_index(), Some(0));
+    press(&mut app, KeyCode::Char('G'));
+    assert_eq!(app.selected_index(), Some(1));
+
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.selected_index(), None);
+}
+
+#[test]
+fn test_dns_stats_panel() {
+    let mut app = App::default();
+    assert!(app.dns_stats().is_none());
+
+    // Without warp-cli the panel shows why the statistics are missing
+    app.on_key_event(KeyEvent::from(KeyCode::Char('s')));
+    assert_eq!(app.pending_operation(), Some("Fetching DNS statistics…"));
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+    assert!(matches!(app.dns_stats(), Some(Err(_))));
+
+    // Other keys are ignored while the panel is open
+    app.on_key_event(KeyEvent::from(KeyCode::Char('m')));
+    assert_eq!(app.selected_index(), None);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('s')));
+    assert!(app.dns_stats().is_none());
+}
+
+#[test]
+fn test_tunnel_stats_panel() {
+    let mut app = App::default();
+    assert!(app.tunnel_stats().is_none());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('h')));
+    assert_eq!(app.pending_operation(), Some("Fetching tunnel statistics…"));
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+    assert!(matches!(app.tunnel_stats(), Some(Err(_))));
+
+    // Other keys are ignored while the panel is open
+    app.on_key_event(KeyEvent::from(KeyCode::Char('s')));
+    assert!(app.dns_stats().is_none());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Esc));
+    assert!(app.tunnel_stats().is_none());
+}
+
+#[test]
+fn test_selection_index_wrapping() {
+    assert_eq!(next_index(0, 3, true), 1);
+    assert_eq!(next_index(2, 3, true), 0);
+    assert_eq!(previous_index(1, 3, true), 0);
+    assert_eq!(previous_index(0, 3, true), 2);
+}
+
+#[test]
+fn test_selection_index_clamping() {
+    assert_eq!(next_index(0, 3, false), 1);
+    assert_eq!(next_index(2, 3, false), 2);
+    assert_eq!(previous_index(1, 3, false), 0);
+    assert_eq!(previous_index(0, 3, false), 0);
+
+    // Single entry lists never move
+    assert_eq!(next_index(0, 1, false), 0);
+    assert_eq!(previous_index(0, 1, true), 0);
+}
+
+#[test]
+fn test_selection_without_wrap_around() {
+    let mut app = App::default().with_config(Config {
+        wrap_selection: false,
+        ..Config::default()
+    });
+    let press = |app: &mut App, code: KeyCode| app.on_key_event(KeyEvent::from(code));
+
+    press(&mut app, KeyCode::Char('m'));
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.selected_index(), Some(0));
+
+    press(&mut app, KeyCode::Char('G'));
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.selected_index(), Some(7));
+}
+
+fn status_info(status: WarpStatus) -> WarpInfo {
+    WarpInfo {
+        status,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_transition_tracking() {
+    let mut app = App::default();
+    assert_eq!(app.transition_elapsed(), None);
+
+    app.handle_status_result(Ok(status_info(WarpStatus::Connecting)));
+    let first = app.transition_elapsed().unwrap();
+    assert!(!app.is_transition_stale());
+
+    // Repeated updates with the same status keep counting from the first one
+    std::thread::sleep(Duration::from_millis(20));
+    app.handle_status_result(Ok(status_info(WarpStatus::Connecting)));
+    assert!(app.transition_elapsed().unwrap() >= first + Duration::from_millis(20));
+
+    app.handle_status_result(Ok(status_info(WarpStatus::Connected)));
+    assert_eq!(app.transition_elapsed(), None);
+
+    // Updates from the manager are tracked too
+    app.handle_warp_message(WarpMessage::StatusUpdate(
+        status_info(WarpStatus::Disconnecting),
+        None,
+    ));
+    assert!(app.transition_elapsed().is_some());
+    app.handle_status_result(Err(WarpError::command_failed("boom")));
+    assert_eq!(app.transition_elapsed(), None);
+}
+
+#[test]
+fn test_stale_transition_allows_retry() {
+    let mut app = App::default().with_config(Config {
+        stale_transition_secs: 0,
+        ..Config::default()
+    });
+    app.handle_status_result(Ok(status_info(WarpStatus::Connecting)));
+    assert!(app.is_transition_stale());
+
+    // A stuck connect can be retried
+    app.on_key_event(KeyEvent::from(KeyCode::Char('c')));
+    assert_eq!(app.pending_operation(), Some("Connecting…"));
+}
+
+#[test]
+fn test_active_transition_blocks_connect() {
+    let mut app = App::default();
+    app.handle_status_result(Ok(status_info(WarpStatus::Connecting)));
+    assert!(!app.is_transition_stale());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('c')));
+    assert_eq!(app.pending_operation(), None);
+}
+
+#[test]
+fn test_status_change_notification_message() {
+    let disconnected = WarpInfo {
+        status: WarpStatus::Disconnected,
+        reason: Some("Manual Disconnection".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        status_change_message(&WarpStatus::Connected, &disconnected).as_deref(),
+        Some("Disconnected (Manual Disconnection)")
+    );
+    assert_eq!(
+        status_change_message(
+            &WarpStatus::Disconnected,
+            &status_info(WarpStatus::Connected)
+        )
+        .as_deref(),
+        Some("Connected")
+    );
+
+    // No change, or a change that isn't a connect or disconnect
+    assert_eq!(
+        status_change_message(&WarpStatus::Disconnected, &disconnected),
+        None
+    );
+    assert_eq!(
+        status_change_message(&WarpStatus::Unknown, &status_info(WarpStatus::Connected)),
+        None
+    );
+    assert_eq!(
+        status_change_message(&WarpStatus::Connected, &status_info(WarpStatus::Connecting)),
+        None
+    );
+}
+
+#[test]
+fn test_reconnect_cooldown() {
+    let now = Instant::now();
+    assert!(reconnect_allowed(None, now));
+    assert!(!reconnect_allowed(Some(now), now));
+    assert!(!reconnect_allowed(
+        Some(now),
+        now + RECONNECT_COOLDOWN - Duration::from_secs(1)
+    ));
+    assert!(reconnect_allowed(Some(now), now + RECONNECT_COOLDOWN));
+
+    // A last attempt in the future (clock oddities) doesn't allow a reconnect
+    assert!(!reconnect_allowed(Some(now + Duration::from_secs(5)), now));
+}
+
+fn dropped_info(reason: &str) -> WarpInfo {
+    WarpInfo {
+        status: WarpStatus::Disconnected,
+        reason: Some(reason.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_auto_reconnect_after_drop() {
+    let mut app = App::default();
+    assert!(!app.is_auto_reconnect_enabled());
+
+    // Disabled by default
+    app.handle_status_result(Ok(status_info(WarpStatus::Connected)));
+    app.handle_status_result(Ok(dropped_info("Network change")));
+    assert_eq!(app.pending_operation(), None);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('a')));
+    assert!(app.is_auto_reconnect_enabled());
+
+    // A manual disconnect is left alone
+    app.handle_status_result(Ok(status_info(WarpStatus::Connected)));
+    app.handle_status_result(Ok(dropped_info("Manual Disconnection")));
+    assert_eq!(app.pending_operation(), None);
+
+    app.handle_status_result(Ok(status_info(WarpStatus::Connected)));
+    app.handle_status_result(Ok(dropped_info("Network change")));
+    assert_eq!(app.pending_operation(), Some("Reconnecting…"));
+
+    // Wait for the attempt to fail without warp-cli
+    for _ in 0..200 {
+        app.poll_pending_operation();
+        if app.pending_operation().is_none() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(app.pending_operation(), None);
+
+    // A second drop within the cooldown doesn't reconnect again
+    app.handle_status_result(Ok(status_info(WarpStatus::Connected)));
+    app.handle_status_result(Ok(dropped_info("Network change")));
+    assert_eq!(app.pending_operation(), None);
+}
+
+#[test]
+fn test_filter_modes() {
+    assert_eq!(filter_modes("").len(), 8);
+    assert_eq!(filter_modes("DOH"), vec![WarpMode::DoH, WarpMode::WarpDoH]);
+    assert_eq!(
+        filter_modes("only"),
+        vec![WarpMode::TunnelOnly, WarpMode::DnsOnly]
+    );
+    assert!(filter_modes("doq").is_empty());
+}
+
+#[test]
+fn test_mode_selection_filter() {
+    let mut app = App::default();
+    let press = |app: &mut App, code: KeyCode| app.on_key_event(KeyEvent::from(code));
+
+    press(&mut app, KeyCode::Char('m'));
+    for c in "dot".chars() {
+        press(&mut app, KeyCode::Char(c));
+    }
+    assert_eq!(app.mode_filter(), "dot");
+    assert_eq!(app.selected_index(), Some(0));
+
+    // Navigation wraps around the two matches, not the full list
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.selected_index(), Some(1));
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.selected_index(), Some(0));
+    press(&mut app, KeyCode::Char('G'));
+    assert_eq!(app.selected_index(), Some(1));
+
+    // No matches leaves nothing to select
+    press(&mut app, KeyCode::Char('x'));
+    assert_eq!(app.selected_index(), None);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.selected_index(), None);
+    press(&mut app, KeyCode::Backspace);
+    assert_eq!(app.mode_filter(), "dot");
+    assert_eq!(app.selected_index(), Some(0));
+
+    // Esc clears the filter first, then closes the list
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.mode_filter(), "");
+    assert_eq!(app.selected_index(), Some(0));
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.selected_index(), None);
+}
+
+#[test]
+fn test_details_toggle() {
+    let mut app = App::default();
+    assert!(!app.is_details_visible());
+    app.on_key_event(KeyEvent::from(KeyCode::Char('i')));
+    assert!(app.is_details_visible());
+    app.on_key_event(KeyEvent::from(KeyCode::Char('i')));
+    assert!(!app.is_details_visible());
+}
+
+#[test]
+fn test_latency_history_never_exceeds_capacity() {
+    let mut history = LatencyHistory::new();
+    assert_eq!(history.capacity(), LATENCY_HISTORY_CAPACITY);
+    for i in 0..LATENCY_HISTORY_CAPACITY as u64 * 2 {
+        history.push(i);
+        assert!(history.len() <= LATENCY_HISTORY_CAPACITY);
+    }
+    assert_eq!(history.len(), LATENCY_HISTORY_CAPACITY);
+    // The oldest samples were dropped
+    assert_eq!(
+        history.samples().next(),
+        Some(LATENCY_HISTORY_CAPACITY as u64)
+    );
+
+    let mut small = LatencyHistory::with_capacity(3);
+    for i in 0..10 {
+        small.push(i);
+    }
+    assert_eq!(small.samples().collect::<Vec<_>>(), vec![7, 8, 9]);
+
+    let mut empty = LatencyHistory::with_capacity(0);
+    empty.push(1);
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_latency_history_follows_status() {
+    let mut app = App::default();
+    let connected = WarpInfo {
+        status: WarpStatus::Connected,
+        ..Default::default()
+    };
+    let stats = |latency_ms| WarpStats {
+        latency_ms,
+        ..Default::default()
+    };
+
+    app.handle_status_with_stats_result(Ok((connected.clone(), Some(stats(Some(20))))));
+    app.handle_status_with_stats_result(Ok((connected.clone(), Some(stats(Some(35))))));
+    // Refreshes without a latency don't add a sample
+    app.handle_status_with_stats_result(Ok((connected.clone(), Some(stats(None)))));
+    app.handle_status_with_stats_result(Ok((connected, None)));
+    assert_eq!(
+        app.latency_history().samples().collect::<Vec<_>>(),
+        vec![20, 35]
+    );
+
+    // Disconnecting clears the window
+    app.handle_status_result(Ok(WarpInfo {
+        status: WarpStatus::Disconnected,
+        ..Default::default()
+    }));
+    assert!(app.latency_history().is_empty());
+}
+
+/// Status updates from the background manager carry the statistics for the sparkline
+#[test]
+fn test_latency_history_from_manager_updates() {
+    let mut app = App::default();
+    let connected = WarpInfo {
+        status: WarpStatus::Connected,
+        ..Default::default()
+    };
+    let stats = |latency_ms| {
+        Some(Box::new(WarpStats {
+            latency_ms: Some(latency_ms),
+            ..Default::default()
+        }))
+    };
+
+    app.handle_warp_message(WarpMessage::StatusUpdate(connected.clone(), stats(20)));
+    app.handle_warp_message(WarpMessage::StatusUpdate(connected.clone(), None));
+    app.handle_warp_message(WarpMessage::StatusUpdate(connected, stats(35)));
+    assert_eq!(
+        app.latency_history().samples().collect::<Vec<_>>(),
+        vec![20, 35]
+    );
+    assert_eq!(app.warp_info().status, WarpStatus::Connected);
+}
+
+#[test]
+fn test_override_dialog() {
+    let press = |app: &mut App, code: KeyCode| app.on_key_event(KeyEvent::from(code));
+    let mut app = App::default();
+
+    // Overrides always ask first
+    press(&mut app, KeyCode::Char('u'));
+    assert_eq!(app.pending_action(), Some(PendingAction::Override));
+    press(&mut app, KeyCode::Char('d'));
+    assert_eq!(app.pending_action(), Some(PendingAction::Override));
+    assert!(app.is_running());
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.pending_action(), None);
+
+    // Without a configured policy there's nothing to apply
+    press(&mut app, KeyCode::Char('u'));
+    press(&mut app, KeyCode::Char('a'));
+    assert_eq!(app.pending_action(), None);
+    assert!(
+        app.last_error()
+            .unwrap()
+            .contains("No override policy configured")
+    );
+}
+
+#[test]
+fn test_reset_settings_dialog() {
+    let press = |app: &mut App, code: KeyCode| app.on_key_event(KeyEvent::from(code));
+    let mut app = App::default();
+
+    // Resetting always asks first, and only 'y' confirms
+    press(&mut app, KeyCode::Char('e'));
+    assert_eq!(app.pending_action(), Some(PendingAction::ResetSettings));
+    press(&mut app, KeyCode::Char('n'));
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.pending_action(), Some(PendingAction::ResetSettings));
+    assert_eq!(app.pending_operation(), None);
+
+    press(&mut app, KeyCode::Esc);
+    assert_eq!(app.pending_action(), None);
+    assert_eq!(app.pending_operation(), None);
+    assert!(app.is_running());
+    assert!(
+        !app.log()
+            .entries()
+            .any(|entry| entry.contains("settings reset"))
+    );
+}
+
+#[test]
+fn test_status_changes_are_logged() {
+    let mut app = App::default();
+    let connected = WarpInfo {
+        status: WarpStatus::Connected,
+        mode: Some(WarpMode::WarpDoH),
+        ..Default::default()
+    };
+
+    // The first status isn't a change
+    app.handle_status_result(Ok(connected.clone()));
+    assert_eq!(app.log().len(), 0);
+
+    app.handle_status_result(Ok(WarpInfo {
+        mode: Some(WarpMode::DoT),
+        ..connected
+    }));
+    let entries: Vec<&str> = app.log().entries().collect();
+    assert_eq!(entries.len(), 1);
+    assert!(
+        entries[0].ends_with("Mode: Warp+DoH -> DoT"),
+        "{}",
+        entries[0]
+    );
+}
+
+#[test]
+fn test_initial_load() {
+    let mut app = App::default();
+    assert!(!app.is_initial_load_done());
+    app.handle_status_result(Ok(WarpInfo::default()));
+    assert!(app.is_initial_load_done());
+
+    // A failed fetch also ends the loading state, showing the error instead
+    let mut app = App::default();
+    app.handle_status_result(Err(WarpError::CommandNotFound));
+    assert!(app.is_initial_load_done());
+
+    let mut app = App::default();
+    app.handle_warp_message(WarpMessage::Error("daemon not ready".to_string()));
+    assert!(app.is_initial_load_done());
+}
+
+#[test]
+fn test_settings_url() {
+    let registration = |organization: Option<&str>| RegistrationInfo {
+        organization: organization.map(str::to_string),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        settings_url(Some(&registration(Some("Acme-Corp")))),
+        "https://acme-corp.cloudflareaccess.com/"
+    );
+    // Consumer registrations and organizations that can't be a subdomain get the docs
+    assert_eq!(settings_url(None), DOCS_URL);
+    assert_eq!(settings_url(Some(&registration(None))), DOCS_URL);
+    assert_eq!(settings_url(Some(&registration(Some("  ")))), DOCS_URL);
+    assert_eq!(
+        settings_url(Some(&registration(Some("evil.com/x?")))),
+        DOCS_URL
+    );
+}
+
+#[cfg(not(feature = "browser"))]
+#[test]
+fn test_open_settings_page_without_browser() {
+    let mut app = App::default();
+    app.on_key_event(KeyEvent::from(KeyCode::Char('b')));
+    assert_eq!(app.pending_operation(), Some("Opening settings page…"));
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+
+    // Without warp-cli the organization is unknown, so the docs are logged
+    let entries: Vec<&str> = app.log().entries().collect();
+    assert!(
+        entries[0].contains("Browser unavailable (built without browser support)"),
+        "{}",
+        entries[0]
+    );
+    assert!(entries[0].ends_with(DOCS_URL), "{}", entries[0]);
+}
+
+#[cfg(not(feature = "clipboard"))]
+#[test]
+fn test_copy_status_without_clipboard() {
+    let mut app = App::default();
+    app.handle_status_result(Ok(WarpInfo {
+        status: WarpStatus::Connected,
+        mode: Some(WarpMode::WarpDoH),
+        ..Default::default()
+    }));
+
+    // The status ends up in the log instead
+    app.on_key_event(KeyEvent::from(KeyCode::Char('y')));
+    assert_eq!(app.toast(), None);
+    let entries: Vec<&str> = app.log().entries().collect();
+    assert!(entries[0].contains("Clipboard unavailable (built without clipboard support)"));
+    assert!(entries[1].ends_with("Status: Connected"), "{}", entries[1]);
+    assert!(entries[2].ends_with("Mode: Warp+DoH"), "{}", entries[2]);
+}
+
+#[test]
+fn test_tab_order() {
+    assert_eq!(Tab::default(), Tab::Status);
+    for tab in Tab::ALL {
+        assert_eq!(tab.next().previous(), tab);
+    }
+    assert_eq!(Tab::Logs.next(), Tab::Status);
+    assert_eq!(Tab::Status.previous(), Tab::Logs);
+
+    assert_eq!(Tab::from_number_key('1'), Some(Tab::Status));
+    assert_eq!(Tab::from_number_key('4'), Some(Tab::Logs));
+    assert_eq!(Tab::from_number_key('0'), None);
+    assert_eq!(Tab::from_number_key('5'), None);
+    assert_eq!(Tab::from_number_key('x'), None);
+}
+
+#[test]
+fn test_tab_switching() {
+    let press = |app: &mut App, code: KeyCode| app.on_key_event(KeyEvent::from(code));
+    let mut app = App::default();
+    assert_eq!(app.active_tab(), Tab::Status);
+    assert!(app.settings().is_none());
+
+    // Opening the settings tab fetches the settings in the background, which fails
+    // without warp-cli
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.active_tab(), Tab::Settings);
+    assert!(app.is_settings_loading());
+    let started = Instant::now();
+    while app.is_settings_loading() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_settings_check();
+    }
+    assert!(app.settings().unwrap().is_err());
+
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.active_tab(), Tab::Stats);
+    press(&mut app, KeyCode::BackTab);
+    assert_eq!(app.active_tab(), Tab::Settings);
+    press(&mut app, KeyCode::Char('4'));
+    assert_eq!(app.active_tab(), Tab::Logs);
+    press(&mut app, KeyCode::Char('1'));
+    assert_eq!(app.active_tab(), Tab::Status);
+
+    // Number keys type into the mode filter while the picker is open
+    press(&mut app, KeyCode::Char('m'));
+    press(&mut app, KeyCode::Char('2'));
+    assert_eq!(app.active_tab(), Tab::Status);
+    assert_eq!(app.mode_filter(), "2");
+}
+
+#[test]
+fn test_logs_tab_scrolls_by_line() {
+    let press = |app: &mut App, code: KeyCode| app.on_key_event(KeyEvent::from(code));
+    let mut app = App::default();
+    for i in 0..20 {
+        app.handle_status_result(Err(WarpError::ParseError(format!("error {}", i))));
+    }
+
+    // Up and Down only scroll the log on the logs tab
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.log().scroll_offset(), 0);
+    press(&mut app, KeyCode::Char('4'));
+    press(&mut app, KeyCode::Up);
+    press(&mut app, KeyCode::Char('k'));
+    assert_eq!(app.log().scroll_offset(), 2);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.log().scroll_offset(), 1);
+}
+
+#[test]
+fn test_keymap_parsing() {
+    let (keymap, warnings) = KeyMap::from_toml_str("");
+    assert!(warnings.is_empty());
+    assert_eq!(keymap, KeyMap::default());
+    assert_eq!(keymap.action_for('c'), Some(Action::Connect));
+    assert_eq!(keymap.action_for('C'), Some(Action::Connect));
+    assert_eq!(keymap.action_for('e'), None);
+
+    let (keymap, warnings) = KeyMap::from_toml_str("connect = \"0\"\nquit = \"Q\"\n");
+    assert!(warnings.is_empty());
+    assert_eq!(keymap.key_for(Action::Connect), '0');
+    assert_eq!(keymap.action_for('0'), Some(Action::Connect));
+    assert_eq!(keymap.action_for('c'), None);
+    assert_eq!(keymap.action_for('q'), Some(Action::Quit));
+    assert_eq!(keymap.key_for(Action::Refresh), 'r');
+
+    // Bad entries keep the default for that action only
+    let (keymap, warnings) =
+        KeyMap::from_toml_str("connect = \"ctrl-e\"\nteleport = \"t\"\nmode = \",\"\n");
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(keymap.key_for(Action::Connect), 'c');
+    assert_eq!(keymap.key_for(Action::Mode), ',');
+
+    let (keymap, warnings) = KeyMap::from_toml_str("this is not toml");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(keymap, KeyMap::default());
+}
+
+#[test]
+fn test_keymap_conflicts_fall_back_to_defaults() {
+    assert!(KeyMap::default().conflicts().is_empty());
+
+    // Swapping two keys isn't a conflict
+    let (keymap, warnings) = KeyMap::from_toml_str("connect = \"d\"\ndisconnect = \"c\"\n");
+    assert!(warnings.is_empty());
+    assert_eq!(keymap.action_for('d'), Some(Action::Connect));
+
+    // Refresh keeps its default 'r', so binding mode to 'R' clashes with it
+    let (keymap, warnings) = KeyMap::from_toml_str("connect = \"0\"\nmode = \"R\"\n");
+    assert_eq!(keymap, KeyMap::default());
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("\"refresh\""));
+    assert!(warnings[0].contains("\"mode\""));
+
+    let mut keymap = KeyMap::default();
+    keymap.bind(Action::Quit, 'c');
+    assert_eq!(
+        keymap.conflicts(),
+        vec![KeyConflict::Actions('c', Action::Connect, Action::Quit)]
+    );
+}
+
+#[test]
+fn test_keymap_rejects_reserved_keys() {
+    // 'f' opens the DNS families picker, the binding is skipped with a warning
+    let (keymap, warnings) = KeyMap::from_toml_str("connect = \"F\"\nquit = \"0\"\n");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("reserved"), "{}", warnings[0]);
+    assert_eq!(keymap.key_for(Action::Connect), 'c');
+    assert_eq!(keymap.key_for(Action::Quit), '0');
+    assert_eq!(keymap.action_for('f'), None);
+
+    let mut keymap = KeyMap::default();
+    keymap.bind(Action::Refresh, 'x');
+    assert_eq!(
+        keymap.conflicts(),
+        vec![KeyConflict::Reserved('x', Action::Refresh)]
+    );
+}
+
+#[test]
+fn test_rebound_keys() {
+    let mut keymap = KeyMap::default();
+    keymap.bind(Action::Quit, '0');
+    keymap.bind(Action::Mode, '9');
+    let mut app = App::default().with_keymap(keymap);
+
+    // The old keys do nothing
+    app.on_key_event(KeyEvent::from(KeyCode::Char('m')));
+    assert_eq!(app.selected_index(), None);
+    app.on_key_event(KeyEvent::from(KeyCode::Char('q')));
+    assert!(app.is_running());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('9')));
+    assert_eq!(app.selected_index(), Some(0));
+    app.on_key_event(KeyEvent::from(KeyCode::Esc));
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('0')));
+    assert!(!app.is_running());
+}
+
+#[test]
+fn test_daemon_status_changes_logged() {
+    let mut app = App::default();
+    assert_eq!(app.daemon_status(), None);
+
+    app.handle_daemon_status_result(Ok(DaemonStatus::Running));
+    assert_eq!(app.daemon_status(), Some(DaemonStatus::Running));
+    assert!(app.log().is_empty());
+
+    app.handle_daemon_status_result(Ok(DaemonStatus::Running));
+    app.handle_daemon_status_result(Ok(DaemonStatus::Unresponsive));
+    assert_eq!(app.daemon_status(), Some(DaemonStatus::Unresponsive));
+    let entries: Vec<&str> = app.log().entries().collect();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].contains("WARP daemon: Unresponsive"));
+}
+
+#[test]
+fn test_no_registration_call_to_action() {
+    let mut app = App::default();
+    assert!(!app.is_registration_missing());
+
+    // 'n' does nothing while the device is registered
+    app.handle_registration_check_result(Ok(RegistrationInfo::default()));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('n')));
+    assert_eq!(app.pending_action(), None);
+
+    // Only a missing registration counts, not any failure
+    app.handle_registration_check_result(Err(WarpError::CommandNotFound));
+    assert!(!app.is_registration_missing());
+
+    app.handle_registration_check_result(Err(WarpError::NoRegistration));
+    assert!(app.is_registration_missing());
+    app.on_key_event(KeyEvent::from(KeyCode::Char('n')));
+    assert_eq!(app.pending_action(), Some(PendingAction::Register));
+    app.on_key_event(KeyEvent::from(KeyCode::Esc));
+    assert_eq!(app.pending_action(), None);
+    assert_eq!(app.pending_operation(), None);
+
+    // A status with an account type means there is a registration
+    app.handle_warp_message(WarpMessage::StatusUpdate(
+        WarpInfo {
+            status: WarpStatus::Disconnected,
+            account_type: Some("Free".to_string()),
+            ..WarpInfo::default()
+        },
+        None,
+    ));
+    assert!(!app.is_registration_missing());
+}
+
+#[test]
+fn test_format_elapsed() {
+    assert_eq!(format_elapsed(Duration::ZERO), "00:00:00");
+    assert_eq!(format_elapsed(Duration::from_millis(59_999)), "00:00:59");
+    assert_eq!(
+        format_elapsed(Duration::from_secs(12 * 60 + 34)),
+        "00:12:34"
+    );
+    assert_eq!(format_elapsed(Duration::from_secs(3599)), "00:59:59");
+    assert_eq!(format_elapsed(Duration::from_secs(3600)), "01:00:00");
+    assert_eq!(
+        format_elapsed(Duration::from_secs(26 * 3600 + 5 * 60 + 7)),
+        "26:05:07"
+    );
+    assert_eq!(format_elapsed(Duration::from_secs(120 * 3600)), "120:00:00");
+}
+
+#[test]
+fn test_connected_timer_resets_on_reconnect() {
+    let mut app = App::default();
+    let status = |status| {
+        WarpMessage::StatusUpdate(
+            WarpInfo {
+                status,
+                ..WarpInfo::default()
+            },
+            None,
+        )
+    };
+
+    app.handle_warp_message(status(WarpStatus::Connecting));
+    assert_eq!(app.connected_for(), None);
+
+    app.handle_warp_message(status(WarpStatus::Connected));
+    std::thread::sleep(Duration::from_millis(50));
+    let first = app.connected_for().unwrap();
+    assert!(first >= Duration::from_millis(50));
+
+    // Further updates while connected keep the timer running
+    app.handle_warp_message(status(WarpStatus::Connected));
+    assert!(app.connected_for().unwrap() >= first);
+
+    app.handle_warp_message(status(WarpStatus::Disconnected));
+    assert_eq!(app.connected_for(), None);
+
+    app.handle_warp_message(status(WarpStatus::Connected));
+    assert!(app.connected_for().unwrap() < first);
+}
+
+#[test]
+fn test_network_line() {
+    let network = |name: &str, trusted, network_type| NetworkInfo {
+        name: name.to_string(),
+        trusted,
+        network_type,
+    };
+
+    assert_eq!(
+        network_line(&network("HomeWifi", true, NetworkType::Wifi)),
+        "✓ HomeWifi (Wi-Fi, trusted)"
+    );
+    assert_eq!(
+        network_line(&network("eth0", false, NetworkType::Ethernet)),
+        "• eth0 (Ethernet)"
+    );
+    assert_eq!(
+        network_line(&network("Office", true, NetworkType::Unknown)),
+        "✓ Office (trusted)"
+    );
+    assert_eq!(
+        network_line(&network("Lab", false, NetworkType::Unknown)),
+        "• Lab"
+    );
+}
+
+#[test]
+fn test_status_retry_decision() {
+    let status = |status| -> Result<(WarpInfo, Option<WarpStats>), WarpError> {
+        Ok((
+            WarpInfo {
+                status,
+                ..WarpInfo::default()
+            },
+            None,
+        ))
+    };
+
+    // A successful command that parsed as Unknown is retried once
+    assert!(should_retry_status(&status(WarpStatus::Unknown), 0));
+    assert!(!should_retry_status(
+        &status(WarpStatus::Unknown),
+        MAX_STATUS_RETRIES
+    ));
+
+    // Known statuses and failed commands aren't
+    assert!(!should_retry_status(&status(WarpStatus::Connected), 0));
+    assert!(!should_retry_status(&status(WarpStatus::Disconnected), 0));
+    let failed: Result<(WarpInfo, Option<WarpStats>), WarpError> = Err(WarpError::CommandNotFound);
+    assert!(!should_retry_status(&failed, 0));
+}
+
+#[test]
+fn test_is_compact_threshold() {
+    assert!(is_compact(Rect::new(0, 0, 80, 0)));
+    assert!(is_compact(Rect::new(0, 0, 80, COMPACT_HEIGHT - 1)));
+    assert!(!is_compact(Rect::new(0, 0, 80, COMPACT_HEIGHT)));
+    assert!(!is_compact(Rect::new(0, 0, 80, 40)));
+    // Only the height matters
+    assert!(is_compact(Rect::new(0, 0, 300, 11)));
+}
+
+#[test]
+fn test_compact_status_line() {
+    let info = WarpInfo {
+        status: WarpStatus::Connected,
+        mode: Some(WarpMode::Warp),
+        warp_enabled: true,
+        gateway_enabled: false,
+        ..WarpInfo::default()
+    };
+    assert_eq!(
+        compact_status_line(&info),
+        "Connected · Warp | WARP: on | Gateway: off"
+    );
+    assert_eq!(
+        compact_status_line(&WarpInfo::default()),
+        "Unknown | WARP: off | Gateway: off"
+    );
+}
+
+#[test]
+fn test_command_timeout() {
+    assert_eq!(adjust_command_timeout(30, 5), 35);
+    assert_eq!(adjust_command_timeout(30, -5), 25);
+    assert_eq!(adjust_command_timeout(5, -5), MIN_COMMAND_TIMEOUT_SECS);
+    assert_eq!(adjust_command_timeout(0, 0), MIN_COMMAND_TIMEOUT_SECS);
+    assert_eq!(adjust_command_timeout(120, 5), MAX_COMMAND_TIMEOUT_SECS);
+    assert_eq!(
+        adjust_command_timeout(u64::MAX, 0),
+        MAX_COMMAND_TIMEOUT_SECS
+    );
+
+    let (config, warnings) = Config::from_toml_str("command_timeout_secs = 10\n");
+    assert!(warnings.is_empty());
+    assert_eq!(config.command_timeout_secs, Some(10));
+
+    // Without one in the config, the client keeps its own timeout
+    let client = WarpClient::builder()
+        .timeout(Duration::from_secs(7))
+        .build();
+    let app = App::default()
+        .with_config(Config::default())
+        .with_client(client);
+    assert_eq!(app.command_timeout(), Duration::from_secs(7));
+
+    let mut app = App::default().with_config(config);
+    assert_eq!(app.command_timeout(), Duration::from_secs(10));
+    app.on_key_event(KeyEvent::from(KeyCode::Char(']')));
+    assert_eq!(app.command_timeout(), Duration::from_secs(15));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('[')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('[')));
+    assert_eq!(app.command_timeout(), Duration::from_secs(5));
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("Command timeout: 5s"))
+    );
+}
+
+#[test]
+fn test_shutdown_request() {
+    let shutdown = Shutdown::new();
+    assert!(!shutdown.is_requested());
+    shutdown.clone().request();
+    assert!(shutdown.is_requested());
+
+    // Quits without asking, even when connected with the confirmation enabled
+    let mut app = connected_app(true);
+    let handle = app.shutdown_handle();
+    app.check_shutdown();
+    assert!(app.is_running());
+
+    handle.request();
+    app.check_shutdown();
+    assert!(!app.is_running());
+    assert!(app.pending_action().is_none());
+}
+
+#[test]
+fn test_favorite_keys() {
+    assert_eq!(parse_function_key("F1"), Some(KeyCode::F(1)));
+    assert_eq!(parse_function_key("f12"), Some(KeyCode::F(12)));
+    assert_eq!(parse_function_key("F0"), None);
+    assert_eq!(parse_function_key("F13"), None);
+    assert_eq!(parse_function_key("x"), None);
+
+    let (config, warnings) = Config::from_toml_str(
+        "[favorites]\nF10 = \"warp\"\nF1 = \"warp+doh\"\nF2 = \"doh\"\nF13 = \"dot\"\nF3 = \"bogus\"\n",
+    );
+    assert!(warnings.is_empty());
+    let mut app = App::default().with_config(config);
+
+    // Invalid entries are skipped with a warning, the rest are sorted by key
+    assert_eq!(
+        app.favorites(),
+        &[
+            (KeyCode::F(1), FavoriteAction::SetMode(WarpMode::WarpDoH)),
+            (KeyCode::F(2), FavoriteAction::SetMode(WarpMode::DoH)),
+            (KeyCode::F(10), FavoriteAction::SetMode(WarpMode::Warp)),
+        ]
+    );
+    assert_eq!(
+        app.favorite_for(KeyCode::F(2)),
+        Some(&FavoriteAction::SetMode(WarpMode::DoH))
+    );
+    assert_eq!(
+        app.log()
+            .entries()
+            .filter(|e| e.contains("Warning: "))
+            .count(),
+        2
+    );
+
+    // A key without a favorite does nothing
+    let logged = app.log().entries().count();
+    app.on_key_event(KeyEvent::from(KeyCode::F(5)));
+    assert_eq!(app.pending_operation(), None);
+    assert_eq!(app.log().entries().count(), logged);
+}
+
+#[test]
+fn test_stale_selection_is_ignored() {
+    for key in ['m', 'f', 'p'] {
+        let mut app = App::default();
+        app.on_key_event(KeyEvent::from(KeyCode::Char(key)));
+        assert!(app.selected_index().is_some(), "key {}", key);
+        let logged = app.log().entries().count();
+
+        app.select_index(WarpMode::ALL.len() + 10);
+        app.on_key_event(KeyEvent::from(KeyCode::Enter));
+
+        assert_eq!(app.selected_index(), None, "key {}", key);
+        assert_eq!(app.pending_operation(), None, "key {}", key);
+        assert!(
+            app.log()
+                .entries()
+                .skip(logged)
+                .all(|entry| !entry.contains("warp-cli")),
+            "key {}",
+            key
+        );
+    }
+}
+
+#[test]
+fn test_exit_code_for_status() {
+    assert_eq!(status_exit_code(&WarpStatus::Connected), 0);
+    assert_eq!(status_exit_code(&WarpStatus::Disconnected), 2);
+    assert_eq!(status_exit_code(&WarpStatus::Connecting), 3);
+    assert_eq!(status_exit_code(&WarpStatus::Disconnecting), 3);
+    assert_eq!(status_exit_code(&WarpStatus::Unknown), 3);
+
+    assert_eq!(App::default().exit_code(), 3);
+    let mut app = connected_app(false);
+    assert_eq!(app.exit_code(), 0);
+    app.handle_status_result(Ok(status_info(WarpStatus::Disconnected)));
+    assert_eq!(app.exit_code(), 2);
+}
+
+#[test]
+fn test_connect_debounce() {
+    let now = Instant::now();
+    assert!(connect_command_allowed(None, now));
+    assert!(!connect_command_allowed(Some(now), now));
+    assert!(!connect_command_allowed(
+        Some(now),
+        now + CONNECT_DEBOUNCE - Duration::from_millis(1)
+    ));
+    assert!(connect_command_allowed(Some(now), now + CONNECT_DEBOUNCE));
+    // A last command in the future, e.g. after a clock adjustment, isn't allowed through
+    assert!(!connect_command_allowed(Some(now + CONNECT_DEBOUNCE), now));
+
+    let mut app = App::default();
+    app.on_key_event(KeyEvent::from(KeyCode::Char('c')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('d')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('c')));
+    let entries: Vec<_> = app.log().entries().collect();
+    assert_eq!(
+        entries
+            .iter()
+            .filter(|entry| entry.contains("warp-cli connect"))
+            .count(),
+        1
+    );
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry.contains("Disconnect ignored — command in progress"))
+    );
+    assert!(
+        entries
+            .iter()
+            .any(|entry| entry.contains("Connect ignored — command in progress"))
+    );
+}
+
+#[test]
+fn test_error_banner_clears() {
+    let reported = Instant::now();
+    assert!(error_visible(reported, reported, false));
+    assert!(error_visible(
+        reported,
+        reported + ERROR_DISPLAY_DURATION - Duration::from_millis(1),
+        false
+    ));
+    assert!(!error_visible(
+        reported,
+        reported + ERROR_DISPLAY_DURATION,
+        false
+    ));
+    assert!(error_visible(
+        reported,
+        reported + ERROR_DISPLAY_DURATION * 100,
+        true
+    ));
+
+    let mut app = App::default();
+    let before = Instant::now();
+    app.handle_status_result(Err(WarpError::command_failed("boom")));
+    let after = Instant::now();
+    let banner = app.error_banner_at(after).unwrap();
+    assert!(banner.contains("Status update failed"), "{}", banner);
+    assert!(banner.contains("boom"), "{}", banner);
+    assert_eq!(app.error_banner_at(after + ERROR_DISPLAY_DURATION), None);
+    // The error itself is kept, only the banner is gone
+    assert!(app.last_error().is_some());
+    assert!(
+        app.error_banner_at(before + ERROR_DISPLAY_DURATION / 2)
+            .is_some()
+    );
+
+    // A missing warp-cli stays on screen until it's found
+    app.handle_status_result(Err(WarpError::CommandNotFound));
+    let later = Instant::now() + ERROR_DISPLAY_DURATION * 10;
+    let banner = app.error_banner_at(later).unwrap();
+    assert!(banner.contains("warp-cli is not installed"), "{}", banner);
+
+    app.handle_status_result(Ok(status_info(WarpStatus::Connected)));
+    assert_eq!(app.error_banner_at(Instant::now()), None);
+}
+
+/// Creating a registration shows the new device ID, or the failure in the error area
+#[test]
+fn test_registration_created() {
+    let mut app = App::default();
+    app.handle_registration_check_result(Err(WarpError::NoRegistration));
+    assert!(app.is_registration_missing());
+
+    app.handle_registration_created(Err(WarpError::command_failed("daemon unreachable")));
+    assert!(app.is_registration_missing());
+    assert_eq!(app.registration_panel_device_id(), None);
+    let banner = app.error_banner_at(Instant::now()).unwrap();
+    assert!(banner.contains("Registration failed"), "{}", banner);
+    assert!(banner.contains("daemon unreachable"), "{}", banner);
+
+    app.handle_registration_created(Ok(RegistrationInfo {
+        device_id: Some("0123-abcd".to_string()),
+        account_type: Some("Free".to_string()),
+        ..Default::default()
+    }));
+    assert!(!app.is_registration_missing());
+    assert_eq!(app.registration_panel_device_id(), Some("0123-abcd"));
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.ends_with("Registered (account type: Free) as device 0123-abcd"))
+    );
+}
@@ -0,0 +1,307 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use warp_tui::app::{App, CONNECT_DEBOUNCE};
+use warp_tui::warp::client::CancelToken;
+use warp_tui::warp::error::WarpError;
+use warp_tui::warp::manager::WarpMessage;
+use warp_tui::warp::{
+    ConnectOutcome, DaemonStatus, DisconnectOutcome, DnsStats, RegistrationInfo, SettingsBatch,
+    TunnelStats, WarpBackend, WarpInfo, WarpResult, WarpSettingsInfo, WarpStats, WarpStatus,
+};
+
+/// State shared by a [`MockBackend`] and its cancellable handles
+#[derive(Debug)]
+struct MockState {
+    status: WarpStatus,
+    /// Results for the next connect and disconnect commands, succeeding once empty
+    responses: VecDeque<WarpResult<()>>,
+    /// Commands run so far, e.g. "connect"
+    calls: Vec<String>,
+    timeout: Duration,
+}
+
+/// In-memory [`WarpBackend`] answering with scripted responses
+#[derive(Debug, Clone)]
+struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockBackend {
+    fn new(status: WarpStatus) -> Self {
+        MockBackend {
+            state: Arc::new(Mutex::new(MockState {
+                status,
+                responses: VecDeque::new(),
+                calls: Vec::new(),
+                timeout: Duration::from_secs(10),
+            })),
+        }
+    }
+
+    /// Make the next connect or disconnect fail with `error`
+    fn fail_next(&self, error: WarpError) {
+        self.state.lock().unwrap().responses.push_back(Err(error));
+    }
+
+    fn set_status(&self, status: WarpStatus) {
+        self.state.lock().unwrap().status = status;
+    }
+
+    fn calls(&self) -> Vec<String> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Record `call` and take its scripted result
+    fn run(&self, call: &str) -> WarpResult<()> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(call.to_string());
+        state.responses.pop_front().unwrap_or(Ok(()))
+    }
+
+    fn unscripted<T>(&self, call: &str) -> WarpResult<T> {
+        self.state.lock().unwrap().calls.push(call.to_string());
+        Err(WarpError::command_failed(format!(
+            "{} isn't scripted",
+            call
+        )))
+    }
+}
+
+impl WarpBackend for MockBackend {
+    fn get_status_sync(&self) -> WarpResult<WarpInfo> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push("status".to_string());
+        Ok(WarpInfo {
+            status: state.status.clone(),
+            ..WarpInfo::default()
+        })
+    }
+
+    fn get_status_with_stats_sync(&self) -> WarpResult<(WarpInfo, Option<WarpStats>)> {
+        self.get_status_sync().map(|info| (info, None))
+    }
+
+    fn get_version_sync(&self) -> WarpResult<String> {
+        Ok("mock".to_string())
+    }
+
+    fn daemon_status_sync(&self) -> WarpResult<DaemonStatus> {
+        Ok(DaemonStatus::Running)
+    }
+
+    fn connect_sync(&self) -> WarpResult<ConnectOutcome> {
+        self.run("connect")?;
+        let mut state = self.state.lock().unwrap();
+        if state.status == WarpStatus::Connected {
+            return Ok(ConnectOutcome::AlreadyConnected);
+        }
+        state.status = WarpStatus::Connected;
+        Ok(ConnectOutcome::Connected)
+    }
+
+    fn disconnect_sync(&self) -> WarpResult<DisconnectOutcome> {
+        self.run("disconnect")?;
+        let mut state = self.state.lock().unwrap();
+        if state.status == WarpStatus::Disconnected {
+            return Ok(DisconnectOutcome::AlreadyDisconnected);
+        }
+        state.status = WarpStatus::Disconnected;
+        Ok(DisconnectOutcome::Disconnected)
+    }
+
+    fn disconnect_and_wait_sync(&self, _timeout: Duration) -> WarpResult<WarpStatus> {
+        self.disconnect_sync()?;
+        Ok(WarpStatus::Disconnected)
+    }
+
+    fn set_mode_sync(&self, mode: &str) -> WarpResult<()> {
+        self.run(&format!("mode {}", mode))
+    }
+
+    fn set_families_mode_sync(&self, mode: &str) -> WarpResult<()> {
+        self.unscripted(&format!("dns families {}", mode))
+    }
+
+    fn set_tunnel_protocol_sync(&self, protocol: &str) -> WarpResult<()> {
+        self.unscripted(&format!("tunnel protocol set {}", protocol))
+    }
+
+    fn set_warp_enabled_sync(&self, _enabled: bool) -> WarpResult<()> {
+        self.unscripted("warp-enabled")
+    }
+
+    fn set_gateway_enabled_sync(&self, _enabled: bool) -> WarpResult<()> {
+        self.unscripted("gateway-enabled")
+    }
+
+    // Not recorded, the app reloads the settings in the background after a command
+    fn get_settings_parsed(&self) -> WarpResult<WarpSettingsInfo> {
+        Ok(WarpSettingsInfo::default())
+    }
+
+    fn apply_settings_batch(&self, _batch: &SettingsBatch) -> WarpResult<()> {
+        self.unscripted("settings batch")
+    }
+
+    fn reset_settings_sync(&self) -> WarpResult<()> {
+        self.unscripted("settings reset")
+    }
+
+    fn set_override_sync(&self, _policy: &str) -> WarpResult<()> {
+        self.unscripted("override")
+    }
+
+    fn clear_override_sync(&self) -> WarpResult<()> {
+        self.unscripted("override clear")
+    }
+
+    // Not recorded, the app checks the registration in the background after a refresh
+    fn get_registration_sync(&self) -> WarpResult<RegistrationInfo> {
+        Ok(RegistrationInfo::default())
+    }
+
+    fn create_registration_sync(&self) -> WarpResult<RegistrationInfo> {
+        self.unscripted("registration new")
+    }
+
+    fn current_ssid_sync(&self) -> WarpResult<String> {
+        self.unscripted("current ssid")
+    }
+
+    fn list_trusted_ssids_sync(&self) -> WarpResult<Vec<String>> {
+        self.unscripted("trusted ssids")
+    }
+
+    fn add_trusted_ssid_sync(&self, _ssid: &str) -> WarpResult<()> {
+        self.unscripted("add-trusted-ssid")
+    }
+
+    fn remove_trusted_ssid_sync(&self, _ssid: &str) -> WarpResult<()> {
+        self.unscripted("del-trusted-ssid")
+    }
+
+    fn run_connectivity_check_sync(&self) -> WarpResult<String> {
+        self.unscripted("debug connectivity-check")
+    }
+
+    fn get_dns_stats_sync(&self) -> WarpResult<DnsStats> {
+        self.unscripted("dns stats")
+    }
+
+    fn get_tunnel_stats_sync(&self) -> WarpResult<TunnelStats> {
+        self.unscripted("tunnel stats")
+    }
+
+    fn timeout(&self) -> Duration {
+        self.state.lock().unwrap().timeout
+    }
+
+    fn set_timeout(&self, timeout: Duration) {
+        self.state.lock().unwrap().timeout = timeout;
+    }
+
+    fn set_capture_raw_status(&self, _capture_raw_status: bool) {}
+
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+
+    fn cancellable(&self, _token: CancelToken) -> Arc<dyn WarpBackend> {
+        Arc::new(self.clone())
+    }
+}
+
+fn app_with(backend: &MockBackend) -> App {
+    let mut app = App::default().with_backend(Arc::new(backend.clone()));
+    app.handle_status_result(backend.get_status_sync());
+    app
+}
+
+/// Wait for the background operation started by the last key to finish
+fn finish_operation(app: &mut App) {
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+    assert_eq!(app.pending_operation(), None);
+}
+
+/// Connecting and disconnecting go through the backend and refresh the status after
+#[test]
+fn test_connect_and_disconnect() {
+    let backend = MockBackend::new(WarpStatus::Disconnected);
+    let mut app = app_with(&backend);
+    assert_eq!(app.warp_info().status, WarpStatus::Disconnected);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('c')));
+    assert_eq!(app.pending_operation(), Some("Connecting…"));
+    finish_operation(&mut app);
+    assert_eq!(app.warp_info().status, WarpStatus::Connected);
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.ends_with("Connected"))
+    );
+
+    std::thread::sleep(CONNECT_DEBOUNCE);
+    app.on_key_event(KeyEvent::from(KeyCode::Char('d')));
+    assert_eq!(app.pending_operation(), Some("Disconnecting…"));
+    finish_operation(&mut app);
+    assert_eq!(app.warp_info().status, WarpStatus::Disconnected);
+
+    assert_eq!(
+        backend.calls(),
+        vec!["status", "connect", "status", "disconnect", "status"]
+    );
+}
+
+/// A failed connect is reported and leaves the status as it was
+#[test]
+fn test_connect_failure() {
+    let backend = MockBackend::new(WarpStatus::Disconnected);
+    backend.fail_next(WarpError::command_failed("daemon unreachable"));
+    let mut app = app_with(&backend);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('c')));
+    finish_operation(&mut app);
+    assert_eq!(app.warp_info().status, WarpStatus::Disconnected);
+    let error = app.error_banner_at(Instant::now()).unwrap();
+    assert!(error.contains("Connect failed"), "{}", error);
+    assert!(error.contains("daemon unreachable"), "{}", error);
+    assert_eq!(backend.calls(), vec!["status", "connect"]);
+}
+
+/// The manager still runs the real warp-cli, so its updates don't reach an app with
+/// another backend
+#[test]
+fn test_manager_updates_ignored() {
+    let backend = MockBackend::new(WarpStatus::Disconnected);
+    let mut app = app_with(&backend);
+
+    let connected = WarpInfo {
+        status: WarpStatus::Connected,
+        ..WarpInfo::default()
+    };
+    let _ = app
+        .warp_manager()
+        .get_event_sender()
+        .send(WarpMessage::StatusUpdate(connected, None));
+    app.process_warp_messages();
+    assert_eq!(app.warp_info().status, WarpStatus::Disconnected);
+}
+
+/// Refreshing picks up a status change made outside the TUI
+#[test]
+fn test_refresh() {
+    let backend = MockBackend::new(WarpStatus::Disconnected);
+    let mut app = app_with(&backend);
+
+    backend.set_status(WarpStatus::Connected);
+    assert_eq!(app.warp_info().status, WarpStatus::Disconnected);
+    app.on_key_event(KeyEvent::from(KeyCode::Char('r')));
+    assert_eq!(app.warp_info().status, WarpStatus::Connected);
+    assert_eq!(backend.calls(), vec!["status", "status"]);
+}
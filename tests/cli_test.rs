@@ -0,0 +1,77 @@
+use warp_tui::cli::Cli;
+use warp_tui::warp::WarpMode;
+
+#[test]
+fn test_no_arguments_starts_tui() {
+    let cli = Cli::parse(Vec::<String>::new()).unwrap();
+    assert_eq!(cli, Cli::default());
+    assert!(!cli.oneshot);
+}
+
+#[test]
+fn test_oneshot_flags() {
+    assert!(Cli::parse(["--status"]).unwrap().oneshot);
+    assert!(Cli::parse(["--oneshot"]).unwrap().oneshot);
+
+    let cli = Cli::parse(["--status"]).unwrap();
+    assert!(!cli.json);
+}
+
+#[test]
+fn test_json_implies_oneshot() {
+    let cli = Cli::parse(["--json"]).unwrap();
+    assert!(cli.oneshot);
+    assert!(cli.json);
+
+    let cli = Cli::parse(["--oneshot", "--json"]).unwrap();
+    assert!(cli.oneshot);
+    assert!(cli.json);
+}
+
+#[test]
+fn test_unknown_argument() {
+    let err = Cli::parse(["--bogus"]).unwrap_err();
+    assert!(err.contains("--bogus"));
+}
+
+#[test]
+fn test_help() {
+    assert!(Cli::parse(["-h"]).unwrap().help);
+    assert!(Cli::parse(["--help"]).unwrap().help);
+}
+
+#[test]
+fn test_set_mode() {
+    let cli = Cli::parse(["--set-mode", "warp+doh"]).unwrap();
+    assert_eq!(cli.set_mode, Some(WarpMode::WarpDoH));
+    assert!(!cli.oneshot);
+
+    assert_eq!(
+        Cli::parse(["--mode", "TunnelOnly"]).unwrap().set_mode,
+        Some(WarpMode::TunnelOnly)
+    );
+
+    let err = Cli::parse(["--set-mode", "warp+dho"]).unwrap_err();
+    assert!(err.contains("unknown mode: warp+dho"), "{}", err);
+    assert!(err.contains("warp+doh"), "{}", err);
+
+    let err = Cli::parse(["--set-mode"]).unwrap_err();
+    assert!(err.contains("--set-mode requires a mode"), "{}", err);
+}
+
+#[test]
+fn test_dry_run() {
+    assert!(!Cli::parse(Vec::<String>::new()).unwrap().dry_run);
+
+    let cli = Cli::parse(["--dry-run", "--set-mode", "doh"]).unwrap();
+    assert!(cli.dry_run);
+    assert_eq!(cli.set_mode, Some(WarpMode::DoH));
+}
+
+#[test]
+fn test_exit_code() {
+    assert!(!Cli::parse(Vec::<String>::new()).unwrap().exit_code);
+    let cli = Cli::parse(["--exit-code"]).unwrap();
+    assert!(cli.exit_code);
+    assert!(!cli.oneshot);
+}
@@ -0,0 +1,28 @@
+// Tests that change the process environment, kept in their own binary so they
+// can't affect clients created by other tests.
+
+#[cfg(unix)]
+#[test]
+fn test_warp_cli_path_env_var() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+    use warp_tui::warp::client::{BINARY_PATH_ENV, WarpClient};
+
+    let dir = std::env::temp_dir().join(format!("warp-tui-env-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let stub = dir.join("warp-cli");
+    std::fs::write(&stub, "#!/bin/sh\necho \"warp-cli 2099.1.2\"\n").unwrap();
+    std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    // SAFETY: this is the only test in this binary, so no other thread reads the environment
+    unsafe { std::env::set_var(BINARY_PATH_ENV, &stub) };
+    let client = WarpClient::new();
+    unsafe { std::env::remove_var(BINARY_PATH_ENV) };
+
+    assert_eq!(client.binary_path(), stub.as_path());
+    assert_eq!(client.get_version_sync().unwrap(), "2099.1.2");
+
+    assert_eq!(WarpClient::new().binary_path(), Path::new("warp-cli"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -8,3 +8,47 @@ pub fn is_warp_cli_available() -> bool {
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
+
+/// A client running a private copy of `tests/mock_warp_cli.sh`, starting disconnected
+/// in `warp+doh` mode
+#[cfg(unix)]
+pub fn mock_warp_client(name: &str) -> warp_tui::warp::WarpClient {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("warp-tui-mock-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let binary = dir.join("warp-cli");
+    std::fs::copy(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/mock_warp_cli.sh"),
+        &binary,
+    )
+    .unwrap();
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    warp_tui::warp::WarpClient::builder()
+        .binary_path(binary)
+        .build()
+}
+
+/// Wait for the settings fetched in the background to arrive
+pub fn wait_for_settings(app: &mut warp_tui::app::App) {
+    let started = std::time::Instant::now();
+    while app.is_settings_loading() && started.elapsed() < std::time::Duration::from_secs(10) {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        app.poll_settings_check();
+    }
+    assert!(!app.is_settings_loading());
+}
+
+/// Commands the mock warp-cli behind `client` has run so far, one per line
+#[cfg(unix)]
+pub fn mock_calls(client: &warp_tui::warp::WarpClient) -> Vec<String> {
+    let calls = client.binary_path().parent().unwrap().join("calls");
+    std::fs::read_to_string(calls)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
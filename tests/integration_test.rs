@@ -1,5 +1,14 @@
 use crate::helpers::is_warp_cli_available;
-use warp_tui::warp::{WarpClient, WarpStatus};
+use std::time::{Duration, Instant};
+use warp_tui::app::App;
+use warp_tui::app::config::{Config, Profile};
+use warp_tui::app::tab::Tab;
+use warp_tui::warp::error::WarpError;
+use warp_tui::warp::manager::{REFRESH_COALESCE_WINDOW, WarpManager, WarpMessage};
+use warp_tui::warp::{
+    ConnectOutcome, DaemonStatus, DisconnectOutcome, FamiliesMode, NetworkType, SettingsBatch,
+    TunnelProtocol, WarpClient, WarpMode, WarpStatus,
+};
 
 mod helpers;
 
@@ -61,71 +70,17 @@ fn test_connect_disconnect_flow() {
         }
     }
 
-    // Test disconnect functionality
-    match client.disconnect_sync() {
-        Ok(_) => {
-            println!("Disconnect command executed successfully");
-
-            // Wait longer for disconnection to complete and retry if needed
-            let mut attempts = 0;
-            let max_attempts = 5;
-            let mut final_status = None;
-
-            while attempts < max_attempts {
-                std::thread::sleep(std::time::Duration::from_secs(2));
-
-                let status = client
-                    .get_status_sync()
-                    .expect("Should be able to get status after disconnect");
-
-                println!(
-                    "Status after disconnect (attempt {}): {:?}",
-                    attempts + 1,
-                    status.status
-                );
-
-                if matches!(
-                    status.status,
-                    WarpStatus::Disconnected | WarpStatus::Disconnecting
-                ) {
-                    final_status = Some(status);
-                    break;
-                }
-
-                attempts += 1;
-            }
+    // Test disconnect functionality, waiting for the tunnel to be torn down
+    match client.disconnect_and_wait_sync(std::time::Duration::from_secs(10)) {
+        Ok(status) => {
+            println!("Status after disconnect: {:?}", status);
 
-            // Verify final status
-            if let Some(status) = final_status {
-                // Status should be Disconnected or Disconnecting
-                assert!(
-                    matches!(
-                        status.status,
-                        WarpStatus::Disconnected | WarpStatus::Disconnecting
-                    ),
-                    "After disconnect, status should be Disconnected or Disconnecting, but was {:?}",
-                    status.status
-                );
-            } else {
-                // If we couldn't get the expected status after multiple attempts,
-                // get the final status for reporting
-                let post_disconnect_status = client
-                    .get_status_sync()
-                    .expect("Should be able to get status after disconnect");
-
-                println!(
-                    "Warning: Status after disconnect attempts: {:?}",
-                    post_disconnect_status.status
-                );
-
-                // More lenient assertion - allow any status except Connected
-                // since disconnect was called but might be in transition
-                assert!(
-                    !matches!(post_disconnect_status.status, WarpStatus::Connected),
-                    "After disconnect command, status should not be Connected, but was {:?}",
-                    post_disconnect_status.status
-                );
-            }
+            // The tunnel may still be in transition if the timeout elapsed
+            assert!(
+                matches!(status, WarpStatus::Disconnected | WarpStatus::Disconnecting),
+                "After disconnect, status should be Disconnected or Disconnecting, but was {:?}",
+                status
+            );
         }
         Err(e) => {
             println!("Disconnect command failed: {:?}", e);
@@ -240,3 +195,1326 @@ fn test_operation_mode() {
         .expect("Failed to get operation mode");
     println!("Current operation mode: {}", mode);
 }
+
+/// Drive connect → status → disconnect against the mock warp-cli, so the client is
+/// exercised without a WARP installation
+#[cfg(unix)]
+#[test]
+fn test_mock_connect_disconnect_flow() {
+    let client = helpers::mock_warp_client("flow");
+
+    let info = client.get_status_sync().unwrap();
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(info.reason.as_deref(), Some("Manual Disconnection"));
+    assert_eq!(info.mode, Some(WarpMode::WarpDoH));
+
+    assert_eq!(client.connect_sync().unwrap(), ConnectOutcome::Connected);
+    let info = client.get_status_sync().unwrap();
+    assert_eq!(info.status, WarpStatus::Connected);
+    assert_eq!(info.reason, None);
+    assert_eq!(
+        client.connect_sync().unwrap(),
+        ConnectOutcome::AlreadyConnected
+    );
+
+    assert_eq!(
+        client.disconnect_sync().unwrap(),
+        DisconnectOutcome::Disconnected
+    );
+    let info = client.get_status_sync().unwrap();
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(
+        client.disconnect_sync().unwrap(),
+        DisconnectOutcome::AlreadyDisconnected
+    );
+}
+
+/// The JSON status reports the connected networks like the plain one
+#[cfg(unix)]
+#[test]
+fn test_mock_json_status_networks() {
+    let client = helpers::mock_warp_client("json-networks");
+    std::fs::write(
+        client.binary_path().with_file_name("network"),
+        "SSID: HomeWifi\n",
+    )
+    .unwrap();
+
+    let info = client.get_status_sync().unwrap();
+    assert_eq!(info.connected_networks, ["HomeWifi"]);
+    assert_eq!(info.networks.len(), 1);
+    assert_eq!(info.networks[0].network_type, NetworkType::Wifi);
+    assert!(
+        helpers::mock_calls(&client)
+            .iter()
+            .any(|call| call == "--json status")
+    );
+}
+
+/// The plain text status output parses to the same transitions as the JSON one
+#[cfg(unix)]
+#[test]
+fn test_mock_text_status_and_mode() {
+    let client = helpers::mock_warp_client("text");
+    let text_client = WarpClient::builder()
+        .binary_path(client.binary_path())
+        .prefer_json(false)
+        .build();
+
+    assert_eq!(
+        text_client.get_status_sync().unwrap().status,
+        WarpStatus::Disconnected
+    );
+    client.connect_sync().unwrap();
+    assert_eq!(
+        text_client.get_status_sync().unwrap().status,
+        WarpStatus::Connected
+    );
+
+    client.set_mode_sync("dot").unwrap();
+    assert_eq!(
+        text_client.get_status_sync().unwrap().mode,
+        Some(WarpMode::DoT)
+    );
+    assert_eq!(client.get_version_sync().unwrap(), "2024.6.497");
+}
+
+/// Switch the tunnel protocol on the mock warp-cli and read it back from the settings
+#[cfg(unix)]
+#[test]
+fn test_mock_tunnel_protocol() {
+    let client = helpers::mock_warp_client("protocol");
+
+    assert_eq!(
+        client.get_settings_parsed().unwrap().tunnel_protocol,
+        Some(TunnelProtocol::WireGuard)
+    );
+
+    client.set_tunnel_protocol_sync("masque").unwrap();
+    assert_eq!(
+        client.get_settings_parsed().unwrap().tunnel_protocol,
+        Some(TunnelProtocol::Masque)
+    );
+}
+
+/// The protocol picker preselects the protocol from the settings fetched in the background
+#[cfg(unix)]
+#[test]
+fn test_mock_protocol_picker_uses_cached_settings() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("protocol-picker");
+    let mut app = App::default().with_client(client.clone());
+    let protocol = |app: &App| app.settings().and_then(|s| s.ok()?.tunnel_protocol);
+    let wait_for = |app: &mut App, expected: TunnelProtocol| {
+        let started = Instant::now();
+        while protocol(app) != Some(expected) && started.elapsed() < Duration::from_secs(10) {
+            std::thread::sleep(Duration::from_millis(10));
+            app.poll_pending_operation();
+            app.poll_settings_check();
+        }
+        assert_eq!(protocol(app), Some(expected));
+    };
+
+    app.start_settings_check();
+    wait_for(&mut app, TunnelProtocol::WireGuard);
+    let calls_before = helpers::mock_calls(&client).len();
+    app.on_key_event(KeyEvent::from(KeyCode::Char('p')));
+    assert_eq!(app.selected_index(), Some(0));
+    assert_eq!(helpers::mock_calls(&client).len(), calls_before);
+
+    // The settings are reloaded once the new protocol is applied
+    app.on_key_event(KeyEvent::from(KeyCode::Char('j')));
+    app.on_key_event(KeyEvent::from(KeyCode::Enter));
+    wait_for(&mut app, TunnelProtocol::Masque);
+    let calls_before = helpers::mock_calls(&client).len();
+    app.on_key_event(KeyEvent::from(KeyCode::Char('p')));
+    assert_eq!(app.selected_index(), Some(1));
+    assert_eq!(helpers::mock_calls(&client).len(), calls_before);
+}
+
+/// Apply and clear an admin override on the mock warp-cli, reading it back from the status
+#[cfg(unix)]
+#[test]
+fn test_mock_override() {
+    let client = helpers::mock_warp_client("override");
+    assert_eq!(client.get_status_sync().unwrap().override_state, None);
+
+    client.set_override_sync("local-network").unwrap();
+    assert_eq!(
+        client.get_status_sync().unwrap().override_state.as_deref(),
+        Some("local-network")
+    );
+
+    client.clear_override_sync().unwrap();
+    assert_eq!(client.get_status_sync().unwrap().override_state, None);
+}
+
+/// The settings tab shows the settings of the mock warp-cli and reloads them with 'r'
+#[cfg(unix)]
+#[test]
+fn test_mock_settings_tab() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("settings-tab");
+    let mut app = App::default().with_client(client.clone());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('2')));
+    assert_eq!(app.active_tab(), Tab::Settings);
+    assert!(app.is_settings_loading());
+    helpers::wait_for_settings(&mut app);
+    assert_eq!(app.settings().unwrap().unwrap().mode, WarpMode::WarpDoH);
+
+    client.set_mode_sync("dot").unwrap();
+    app.on_key_event(KeyEvent::from(KeyCode::Char('r')));
+    helpers::wait_for_settings(&mut app);
+    assert_eq!(app.settings().unwrap().unwrap().mode, WarpMode::DoT);
+}
+
+/// Without `--json` support the status and mode come from the plain output
+#[cfg(unix)]
+#[test]
+fn test_mock_without_json_support() {
+    let client = helpers::mock_warp_client("no-json");
+    client.set_mode_sync("dot").unwrap();
+
+    // Wrap the mock so every `--json` command is rejected like old warp-cli versions do
+    let wrapper = client.binary_path().with_file_name("warp-cli-no-json");
+    std::fs::write(
+        &wrapper,
+        format!(
+            "#!/bin/sh\n[ \"$1\" = --json ] && {{ echo \"error: unexpected argument '--json' found\" >&2; exit 2; }}\nexec {} \"$@\"\n",
+            client.binary_path().display()
+        ),
+    )
+    .unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let client = WarpClient::builder().binary_path(wrapper).build();
+
+    assert_eq!(client.get_operation_mode().unwrap(), WarpMode::DoT);
+    let info = client.get_status_sync().unwrap();
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(info.mode, Some(WarpMode::DoT));
+}
+
+/// A failing command's stderr and exit code come back to the caller instead of an error
+#[cfg(unix)]
+#[test]
+fn test_mock_raw_output_of_failing_command() {
+    let client = helpers::mock_warp_client("raw");
+
+    let output = client.run_raw_sync(&["tunnel", "rotate-keys"]).unwrap();
+    assert!(!output.success());
+    assert_eq!(output.exit_code, Some(2));
+    assert_eq!(output.stdout, "");
+    assert!(
+        output
+            .stderr
+            .contains("unrecognized subcommand 'rotate-keys'")
+    );
+
+    let output = client.run_raw_sync(&["connect"]).unwrap();
+    assert!(output.success());
+    assert_eq!(output.stdout.trim(), "Success");
+    assert_eq!(output.stderr, "");
+}
+
+/// Read the DNS statistics from the mock warp-cli in both output formats
+#[cfg(unix)]
+#[test]
+fn test_mock_dns_stats() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("dns-stats");
+    let text_client = WarpClient::builder()
+        .binary_path(client.binary_path())
+        .prefer_json(false)
+        .build();
+
+    for client in [client.clone(), text_client] {
+        let stats = client.get_dns_stats_sync().unwrap();
+        assert_eq!(stats.total_queries, 120);
+        assert_eq!(stats.cached, 90);
+        assert_eq!(stats.blocked, 3);
+        assert_eq!(stats.latency, Some(4.2));
+    }
+
+    // The panel fetches them in the background
+    let mut app = App::default().with_client(client);
+    app.on_key_event(KeyEvent::from(KeyCode::Char('s')));
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+    assert_eq!(app.dns_stats().unwrap().unwrap().total_queries, 120);
+}
+
+/// Read the tunnel counters from the mock warp-cli, which only has them while connected
+#[cfg(unix)]
+#[test]
+fn test_mock_tunnel_stats() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("tunnel-stats");
+    assert!(matches!(
+        client.get_tunnel_stats_sync(),
+        Err(WarpError::NotConnected)
+    ));
+
+    client.connect_sync().unwrap();
+    let stats = client.get_tunnel_stats_sync().unwrap();
+    assert_eq!(stats.rx_bytes, 8192);
+    assert_eq!(stats.tx_packets, 32);
+    let since = stats.since_last_handshake.unwrap();
+    assert!(since >= Duration::from_secs(30) && since < Duration::from_secs(40));
+
+    // The panel fetches them in the background
+    let mut app = App::default().with_client(client);
+    app.on_key_event(KeyEvent::from(KeyCode::Char('h')));
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+    assert_eq!(app.tunnel_stats().unwrap().unwrap().rx_bytes, 8192);
+}
+
+/// Failed commands carry the exit code of warp-cli
+#[cfg(unix)]
+#[test]
+fn test_mock_command_failed_exit_code() {
+    let client = helpers::mock_warp_client("exit-code");
+
+    match client.set_families_mode_sync("off") {
+        Err(WarpError::CommandFailed { code, stderr }) => {
+            assert_eq!(code, Some(2));
+            assert!(stderr.contains("unrecognized subcommand 'families'"));
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
+}
+
+/// Alternative mode spellings reach warp-cli in their canonical form
+#[cfg(unix)]
+#[test]
+fn test_mock_set_mode_normalizes_mode() {
+    let client = helpers::mock_warp_client("set-mode");
+
+    client.set_mode_sync("TunnelOnly").unwrap();
+    client.set_mode_sync(" Warp+DoT ").unwrap();
+    assert!(client.set_mode_sync("warp+dho").is_err());
+
+    assert_eq!(
+        helpers::mock_calls(&client),
+        vec!["mode tunnel_only", "mode warp+dot"]
+    );
+}
+
+/// Applying a profile runs its settings as one batch in the background, undoing them
+/// all if one fails
+#[cfg(unix)]
+#[test]
+fn test_mock_apply_profile() {
+    let client = helpers::mock_warp_client("profile");
+    let mut app = App::default().with_client(client.clone());
+    let finish = |app: &mut App| {
+        let started = Instant::now();
+        while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+            std::thread::sleep(Duration::from_millis(10));
+            app.poll_pending_operation();
+        }
+        assert_eq!(app.pending_operation(), None);
+    };
+
+    app.apply_profile(&Profile {
+        name: "Home".to_string(),
+        mode: Some("dot".to_string()),
+        families_mode: None,
+    });
+    assert_eq!(app.pending_operation(), Some("Applying profile…"));
+    finish(&mut app);
+    assert_eq!(app.warp_info().mode, Some(WarpMode::DoT));
+    assert_eq!(app.active_profile(), Some("Home"));
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.ends_with("Profile Home applied"))
+    );
+
+    // The mock warp-cli doesn't know `dns families`, so the mode is switched back
+    let calls_before = helpers::mock_calls(&client).len();
+    app.apply_profile(&Profile {
+        name: "Work".to_string(),
+        mode: Some("warp+doh".to_string()),
+        families_mode: Some("malware".to_string()),
+    });
+    finish(&mut app);
+    let changes: Vec<String> = helpers::mock_calls(&client)
+        .into_iter()
+        .skip(calls_before)
+        .filter(|call| call.starts_with("mode ") || call.starts_with("dns families "))
+        .collect();
+    assert_eq!(
+        changes,
+        ["mode warp+doh", "dns families malware", "mode dot"]
+    );
+    assert_eq!(client.get_operation_mode().unwrap(), WarpMode::DoT);
+
+    let error = app
+        .log()
+        .entries()
+        .find(|entry| entry.contains("Error: Apply profile failed"))
+        .unwrap();
+    assert!(error.contains("set families mode malware"), "{}", error);
+    assert!(
+        error.contains("the previous settings were restored"),
+        "{}",
+        error
+    );
+
+    // Values warp-cli wouldn't accept are reported without running anything
+    let calls_before = helpers::mock_calls(&client).len();
+    app.apply_profile(&Profile {
+        name: "Broken".to_string(),
+        mode: None,
+        families_mode: Some("everything".to_string()),
+    });
+    assert_eq!(app.pending_operation(), None);
+    assert_eq!(helpers::mock_calls(&client).len(), calls_before);
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("Profile Broken: unknown DNS families mode"))
+    );
+}
+
+/// A failing step of a settings batch undoes the steps before it; the mock warp-cli
+/// doesn't know `dns families`, so that step always fails
+#[test]
+fn test_mock_settings_batch_rollback() {
+    let client = helpers::mock_warp_client("settings-batch");
+    let batch = SettingsBatch {
+        mode: Some(WarpMode::DoT),
+        families_mode: Some(FamiliesMode::Malware),
+        tunnel_protocol: Some(TunnelProtocol::Masque),
+    };
+
+    match client.apply_settings_batch(&batch) {
+        Err(WarpError::BatchFailed {
+            step, rolled_back, ..
+        }) => {
+            assert_eq!(step, "set families mode malware");
+            assert!(rolled_back);
+        }
+        result => panic!("expected a failed batch, got {:?}", result),
+    }
+    assert_eq!(client.get_operation_mode().unwrap(), WarpMode::WarpDoH);
+    assert_eq!(
+        helpers::mock_calls(&client),
+        [
+            "--json settings",
+            "mode dot",
+            "dns families malware",
+            "mode warp+doh",
+            "--json settings",
+        ]
+    );
+    // Steps after the failing one never run
+    assert_eq!(
+        client.get_settings_parsed().unwrap().tunnel_protocol,
+        Some(TunnelProtocol::WireGuard)
+    );
+
+    // Without a known previous mode there's nothing to restore
+    std::fs::write(client.binary_path().with_file_name("mode"), "bogus").unwrap();
+    let error = client.apply_settings_batch(&batch).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            WarpError::BatchFailed {
+                rolled_back: false,
+                ..
+            }
+        ),
+        "{:?}",
+        error
+    );
+    assert!(
+        error
+            .to_string()
+            .contains("restoring the previous settings failed"),
+        "{}",
+        error
+    );
+
+    let batch = SettingsBatch {
+        mode: Some(WarpMode::Warp),
+        tunnel_protocol: Some(TunnelProtocol::Masque),
+        ..SettingsBatch::default()
+    };
+    client.apply_settings_batch(&batch).unwrap();
+    let settings = client.get_settings_parsed().unwrap();
+    assert_eq!(settings.mode, WarpMode::Warp);
+    assert_eq!(settings.tunnel_protocol, Some(TunnelProtocol::Masque));
+}
+
+/// The unparsed status output is only kept once enabled, and the raw status panel enables it
+#[test]
+fn test_mock_raw_status() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("raw-status");
+    assert!(!client.captures_raw_status());
+    assert_eq!(client.get_status_sync().unwrap().raw, None);
+
+    client.set_capture_raw_status(true);
+    let raw = client.get_status_sync().unwrap().raw.unwrap();
+    assert!(raw.contains(r#""status":"Disconnected""#), "{}", raw);
+
+    let plain = WarpClient::builder()
+        .binary_path(client.binary_path())
+        .prefer_json(false)
+        .capture_raw_status(true)
+        .build();
+    let raw = plain.get_status_sync().unwrap().raw.unwrap();
+    assert!(raw.contains("Status update: Disconnected"), "{}", raw);
+
+    client.set_capture_raw_status(false);
+    let mut app = App::default().with_client(client.clone());
+    app.on_key_event(KeyEvent::from(KeyCode::Char('l')));
+    assert!(app.is_raw_status_shown());
+    assert!(client.captures_raw_status());
+    assert!(app.warp_info().raw.is_some());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Esc));
+    assert!(!app.is_raw_status_shown());
+    assert!(!client.captures_raw_status());
+}
+
+/// Disconnecting when quitting is reflected in the exit code
+#[test]
+fn test_mock_exit_code_after_disconnect_on_quit() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("exit-code");
+    client.connect_sync().unwrap();
+    let mut app = App::default()
+        .with_config(Config {
+            confirm_quit_when_connected: true,
+            ..Config::default()
+        })
+        .with_client(client.clone());
+    app.handle_status_result(client.get_status_sync());
+    assert_eq!(app.exit_code(), 0);
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('q')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('d')));
+    assert!(!app.is_running());
+    assert_eq!(app.exit_code(), 2);
+}
+
+/// The Wi-Fi network the device is on can be trusted and untrusted, wired ones can't
+#[test]
+fn test_mock_trust_current_network() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("trust-network");
+    let network = client.binary_path().with_file_name("network");
+    std::fs::write(&network, "SSID: HomeWifi\n").unwrap();
+
+    assert_eq!(client.current_ssid_sync().unwrap(), "HomeWifi");
+    assert_eq!(client.trust_current_network_sync().unwrap(), "HomeWifi");
+    assert_eq!(client.list_trusted_ssids_sync().unwrap(), ["HomeWifi"]);
+    assert_eq!(client.untrust_current_network_sync().unwrap(), "HomeWifi");
+    assert!(client.list_trusted_ssids_sync().unwrap().is_empty());
+
+    // The key toggles trust for the current network
+    let mut app = App::default().with_client(client.clone());
+    let press_and_wait = |app: &mut App| {
+        app.on_key_event(KeyEvent::from(KeyCode::Char('v')));
+        let started = Instant::now();
+        while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+            std::thread::sleep(Duration::from_millis(10));
+            app.poll_pending_operation();
+        }
+    };
+    press_and_wait(&mut app);
+    assert_eq!(client.list_trusted_ssids_sync().unwrap(), ["HomeWifi"]);
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("Trusted HomeWifi"))
+    );
+    press_and_wait(&mut app);
+    assert!(client.list_trusted_ssids_sync().unwrap().is_empty());
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("HomeWifi is no longer trusted"))
+    );
+
+    std::fs::write(&network, "Network: enp3s0\n").unwrap();
+    let error = client.trust_current_network_sync().unwrap_err();
+    assert!(
+        error.to_string().contains("wired network enp3s0"),
+        "{}",
+        error
+    );
+    assert!(
+        !helpers::mock_calls(&client)
+            .iter()
+            .any(|call| call.contains("enp3s0"))
+    );
+}
+
+/// A favorite key switches straight to its mode without opening the mode picker
+#[test]
+fn test_mock_favorite_sets_mode() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("favorite");
+    let (config, _) = Config::from_toml_str("[favorites]\nF2 = \"doh\"\n");
+    let mut app = App::default()
+        .with_config(config)
+        .with_client(client.clone());
+
+    app.on_key_event(KeyEvent::from(KeyCode::F(2)));
+    assert_eq!(app.pending_operation(), Some("Applying mode…"));
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+
+    assert_eq!(client.get_operation_mode().unwrap(), WarpMode::DoH);
+    assert!(helpers::mock_calls(&client).contains(&"mode doh".to_string()));
+}
+
+/// A command outliving a short timeout is killed and reported with the timeout
+#[cfg(unix)]
+#[test]
+fn test_mock_command_timeout() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let client = helpers::mock_warp_client("command-timeout");
+    let path = client.binary_path().with_file_name("warp-cli-sleeping");
+    std::fs::write(&path, "#!/bin/sh\nexec sleep 5\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let client = WarpClient::builder()
+        .binary_path(path)
+        .prefer_json(false)
+        .timeout(Duration::from_secs(30))
+        .build();
+
+    // Clones share the timeout, so lowering it applies to the original as well
+    client.clone().set_timeout(Duration::from_secs(1));
+    assert_eq!(client.timeout(), Duration::from_secs(1));
+
+    let started = Instant::now();
+    let error = client.get_status_sync().unwrap_err();
+    assert!(started.elapsed() < Duration::from_secs(3));
+    match error {
+        WarpError::Timeout(message) => {
+            assert_eq!(message, "warp-cli status timed out after 1s")
+        }
+        e => panic!("expected a timeout, got {:?}", e),
+    }
+}
+
+/// Every status seen while connecting and disconnecting follows from the previous one
+#[cfg(unix)]
+#[test]
+fn test_mock_status_transitions_are_legal() {
+    let client = helpers::mock_warp_client("transitions");
+    std::fs::write(client.binary_path().with_file_name("connect_polls"), "2").unwrap();
+    std::fs::write(client.binary_path().with_file_name("disconnect_polls"), "2").unwrap();
+
+    let mut seen = vec![client.get_status_sync().unwrap().status];
+    let mut observe_until = |target: WarpStatus| {
+        for _ in 0..10 {
+            let status = client.get_status_sync().unwrap().status;
+            seen.push(status.clone());
+            if status == target {
+                return;
+            }
+        }
+        panic!("never reached {}: {:?}", target, seen);
+    };
+
+    client.connect_sync().unwrap();
+    observe_until(WarpStatus::Connected);
+    client.disconnect_sync().unwrap();
+    observe_until(WarpStatus::Disconnected);
+
+    assert!(seen.contains(&WarpStatus::Connecting), "{:?}", seen);
+    assert!(seen.contains(&WarpStatus::Disconnecting), "{:?}", seen);
+    for pair in seen.windows(2) {
+        assert!(
+            pair[0].can_transition_to(&pair[1]),
+            "illegal transition {} -> {} in {:?}",
+            pair[0],
+            pair[1],
+            seen
+        );
+    }
+}
+
+/// Disconnecting polls the status until the tunnel is down
+#[cfg(unix)]
+#[test]
+fn test_mock_disconnect_and_wait() {
+    let client = helpers::mock_warp_client("disconnect-wait");
+    let polls_file = client.binary_path().with_file_name("disconnect_polls");
+    let status_calls = || {
+        helpers::mock_calls(&client)
+            .iter()
+            .filter(|call| call.ends_with("status"))
+            .count()
+    };
+
+    // Three checks see Disconnecting, the fourth Disconnected
+    client.connect_sync().unwrap();
+    std::fs::write(&polls_file, "3").unwrap();
+    client.disconnect_sync().unwrap();
+    let status = client
+        .wait_for_status_sync(
+            &WarpStatus::Disconnected,
+            Duration::from_secs(5),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+    assert_eq!(status, WarpStatus::Disconnected);
+    assert_eq!(status_calls(), 4);
+
+    // The last status is returned once the timeout elapses
+    client.connect_sync().unwrap();
+    std::fs::write(&polls_file, "1000").unwrap();
+    client.disconnect_sync().unwrap();
+    let started = Instant::now();
+    let status = client
+        .wait_for_status_sync(
+            &WarpStatus::Disconnected,
+            Duration::from_millis(100),
+            Duration::from_millis(10),
+        )
+        .unwrap();
+    assert_eq!(status, WarpStatus::Disconnecting);
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // The combined call disconnects and waits
+    std::fs::write(&polls_file, "1").unwrap();
+    client.connect_sync().unwrap();
+    assert_eq!(
+        client
+            .disconnect_and_wait_sync(Duration::from_secs(5))
+            .unwrap(),
+        WarpStatus::Disconnected
+    );
+}
+
+/// Only the built client disconnects on drop, its clones don't
+#[cfg(unix)]
+#[test]
+fn test_mock_disconnect_on_drop() {
+    let mock = helpers::mock_warp_client("disconnect-on-drop");
+    let disconnects = || {
+        helpers::mock_calls(&mock)
+            .iter()
+            .filter(|call| call.ends_with("disconnect"))
+            .count()
+    };
+
+    let client = WarpClient::builder()
+        .binary_path(mock.binary_path())
+        .disconnect_on_drop(true)
+        .build();
+    client.connect_sync().unwrap();
+
+    drop(client.clone());
+    assert_eq!(disconnects(), 0);
+    assert_eq!(
+        mock.get_status_sync().unwrap().status,
+        WarpStatus::Connected
+    );
+
+    drop(client);
+    assert_eq!(disconnects(), 1);
+    assert_eq!(
+        mock.get_status_sync().unwrap().status,
+        WarpStatus::Disconnected
+    );
+
+    // Off by default
+    let client = WarpClient::builder()
+        .binary_path(mock.binary_path())
+        .build();
+    client.connect_sync().unwrap();
+    drop(client);
+    assert_eq!(disconnects(), 1);
+}
+
+/// The health check tells a running daemon from a hanging or missing one
+#[cfg(unix)]
+#[test]
+fn test_mock_daemon_status() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let client = helpers::mock_warp_client("daemon-status");
+    assert_eq!(client.daemon_status_sync().unwrap(), DaemonStatus::Running);
+
+    let script = |name: &str, body: &str| {
+        let path = client.binary_path().with_file_name(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        WarpClient::builder()
+            .binary_path(path)
+            .timeout(Duration::from_millis(300))
+            .build()
+    };
+
+    let hanging = script("warp-cli-hanging", "sleep 5");
+    let started = Instant::now();
+    assert_eq!(
+        hanging.daemon_status_sync().unwrap(),
+        DaemonStatus::Unresponsive
+    );
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    let down = script(
+        "warp-cli-daemon-down",
+        "echo 'Error: Unable to connect to the CloudflareWARP daemon. Maybe the daemon is not running?'; exit 1",
+    );
+    assert_eq!(down.daemon_status_sync().unwrap(), DaemonStatus::NotFound);
+
+    // The daemon answered, it just had nothing good to say
+    let unregistered = script(
+        "warp-cli-unregistered",
+        "echo 'Error: Missing registration' >&2; exit 1",
+    );
+    assert_eq!(
+        unregistered.daemon_status_sync().unwrap(),
+        DaemonStatus::Running
+    );
+
+    let missing = WarpClient::builder()
+        .binary_path(client.binary_path().with_file_name("no-such-warp-cli"))
+        .build();
+    assert_eq!(
+        missing.daemon_status_sync().unwrap(),
+        DaemonStatus::NotFound
+    );
+}
+
+/// In dry-run mode mutating commands never reach warp-cli, the status still does
+#[cfg(unix)]
+#[test]
+fn test_mock_dry_run() {
+    let mock = helpers::mock_warp_client("dry-run");
+    let client = WarpClient::builder()
+        .binary_path(mock.binary_path())
+        .dry_run(true)
+        .build();
+    assert!(client.is_dry_run());
+    assert!(client.clone().is_dry_run());
+
+    assert_eq!(client.connect_sync().unwrap(), ConnectOutcome::Connected);
+    client.set_mode_sync("dot").unwrap();
+    client.set_tunnel_protocol_sync("masque").unwrap();
+    client.set_override_sync("allow-all").unwrap();
+    client.clear_override_sync().unwrap();
+    assert_eq!(
+        client.disconnect_sync().unwrap(),
+        DisconnectOutcome::Disconnected
+    );
+    assert!(helpers::mock_calls(&mock).is_empty());
+
+    // Nothing changed, and nothing is waited for either
+    let info = client.get_status_sync().unwrap();
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(info.mode, Some(WarpMode::WarpDoH));
+    assert_eq!(
+        client
+            .disconnect_and_wait_sync(Duration::from_secs(5))
+            .unwrap(),
+        WarpStatus::Disconnected
+    );
+    assert!(
+        helpers::mock_calls(&mock)
+            .iter()
+            .all(|call| !call.contains("connect") && !call.starts_with("mode"))
+    );
+
+    // A missing binary can't fail a command that's never spawned
+    let client = WarpClient::builder()
+        .binary_path(mock.binary_path().with_file_name("no-such-warp-cli"))
+        .dry_run(true)
+        .build();
+    assert_eq!(client.connect_sync().unwrap(), ConnectOutcome::Connected);
+    assert!(matches!(
+        client.get_status_sync(),
+        Err(WarpError::CommandNotFound)
+    ));
+}
+
+/// The DNS families mode is applied in the background and its failure reported
+#[cfg(unix)]
+#[test]
+fn test_mock_families_select() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("families-select");
+    let mut app = App::default().with_client(client.clone());
+    app.on_key_event(KeyEvent::from(KeyCode::Char('f')));
+    assert_eq!(app.selected_index(), Some(0));
+    app.on_key_event(KeyEvent::from(KeyCode::Enter));
+    assert_eq!(app.pending_operation(), Some("Applying families mode…"));
+
+    // The picker stays closed while the command runs
+    app.on_key_event(KeyEvent::from(KeyCode::Char('f')));
+    assert_eq!(app.selected_index(), None);
+
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+    // The mock warp-cli doesn't know `dns families`
+    let error = app.last_error().unwrap();
+    assert!(error.contains("Set families mode failed"), "{}", error);
+    assert!(helpers::mock_calls(&client).contains(&"dns families off".to_string()));
+
+    // Nor does the picker open without warp-cli
+    app.handle_status_result(Err(WarpError::CommandNotFound));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('f')));
+    assert_eq!(app.selected_index(), None);
+}
+
+/// The registration panel fetches the registration in the background
+#[cfg(unix)]
+#[test]
+fn test_mock_registration_panel() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("registration-panel");
+    let mut app = App::default().with_client(client);
+    app.on_key_event(KeyEvent::from(KeyCode::Char('g')));
+    assert_eq!(app.pending_operation(), Some("Fetching registration…"));
+    assert_eq!(app.registration_panel_device_id(), None);
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+    assert_eq!(app.registration_panel_device_id(), Some("0123-abcd"));
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('g')));
+    assert_eq!(app.registration_panel_device_id(), None);
+    assert_eq!(app.pending_operation(), None);
+}
+
+/// An unregistered device is registered from the call to action
+#[cfg(unix)]
+#[test]
+fn test_mock_register_from_empty_state() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("register");
+    std::fs::write(client.binary_path().with_file_name("unregistered"), "").unwrap();
+    assert!(matches!(
+        client.get_registration_sync(),
+        Err(WarpError::NoRegistration)
+    ));
+
+    let mut app = App::default().with_client(client.clone());
+    app.handle_registration_check_result(client.get_registration_sync());
+    assert!(app.is_registration_missing());
+
+    app.on_key_event(KeyEvent::from(KeyCode::Char('n')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('y')));
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+
+    assert!(!app.is_registration_missing());
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("Registered (account type: Free) as device 0123-abcd"))
+    );
+    assert_eq!(app.registration_panel_device_id(), Some("0123-abcd"));
+    let info = client.get_registration_sync().unwrap();
+    assert_eq!(info.device_id.as_deref(), Some("0123-abcd"));
+}
+
+/// Settings locked by the organization disable every command that changes WARP
+#[cfg(unix)]
+#[test]
+fn test_mock_locked_settings() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("locked");
+    std::fs::write(client.binary_path().with_file_name("locked"), "").unwrap();
+    let mut app = App::default()
+        .with_config(Config {
+            profiles: vec![Profile {
+                name: "work".to_string(),
+                mode: Some("doh".to_string()),
+                families_mode: None,
+            }],
+            ..Default::default()
+        })
+        .with_client(client.clone());
+    assert!(!app.is_locked());
+
+    // The startup fetch runs in the background, the settings count as unlocked meanwhile
+    app.start_settings_check();
+    assert!(!app.is_locked());
+    let started = Instant::now();
+    while !app.is_locked() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_settings_check();
+    }
+    assert!(app.is_locked());
+
+    // Reloading the settings keeps the lock
+    app.on_key_event(KeyEvent::from(KeyCode::Char('2')));
+    helpers::wait_for_settings(&mut app);
+    app.on_key_event(KeyEvent::from(KeyCode::Char('1')));
+    assert!(app.is_locked());
+
+    // Registering is offered only without a registration
+    std::fs::write(client.binary_path().with_file_name("unregistered"), "").unwrap();
+    app.handle_registration_check_result(client.get_registration_sync());
+    assert!(app.is_registration_missing());
+
+    let calls_before = helpers::mock_calls(&client).len();
+    for key in ['c', 'd', 'm', 'f', 'p', 'o', 'w', 'z', 'e', 'v', 'u', 'n'] {
+        app.on_key_event(KeyEvent::from(KeyCode::Char(key)));
+        assert_eq!(app.pending_operation(), None, "key {}", key);
+        assert_eq!(app.pending_action(), None, "key {}", key);
+        assert_eq!(app.selected_index(), None, "key {}", key);
+    }
+    assert_eq!(helpers::mock_calls(&client).len(), calls_before);
+    assert_eq!(
+        client.get_status_sync().unwrap().status,
+        WarpStatus::Disconnected
+    );
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("Connecting is disabled: managed by your organization"))
+    );
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("Registering is disabled: managed by your organization"))
+    );
+
+    // Unlocking is picked up when the settings are reloaded
+    std::fs::remove_file(client.binary_path().with_file_name("locked")).unwrap();
+    app.on_key_event(KeyEvent::from(KeyCode::Char('2')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('r')));
+    helpers::wait_for_settings(&mut app);
+    assert!(!app.is_locked());
+    app.on_key_event(KeyEvent::from(KeyCode::Char('1')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('m')));
+    assert!(app.selected_index().is_some());
+}
+
+/// A burst of refresh requests runs warp-cli status only once
+#[cfg(unix)]
+#[tokio::test]
+async fn test_manager_coalesces_refreshes() {
+    let client = helpers::mock_warp_client("coalesce");
+    let mut manager = WarpManager::with_client(client.clone());
+    let mut events = manager.take_event_receiver().unwrap();
+    let status_calls = || {
+        helpers::mock_calls(&client)
+            .iter()
+            .filter(|call| call.ends_with("status"))
+            .count()
+    };
+
+    // Concurrent requests while the first one is in flight
+    let (first, second) = tokio::join!(
+        manager.handle_message(WarpMessage::Refresh),
+        manager.handle_message(WarpMessage::Refresh)
+    );
+    first.unwrap();
+    second.unwrap();
+    // Sequential requests right after it completed
+    for _ in 0..3 {
+        manager.handle_message(WarpMessage::Refresh).await.unwrap();
+    }
+    assert_eq!(status_calls(), 1);
+    assert!(matches!(
+        events.try_recv(),
+        Ok(WarpMessage::StatusUpdate(..))
+    ));
+    assert!(events.try_recv().is_err());
+
+    // A connect changes the status, so it's always followed by a refresh
+    manager.handle_message(WarpMessage::Connect).await.unwrap();
+    assert_eq!(status_calls(), 2);
+
+    // Once the window has passed, refreshing works again
+    tokio::time::sleep(REFRESH_COALESCE_WINDOW).await;
+    manager.handle_message(WarpMessage::Refresh).await.unwrap();
+    assert_eq!(status_calls(), 3);
+}
+
+/// The manager fetches a status caught mid-update once more before publishing it
+#[cfg(unix)]
+#[tokio::test]
+async fn test_manager_retries_unknown_status() {
+    let client = helpers::mock_warp_client("manager-retry");
+    std::fs::write(client.binary_path().with_file_name("status"), "Updating").unwrap();
+    let mut manager = WarpManager::with_client(client.clone());
+    let mut events = manager.take_event_receiver().unwrap();
+    let status_calls = || {
+        helpers::mock_calls(&client)
+            .iter()
+            .filter(|call| call.ends_with("status"))
+            .count()
+    };
+
+    manager.handle_message(WarpMessage::Refresh).await.unwrap();
+    assert_eq!(status_calls(), 2);
+    assert!(matches!(
+        events.try_recv(),
+        Ok(WarpMessage::StatusUpdate(info, _)) if info.status == WarpStatus::Unknown
+    ));
+    assert!(events.try_recv().is_err());
+
+    // A known status is published without fetching it again
+    tokio::time::sleep(REFRESH_COALESCE_WINDOW).await;
+    std::fs::write(client.binary_path().with_file_name("status"), "Connected").unwrap();
+    manager.handle_message(WarpMessage::Refresh).await.unwrap();
+    assert_eq!(status_calls(), 3);
+}
+
+/// Refreshes by the manager include the statistics once connected
+#[cfg(unix)]
+#[tokio::test]
+async fn test_manager_refresh_includes_stats() {
+    let client = helpers::mock_warp_client("manager-stats");
+    let mut manager = WarpManager::with_client(client);
+    let mut events = manager.take_event_receiver().unwrap();
+
+    manager.handle_message(WarpMessage::Refresh).await.unwrap();
+    assert!(matches!(
+        events.try_recv(),
+        Ok(WarpMessage::StatusUpdate(_, None))
+    ));
+
+    manager.handle_message(WarpMessage::Connect).await.unwrap();
+    let mut stats = None;
+    while let Ok(event) = events.try_recv() {
+        if let WarpMessage::StatusUpdate(info, update) = event {
+            assert_eq!(info.status, WarpStatus::Connected);
+            stats = update;
+        }
+    }
+    assert_eq!(stats.unwrap().latency_ms, Some(23));
+}
+
+/// Only the first processing task gets the command queue, a second one returns at once
+#[cfg(unix)]
+#[tokio::test]
+async fn test_manager_second_process_messages_returns() {
+    let client = helpers::mock_warp_client("process-twice");
+    let mut manager = WarpManager::with_client(client);
+    let mut events = manager.take_event_receiver().unwrap();
+    let manager = std::sync::Arc::new(manager);
+
+    let processing = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.process_messages().await }
+    });
+    tokio::task::yield_now().await;
+
+    tokio::time::timeout(Duration::from_secs(1), manager.process_messages())
+        .await
+        .expect("second process_messages call deadlocked");
+
+    // The first task still handles commands
+    manager.get_sender().send(WarpMessage::Refresh).unwrap();
+    let event = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .unwrap();
+    assert!(matches!(event, Some(WarpMessage::StatusUpdate(..))));
+    assert!(!processing.is_finished());
+    processing.abort();
+}
+
+/// A watcher sends the status on every poll until it's dropped
+#[cfg(unix)]
+#[tokio::test]
+async fn test_mock_watch_status() {
+    let client = helpers::mock_warp_client("watch");
+    let mut watcher = client.watch(Duration::from_millis(50));
+
+    let first = watcher.recv().await.unwrap().unwrap();
+    assert_eq!(first.status, WarpStatus::Disconnected);
+
+    client.connect_sync().unwrap();
+    let mut statuses = Vec::new();
+    while statuses.last() != Some(&WarpStatus::Connected) {
+        assert!(statuses.len() < 5, "never saw Connected: {:?}", statuses);
+        let info = tokio::time::timeout(Duration::from_secs(5), watcher.recv())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        statuses.push(info.status);
+    }
+
+    // Dropping the receiver stops the polling
+    drop(watcher);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let calls = helpers::mock_calls(&client).len();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(helpers::mock_calls(&client).len(), calls);
+}
+
+/// Stats are only fetched when connected
+#[cfg(unix)]
+#[test]
+fn test_mock_status_with_stats() {
+    let client = helpers::mock_warp_client("status-stats");
+    let stats_calls = || {
+        helpers::mock_calls(&client)
+            .iter()
+            .filter(|call| call.ends_with("warp-stats"))
+            .count()
+    };
+
+    let (info, stats) = client.get_status_with_stats_sync().unwrap();
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(stats, None);
+    assert_eq!(stats_calls(), 0);
+
+    client.connect_sync().unwrap();
+    let (info, stats) = client.get_status_with_stats_sync().unwrap();
+    assert_eq!(info.status, WarpStatus::Connected);
+    let stats = stats.unwrap();
+    assert_eq!(stats.endpoint.as_deref(), Some("162.159.193.1:2408"));
+    assert_eq!(stats.latency_ms, Some(23));
+    assert_eq!(stats_calls(), 1);
+}
+
+/// A stats failure doesn't hide the status
+#[cfg(unix)]
+#[test]
+fn test_mock_status_with_failing_stats() {
+    let client = helpers::mock_warp_client("status-stats-failing");
+    client.connect_sync().unwrap();
+
+    // Wrap the mock so `--json warp-stats` prints garbage while everything else works
+    let wrapper = client.binary_path().with_file_name("warp-cli-broken-stats");
+    std::fs::write(
+        &wrapper,
+        format!(
+            "#!/bin/sh\n[ \"$2\" = warp-stats ] && {{ echo garbage; exit 0; }}\nexec {} \"$@\"\n",
+            client.binary_path().display()
+        ),
+    )
+    .unwrap();
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let client = WarpClient::builder().binary_path(wrapper).build();
+
+    let (info, stats) = client.get_status_with_stats_sync().unwrap();
+    assert_eq!(info.status, WarpStatus::Connected);
+    assert_eq!(stats, None);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_mock_abort_operation() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::os::unix::fs::PermissionsExt;
+
+    // A warp-cli whose connect hangs, everything else goes to the mock
+    let client = helpers::mock_warp_client("abort");
+    let path = client
+        .binary_path()
+        .with_file_name("warp-cli-hanging-connect");
+    std::fs::write(
+        &path,
+        "#!/bin/sh\ncase \"$1\" in\n  connect) exec sleep 5 ;;\n  *) exec \"$(dirname \"$0\")/warp-cli\" \"$@\" ;;\nesac\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let client = WarpClient::builder().binary_path(path).build();
+
+    let mut app = App::default().with_client(client);
+    app.on_key_event(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE));
+    assert_eq!(app.pending_operation(), Some("Connecting…"));
+
+    let started = Instant::now();
+    app.on_key_event(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL));
+    while app.pending_operation().is_some() {
+        assert!(
+            started.elapsed() < Duration::from_secs(2),
+            "abort timed out"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("Connect aborted"))
+    );
+    // Refreshed afterwards, so the status reflects what WARP is actually doing
+    assert_eq!(app.warp_info().status, WarpStatus::Disconnected);
+    assert!(app.last_error().is_none());
+}
+
+/// Resetting the settings restores the defaults, with or without a registration
+#[cfg(unix)]
+#[test]
+fn test_mock_reset_settings() {
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    let client = helpers::mock_warp_client("reset");
+    client.set_mode_sync("doh").unwrap();
+    client.set_tunnel_protocol_sync("masque").unwrap();
+
+    let mut app = App::default().with_client(client.clone());
+    app.on_key_event(KeyEvent::from(KeyCode::Char('e')));
+    app.on_key_event(KeyEvent::from(KeyCode::Char('y')));
+    let started = Instant::now();
+    while app.pending_operation().is_some() && started.elapsed() < Duration::from_secs(10) {
+        std::thread::sleep(Duration::from_millis(10));
+        app.poll_pending_operation();
+    }
+
+    assert!(
+        helpers::mock_calls(&client)
+            .iter()
+            .any(|call| call == "settings reset")
+    );
+    assert!(
+        app.log()
+            .entries()
+            .any(|entry| entry.contains("Settings reset to defaults"))
+    );
+    assert!(app.last_error().is_none());
+    assert_eq!(client.get_operation_mode().unwrap(), WarpMode::WarpDoH);
+    assert!(!client.binary_path().with_file_name("protocol").exists());
+
+    // Nothing to reset without a registration, which isn't a failure
+    std::fs::write(client.binary_path().with_file_name("unregistered"), "").unwrap();
+    client.reset_settings_sync().unwrap();
+}
@@ -0,0 +1,27 @@
+// Installs a global subscriber and changes the environment, so it runs in its own binary
+
+use warp_tui::logging::{LOG_PATH_ENV, init_logging};
+use warp_tui::warp::WarpClient;
+
+#[test]
+fn test_commands_are_logged_to_file() {
+    let path = std::env::temp_dir().join(format!("warp-tui-log-test-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    // SAFETY: this is the only test in this binary, so no other thread reads the environment
+    unsafe { std::env::set_var(LOG_PATH_ENV, &path) };
+    init_logging().unwrap();
+    unsafe { std::env::remove_var(LOG_PATH_ENV) };
+
+    let client = WarpClient::builder()
+        .binary_path("/nonexistent/warp-tui-test/warp-cli")
+        .build();
+    assert!(client.get_version_sync().is_err());
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    assert!(log.contains("WARN"));
+    assert!(log.contains("warp-cli failed to run"));
+    assert!(log.contains("--version"));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,215 @@
+#!/bin/sh
+# Stand-in for warp-cli used by the integration tests.
+#
+# The connection status and operation mode are kept in files next to the script,
+# so every copy of it behaves like an independent WARP installation.
+
+dir=$(dirname "$0")
+status_file="$dir/status"
+mode_file="$dir/mode"
+protocol_file="$dir/protocol"
+override_file="$dir/override"
+unregistered_file="$dir/unregistered"
+# Lines listing the connected networks, e.g. "SSID: HomeWifi", shown by the status;
+# the JSON status turns them into "ssid" and "network" fields
+network_file="$dir/network"
+trusted_file="$dir/trusted_ssids"
+
+status=$(cat "$status_file" 2>/dev/null || echo Disconnected)
+mode=$(cat "$mode_file" 2>/dev/null || echo warp+doh)
+protocol=$(cat "$protocol_file" 2>/dev/null || echo WireGuard)
+
+# With a `connect_polls` or `disconnect_polls` file holding N, connecting or
+# disconnecting takes N status checks
+connect_polls_file="$dir/connect_polls"
+polls_file="$dir/disconnect_polls"
+settle_after_polls() {
+    polls=$(cat "$1" 2>/dev/null || echo 1)
+    if [ "$polls" -le 1 ]; then
+        rm -f "$1"
+        echo "$2" > "$status_file"
+    else
+        echo $((polls - 1)) > "$1"
+    fi
+}
+if [ "$1 $2" = "--json status" -o "$1" = status ]; then
+    case "$status" in
+        Connecting) settle_after_polls "$connect_polls_file" Connected ;;
+        Disconnecting) settle_after_polls "$polls_file" Disconnected ;;
+    esac
+fi
+
+# Every invocation is recorded, so tests can count the commands a feature ran
+echo "$*" >> "$dir/calls"
+
+json=false
+if [ "$1" = "--json" ]; then
+    json=true
+    shift
+fi
+
+case "$1" in
+    --version)
+        echo "warp-cli 2024.6.497"
+        ;;
+    status)
+        if $json; then
+            override=$(cat "$override_file" 2>/dev/null)
+            networks=""
+            if [ -f "$network_file" ]; then
+                while IFS= read -r line; do
+                    case "$line" in
+                        "SSID: "*) networks="$networks,\"ssid\":\"${line#SSID: }\"" ;;
+                        "Network: "*) networks="$networks,\"network\":\"${line#Network: }\"" ;;
+                    esac
+                done < "$network_file"
+            fi
+            if [ "$status" = Disconnected ]; then
+                printf '{"status":"%s","reason":"Manual Disconnection","override":"%s"%s}\n' \
+                    "$status" "$override" "$networks"
+            else
+                printf '{"status":"%s","override":"%s"%s}\n' "$status" "$override" "$networks"
+            fi
+        else
+            echo "Status update: $status"
+            if [ "$status" = Disconnected ]; then
+                echo "Reason: Manual Disconnection"
+            fi
+            if [ -f "$override_file" ]; then
+                echo "Override: $(cat "$override_file")"
+            fi
+            if [ -f "$network_file" ]; then
+                cat "$network_file"
+            fi
+        fi
+        ;;
+    settings)
+        if [ "$2" = reset ]; then
+            if [ -f "$unregistered_file" ]; then
+                echo "Error: Missing registration. Try running: \"warp-cli registration new\"" >&2
+                exit 1
+            fi
+            rm -f "$mode_file" "$protocol_file" "$override_file"
+            echo "Success"
+            exit 0
+        fi
+        # One trusted SSID per line in the trusted_ssids file
+        if [ "$2" = add-trusted-ssid ]; then
+            echo "$3" >> "$trusted_file"
+            echo "Success"
+            exit 0
+        fi
+        if [ "$2" = del-trusted-ssid ]; then
+            grep -vxF "$3" "$trusted_file" > "$trusted_file.new" 2>/dev/null
+            mv "$trusted_file.new" "$trusted_file"
+            echo "Success"
+            exit 0
+        fi
+        if $json; then
+            locked=false
+            if [ -f "$dir/locked" ]; then
+                locked=true
+            fi
+            printf '{"settings":{"operation_mode":"%s","always_on":false,"switch_locked":%s,"tunnel_protocol":"%s"}}\n' \
+                "$mode" "$locked" "$protocol"
+        else
+            echo "Mode: $mode"
+            if [ -s "$trusted_file" ]; then
+                printf 'Trusted SSIDs: %s\n' "$(paste -sd, "$trusted_file")"
+            fi
+        fi
+        ;;
+    mode)
+        echo "$2" > "$mode_file"
+        echo "Success"
+        ;;
+    warp-stats)
+        if [ "$status" != Connected ]; then
+            echo "Error: Not connected" >&2
+            exit 1
+        fi
+        echo '{"endpoint":"162.159.193.1:2408","latency_ms":23,"bytes_sent":1024,"bytes_received":4096}'
+        ;;
+    dns)
+        if [ "$2" != stats ]; then
+            echo "error: unrecognized subcommand '$2'" >&2
+            exit 2
+        fi
+        if $json; then
+            echo '{"total_queries":120,"cached":90,"blocked":3,"latency":4.2}'
+        else
+            printf 'Total queries: 120\nCached: 90\nBlocked: 3\nLatency: 4.2 ms\n'
+        fi
+        ;;
+    tunnel)
+        if [ "$2" = stats ]; then
+            if [ "$status" != Connected ]; then
+                echo "Error: Not connected" >&2
+                exit 1
+            fi
+            echo "{\"rx_bytes\":8192,\"tx_bytes\":2048,\"rx_packets\":64,\"tx_packets\":32,\"last_handshake\":$(( $(date +%s) - 30 ))}"
+            exit 0
+        fi
+        if [ "$2 $3" != "protocol set" ]; then
+            echo "error: unrecognized subcommand '$2'" >&2
+            exit 2
+        fi
+        echo "$4" > "$protocol_file"
+        echo "Success"
+        ;;
+    registration)
+        case "$2" in
+            show)
+                if [ -f "$unregistered_file" ]; then
+                    echo "Error: Missing registration. Try running: \"warp-cli registration new\"" >&2
+                    exit 1
+                fi
+                printf 'Account type: Free\nDevice ID: 0123-abcd\n'
+                ;;
+            new)
+                rm -f "$unregistered_file"
+                echo "Success"
+                ;;
+            *)
+                echo "error: unrecognized subcommand '$2'" >&2
+                exit 2
+                ;;
+        esac
+        ;;
+    override)
+        if [ "$2" = clear ]; then
+            rm -f "$override_file"
+        else
+            echo "$2" > "$override_file"
+        fi
+        echo "Success"
+        ;;
+    connect)
+        if [ "$status" = Connected ]; then
+            echo "Error: already connected" >&2
+            exit 1
+        fi
+        if [ -f "$connect_polls_file" ]; then
+            echo Connecting > "$status_file"
+        else
+            echo Connected > "$status_file"
+        fi
+        echo "Success"
+        ;;
+    disconnect)
+        if [ "$status" = Disconnected ]; then
+            echo "Error: already disconnected" >&2
+            exit 1
+        fi
+        if [ -f "$polls_file" ]; then
+            echo Disconnecting > "$status_file"
+        else
+            echo Disconnected > "$status_file"
+        fi
+        echo "Success"
+        ;;
+    *)
+        echo "error: unrecognized subcommand '$1'" >&2
+        exit 2
+        ;;
+esac
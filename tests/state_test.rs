@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use warp_tui::app::App;
+use warp_tui::app::state::{self, SessionState, StateError};
+use warp_tui::warp::WarpMode;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("warp-tui-state-test-{}", std::process::id()))
+        .join(name)
+}
+
+#[test]
+fn test_state_round_trip() {
+    let path = temp_path("round-trip/state.toml");
+    let saved = SessionState {
+        refresh_interval_ms: Some(2500),
+        last_mode: Some("warp+doh".to_string()),
+        auto_reconnect: Some(true),
+        last_profile: Some("Work".to_string()),
+    };
+
+    state::save(&path, &saved).unwrap();
+    let loaded = state::load(&path).unwrap();
+    assert_eq!(loaded, saved);
+    assert_eq!(loaded.last_mode(), Some(WarpMode::WarpDoH));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_missing_and_corrupt_state() {
+    let path = temp_path("missing/state.toml");
+    assert!(matches!(state::load(&path), Err(StateError::Io(_))));
+
+    let path = temp_path("corrupt/state.toml");
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "refresh_interval_ms = \"fast\"").unwrap();
+    assert!(matches!(state::load(&path), Err(StateError::Parse(_))));
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    // Unknown modes are dropped
+    let state = SessionState {
+        refresh_interval_ms: None,
+        last_mode: Some("warp+doq".to_string()),
+        ..SessionState::default()
+    };
+    assert_eq!(state.last_mode(), None);
+}
+
+#[test]
+fn test_app_applies_state() {
+    let mut app = App::default();
+    app.apply_state(&SessionState {
+        refresh_interval_ms: Some(1_000_000),
+        last_mode: Some("dot".to_string()),
+        auto_reconnect: Some(true),
+        last_profile: Some("Home".to_string()),
+    });
+    assert_eq!(app.refresh_interval_ms(), 10_000);
+    assert!(app.is_auto_reconnect_enabled());
+    assert_eq!(app.active_profile(), Some("Home"));
+
+    let state = app.session_state();
+    assert_eq!(state.refresh_interval_ms, Some(10_000));
+    assert_eq!(state.last_mode.as_deref(), Some("dot"));
+    assert_eq!(state.auto_reconnect, Some(true));
+    assert_eq!(state.last_profile.as_deref(), Some("Home"));
+
+    // Defaults are kept for anything that wasn't saved
+    let mut app = App::default();
+    app.apply_state(&SessionState::default());
+    assert_eq!(app.refresh_interval_ms(), 1000);
+    assert_eq!(app.session_state().last_mode, None);
+    assert!(!app.is_auto_reconnect_enabled());
+    assert_eq!(app.active_profile(), None);
+}
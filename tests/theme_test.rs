@@ -0,0 +1,91 @@
+use ratatui::style::Color;
+use warp_tui::app::theme::{Theme, color_for_mode, parse_color};
+use warp_tui::warp::{WarpMode, WarpStatus};
+
+#[test]
+fn test_parse_color() {
+    assert_eq!(parse_color("green"), Some(Color::Green));
+    assert_eq!(parse_color("Green"), Some(Color::Green));
+    assert_eq!(parse_color("bright_red"), Some(Color::LightRed));
+    assert_eq!(parse_color("bright-blue"), Some(Color::LightBlue));
+    assert_eq!(parse_color("dark_gray"), Some(Color::DarkGray));
+    assert_eq!(parse_color("#FF8000"), Some(Color::Rgb(255, 128, 0)));
+    assert_eq!(parse_color("#00ff7f"), Some(Color::Rgb(0, 255, 127)));
+
+    assert_eq!(parse_color("chartreuse"), None);
+    assert_eq!(parse_color("#FFF"), None);
+    assert_eq!(parse_color("#GGGGGG"), None);
+    assert_eq!(parse_color(""), None);
+}
+
+#[test]
+fn test_default_theme_colors() {
+    let theme = Theme::default();
+    assert_eq!(theme.color_for(&WarpStatus::Connected), Color::Green);
+    assert_eq!(theme.color_for(&WarpStatus::Disconnected), Color::Red);
+    assert_eq!(theme.color_for(&WarpStatus::Connecting), Color::Yellow);
+    assert_eq!(theme.color_for(&WarpStatus::Disconnecting), Color::Yellow);
+    assert_eq!(theme.color_for(&WarpStatus::Unknown), Color::Gray);
+}
+
+#[test]
+fn test_theme_from_toml() {
+    let (theme, warnings) = Theme::from_toml_str(
+        r##"
+        connected = "bright_green"
+        disconnected = "#AA0000"
+        "##,
+    );
+    assert!(warnings.is_empty());
+    assert_eq!(theme.color_for(&WarpStatus::Connected), Color::LightGreen);
+    assert_eq!(
+        theme.color_for(&WarpStatus::Disconnected),
+        Color::Rgb(0xAA, 0, 0)
+    );
+    // Missing keys keep their defaults
+    assert_eq!(theme.color_for(&WarpStatus::Unknown), Color::Gray);
+}
+
+#[test]
+fn test_theme_invalid_color_falls_back() {
+    let (theme, warnings) = Theme::from_toml_str(
+        r#"
+        connected = "not-a-color"
+        unknown = "white"
+        "#,
+    );
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("not-a-color"));
+    assert_eq!(theme.color_for(&WarpStatus::Connected), Color::Green);
+    assert_eq!(theme.color_for(&WarpStatus::Unknown), Color::White);
+
+    let (theme, warnings) = Theme::from_toml_str("this is not toml");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(theme, Theme::default());
+}
+
+#[test]
+fn test_missing_theme_file_uses_defaults() {
+    let path = std::env::temp_dir().join("warp-tui-theme-does-not-exist.toml");
+    let (theme, warnings) = Theme::load(&path);
+    assert!(warnings.is_empty());
+    assert_eq!(theme, Theme::default());
+}
+
+#[test]
+fn test_mode_colors() {
+    let cases = [
+        (WarpMode::DoH, Color::Blue),
+        (WarpMode::DoT, Color::Blue),
+        (WarpMode::DnsOnly, Color::Blue),
+        (WarpMode::WarpDoH, Color::Cyan),
+        (WarpMode::WarpDoT, Color::Cyan),
+        (WarpMode::Warp, Color::Cyan),
+        (WarpMode::Proxy, Color::Cyan),
+        (WarpMode::TunnelOnly, Color::Cyan),
+        (WarpMode::Unknown, Color::Gray),
+    ];
+    for (mode, color) in cases {
+        assert_eq!(color_for_mode(&mode), color, "{:?}", mode);
+    }
+}
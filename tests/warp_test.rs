@@ -1,5 +1,18 @@
-use warp_tui::warp::client::WarpClient;
-use warp_tui::warp::types::{WarpMode, WarpStatus};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use warp_tui::warp::client::{
+    CancelToken, DEFAULT_MAX_OUTPUT_LINES, WarpClient, is_daemon_down_error, is_transient_error,
+    is_unsupported_command, redact_args, run_cancellable, run_with_timeout, truncate_output,
+    validate_cidr,
+};
+use warp_tui::warp::error::WarpError;
+use warp_tui::warp::manager::{REFRESH_COALESCE_WINDOW, RefreshCoalescer};
+use warp_tui::warp::types::{
+    ConnectOutcome, DisconnectOutcome, FamiliesMode, NetworkInfo, NetworkType, SettingChange,
+    SettingsBatch, TunnelProtocol, WARP_INFO_SCHEMA_VERSION, WarpInfo, WarpInfoChange,
+    WarpInfoEnvelope, WarpMode, WarpSettingsInfo, WarpStatus, mask_license_key,
+};
 
 #[tokio::test]
 async fn test_client_creation() {
@@ -93,3 +106,1613 @@ fn test_status_line_parsing() {
         WarpStatus::Unknown
     );
 }
+
+#[test]
+fn test_settings_parsing() {
+    let client = WarpClient::new();
+
+    let output = r#"{
+        "settings": {
+            "always_on": true,
+            "switch_locked": true,
+            "operation_mode": "warp+doh",
+            "dns_log_enabled": true,
+            "tunnel_protocol": "MASQUE",
+            "families_mode": "malware",
+            "disable_for_wifi": false
+        }
+    }"#;
+    let settings = client.parse_settings_output(output).unwrap();
+    assert!(settings.always_on);
+    assert!(settings.switch_locked);
+    assert_eq!(settings.mode, WarpMode::WarpDoH);
+    assert!(settings.dns_log_enabled);
+    assert_eq!(settings.tunnel_protocol, Some(TunnelProtocol::Masque));
+    assert_eq!(settings.families_mode, Some("malware".to_string()));
+
+    // Older warp-cli versions omit most keys
+    let output = r#"{"settings": {"operation_mode": "dot"}}"#;
+    let settings = client.parse_settings_output(output).unwrap();
+    assert!(!settings.always_on);
+    assert!(!settings.switch_locked);
+    assert_eq!(settings.mode, WarpMode::DoT);
+    assert!(!settings.dns_log_enabled);
+    assert_eq!(settings.tunnel_protocol, None);
+    assert_eq!(settings.families_mode, None);
+
+    // Unrecognized modes resolve to Unknown
+    let output = r#"{"settings": {"operation_mode": "something-new"}}"#;
+    let settings = client.parse_settings_output(output).unwrap();
+    assert_eq!(settings.mode, WarpMode::Unknown);
+
+    // Unrecognized tunnel protocols are left out
+    let output = r#"{"settings": {"tunnel_protocol": "carrier-pigeon"}}"#;
+    let settings = client.parse_settings_output(output).unwrap();
+    assert_eq!(settings.tunnel_protocol, None);
+
+    assert!(client.parse_settings_output("not json").is_err());
+}
+
+#[test]
+fn test_account_parsing() {
+    let client = WarpClient::new();
+
+    let output = r#"{
+        "account_type": "unlimited",
+        "id": "a1b2c3d4-0000-1111-2222-333344445555",
+        "public_key": "pubkey123=",
+        "quota": 1000000,
+        "premium_data": 500000
+    }"#;
+    let account = client.parse_account_json(output).unwrap();
+    assert_eq!(account.account_type, Some("unlimited".to_string()));
+    assert_eq!(
+        account.device_id,
+        Some("a1b2c3d4-0000-1111-2222-333344445555".to_string())
+    );
+    assert_eq!(account.public_key, Some("pubkey123=".to_string()));
+    assert_eq!(account.quota, Some(1000000));
+    assert_eq!(account.premium_data, Some(500000));
+
+    let output = "Account type: Free\nDevice ID: device-1\nPublic key: pubkey123=\nQuota: 42\nPremium data: 7";
+    let account = client.parse_account_output(output).unwrap();
+    assert_eq!(account.account_type, Some("Free".to_string()));
+    assert_eq!(account.device_id, Some("device-1".to_string()));
+    assert_eq!(account.public_key, Some("pubkey123=".to_string()));
+    assert_eq!(account.quota, Some(42));
+    assert_eq!(account.premium_data, Some(7));
+
+    assert!(matches!(
+        client.parse_account_output("Error: Missing registration"),
+        Err(WarpError::NoRegistration)
+    ));
+}
+
+#[test]
+fn test_transient_error_detection() {
+    assert!(is_transient_error("Error: daemon not ready"));
+    assert!(is_transient_error("Daemon Not Ready, please wait"));
+    assert!(is_transient_error("Resource temporarily unavailable"));
+    assert!(is_transient_error(
+        "Unable to connect to daemon: connection refused"
+    ));
+    assert!(is_transient_error("Please try again later"));
+
+    assert!(!is_transient_error("Error: Missing registration"));
+    assert!(!is_transient_error("Invalid argument"));
+    assert!(!is_transient_error(""));
+}
+
+#[test]
+fn test_license_key_masking() {
+    assert_eq!(
+        mask_license_key("ab12cd34-ef56gh78-ij90kl12"),
+        "****-****-kl12"
+    );
+    assert_eq!(mask_license_key("12345"), "****-****-2345");
+
+    // Short keys are masked entirely
+    assert_eq!(mask_license_key("abcd"), "****");
+    assert_eq!(mask_license_key("a"), "****");
+
+    // Empty keys stay empty
+    assert_eq!(mask_license_key(""), "");
+    assert_eq!(mask_license_key("   "), "");
+}
+
+#[test]
+fn test_connected_networks_parsing() {
+    let client = WarpClient::new();
+
+    // No network lines
+    let info = client.parse_status_text("Status update: Connected");
+    assert!(info.connected_networks.is_empty());
+
+    // A single network
+    let info = client.parse_status_text("Status update: Connected\nNetwork: HomeWifi");
+    assert_eq!(info.connected_networks, vec!["HomeWifi".to_string()]);
+
+    // Multiple networks, including a trusted-network block
+    let output = "Status update: Disconnected\n\
+        Network: HomeWifi\n\
+        SSID: \"Coffee Shop\"\n\
+        Trusted networks:\n\
+        - Office\n\
+        - HomeWifi\n\
+        Account type: Free";
+    let info = client.parse_status_text(output);
+    assert_eq!(
+        info.connected_networks,
+        vec![
+            "HomeWifi".to_string(),
+            "Coffee Shop".to_string(),
+            "Office".to_string()
+        ]
+    );
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(info.account_type, Some("Free".to_string()));
+
+    // Malformed lines are skipped
+    let output = "Network:\nNetwork\nSSID:   \nTrusted networks:\n-\n- \nStatus update: Connected";
+    let info = client.parse_status_text(output);
+    assert!(info.connected_networks.is_empty());
+    assert_eq!(info.status, WarpStatus::Connected);
+}
+
+#[test]
+fn test_warp_stats_parsing() {
+    let client = WarpClient::new();
+
+    let output = r#"{
+        "endpoint": "162.159.193.1:2408",
+        "latency_ms": 23,
+        "loss_percent": 0.5,
+        "bytes_sent": 1048576,
+        "bytes_received": 5242880
+    }"#;
+    let stats = client.parse_warp_stats(output).unwrap();
+    assert_eq!(stats.endpoint, Some("162.159.193.1:2408".to_string()));
+    assert_eq!(stats.latency_ms, Some(23));
+    assert_eq!(stats.loss_percent, Some(0.5));
+    assert_eq!(stats.bytes_sent, 1048576);
+    assert_eq!(stats.bytes_received, 5242880);
+
+    // Missing counters default to zero
+    let stats = client.parse_warp_stats("{}").unwrap();
+    assert_eq!(stats.endpoint, None);
+    assert_eq!(stats.bytes_sent, 0);
+
+    assert!(matches!(
+        client.parse_warp_stats("Error: Not connected"),
+        Err(WarpError::NotConnected)
+    ));
+    // Only output that isn't JSON is taken for warp-cli's message
+    let stats = client
+        .parse_warp_stats(r#"{"endpoint": "not connected yet", "latency_ms": 5}"#)
+        .unwrap();
+    assert_eq!(stats.latency_ms, Some(5));
+    assert!(matches!(
+        client.parse_warp_stats("garbage"),
+        Err(WarpError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_run_with_timeout_kills_long_running_command() {
+    // Skip test if sleep is not available
+    if Command::new("sleep").arg("0").status().is_err() {
+        println!("Skipping test: sleep not available");
+        return;
+    }
+
+    let mut command = Command::new("sleep");
+    command.arg("5");
+    let started = Instant::now();
+    let result = run_with_timeout(command, Duration::from_millis(200));
+    assert!(matches!(result, Err(WarpError::Timeout(_))));
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // Commands finishing in time return their output
+    let mut command = Command::new("sleep");
+    command.arg("0");
+    let output = run_with_timeout(command, Duration::from_secs(5)).unwrap();
+    assert!(output.status.success());
+}
+
+#[test]
+fn test_run_with_timeout_missing_command() {
+    let command = Command::new("warp-cli-does-not-exist");
+    assert!(matches!(
+        run_with_timeout(command, Duration::from_secs(1)),
+        Err(WarpError::CommandNotFound)
+    ));
+}
+
+#[test]
+fn test_families_mode_conversion() {
+    for mode in FamiliesMode::ALL {
+        assert_eq!(FamiliesMode::from_cli_str(mode.as_cli_str()), Some(mode));
+    }
+    assert_eq!(
+        FamiliesMode::from_cli_str("MALWARE"),
+        Some(FamiliesMode::Malware)
+    );
+    assert_eq!(FamiliesMode::from_cli_str("adult"), None);
+    assert_eq!(FamiliesMode::from_cli_str(""), None);
+}
+
+#[test]
+fn test_set_families_mode_rejects_unknown_modes() {
+    let client = WarpClient::new();
+
+    // Validation happens before warp-cli is invoked, so this works without WARP installed
+    match client.set_families_mode_sync("strict") {
+        Err(WarpError::CommandFailed { stderr: msg, .. }) => {
+            assert!(msg.contains("unknown families mode: strict"));
+            assert!(msg.contains("off, malware, full"));
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_set_mode_rejects_unknown_modes() {
+    let client = WarpClient::new();
+
+    // Validation happens before warp-cli is invoked, so this works without WARP installed
+    for mode in ["warp+dho", "", "unknown", "tunnel"] {
+        match client.set_mode_sync(mode) {
+            Err(WarpError::CommandFailed { code, stderr: msg }) => {
+                assert_eq!(code, None);
+                assert!(msg.contains(&format!("unknown mode: {}", mode)), "{}", msg);
+                assert!(msg.contains("doh, dot, warp+doh, warp+dot, warp, proxy"));
+            }
+            other => panic!("expected CommandFailed for {:?}, got {:?}", mode, other),
+        }
+    }
+}
+
+#[test]
+fn test_set_mode_accepts_known_modes() {
+    // A missing binary shows the mode got past validation and warp-cli was spawned
+    let client = WarpClient::builder()
+        .binary_path("/nonexistent/warp-cli")
+        .build();
+    for mode in WarpMode::ALL {
+        assert!(matches!(
+            client.set_mode_sync(mode.as_cli_str()),
+            Err(WarpError::CommandNotFound)
+        ));
+    }
+    for mode in ["DoH", "TunnelOnly", "dns-only", " warp+dot "] {
+        assert!(matches!(
+            client.set_mode_sync(mode),
+            Err(WarpError::CommandNotFound)
+        ));
+    }
+}
+
+#[test]
+fn test_mode_cli_str_round_trip() {
+    let cases = [
+        (WarpMode::DoH, "doh"),
+        (WarpMode::DoT, "dot"),
+        (WarpMode::WarpDoH, "warp+doh"),
+        (WarpMode::WarpDoT, "warp+dot"),
+        (WarpMode::Warp, "warp"),
+        (WarpMode::Proxy, "proxy"),
+        (WarpMode::TunnelOnly, "tunnel_only"),
+        (WarpMode::DnsOnly, "dns_only"),
+        (WarpMode::Unknown, "unknown"),
+    ];
+    for (mode, cli_str) in cases {
+        assert_eq!(mode.as_cli_str(), cli_str);
+        assert_eq!(WarpMode::from_cli_str(cli_str), mode);
+        assert_eq!(WarpMode::from_cli_str(mode.as_cli_str()), mode);
+    }
+
+    // Display strings are for humans and still parse
+    assert_eq!(WarpMode::from_cli_str("Warp+DoH"), WarpMode::WarpDoH);
+    assert_eq!(WarpMode::from_cli_str("something-new"), WarpMode::Unknown);
+}
+
+#[test]
+fn test_trusted_ssids_parsing() {
+    let client = WarpClient::new();
+
+    let output = "Always On: true\n\
+        Trusted SSIDs: \"Home Wifi\", \"Office, 2nd Floor\", \"cafe\"\n\
+        Mode: WarpWithDnsOverHttps";
+    assert_eq!(
+        client.parse_trusted_ssids(output),
+        vec![
+            "Home Wifi".to_string(),
+            "Office, 2nd Floor".to_string(),
+            "cafe".to_string()
+        ]
+    );
+
+    // Unquoted lists are split on commas
+    let output = "Trusted SSIDs: HomeWifi, Office";
+    assert_eq!(
+        client.parse_trusted_ssids(output),
+        vec!["HomeWifi".to_string(), "Office".to_string()]
+    );
+
+    assert!(client.parse_trusted_ssids("Trusted SSIDs:").is_empty());
+    assert!(client.parse_trusted_ssids("Always On: true").is_empty());
+}
+
+#[test]
+fn test_trusted_ssid_rejects_empty_input() {
+    let client = WarpClient::new();
+
+    for ssid in ["", "   ", "\t"] {
+        assert!(matches!(
+            client.add_trusted_ssid_sync(ssid),
+            Err(WarpError::CommandFailed { .. })
+        ));
+        assert!(matches!(
+            client.remove_trusted_ssid_sync(ssid),
+            Err(WarpError::CommandFailed { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_cidr_validation() {
+    // IPv4
+    assert!(validate_cidr("10.0.0.0/8").is_ok());
+    assert!(validate_cidr("192.168.1.1/32").is_ok());
+    assert!(validate_cidr("0.0.0.0/0").is_ok());
+    assert!(validate_cidr(" 172.16.0.0/12 ").is_ok());
+    assert!(validate_cidr("10.0.0.0").is_err());
+    assert!(validate_cidr("10.0.0.0/33").is_err());
+    assert!(validate_cidr("10.0.0.0/-1").is_err());
+    assert!(validate_cidr("10.0.0/8").is_err());
+    assert!(validate_cidr("300.0.0.0/8").is_err());
+
+    // IPv6
+    assert!(validate_cidr("fd00::/8").is_ok());
+    assert!(validate_cidr("2606:4700::/32").is_ok());
+    assert!(validate_cidr("::1/128").is_ok());
+    assert!(validate_cidr("fd00::").is_err());
+    assert!(validate_cidr("fd00::/129").is_err());
+    assert!(validate_cidr("fd00::zz/64").is_err());
+
+    assert!(validate_cidr("").is_err());
+    assert!(validate_cidr("/24").is_err());
+
+    match validate_cidr("10.0.0.0/40") {
+        Err(WarpError::CommandFailed { stderr: msg, .. }) => assert!(msg.contains("at most 32")),
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_excluded_routes_parsing() {
+    let client = WarpClient::new();
+
+    let output = "Excluded IPs:\n\
+        10.0.0.0/8 (RFC 1918)\n\
+        192.168.0.0/16\n\
+        fd00::/8 local\n";
+    assert_eq!(
+        client.parse_excluded_routes(output),
+        vec![
+            "10.0.0.0/8".to_string(),
+            "192.168.0.0/16".to_string(),
+            "fd00::/8".to_string()
+        ]
+    );
+    assert!(client.parse_excluded_routes("").is_empty());
+}
+
+#[test]
+fn test_parse_version() {
+    let client = WarpClient::new();
+
+    assert_eq!(
+        client.parse_version("warp-cli 2024.6.497").unwrap(),
+        "2024.6.497"
+    );
+    assert_eq!(
+        client.parse_version("warp-cli v2023.10.120\n").unwrap(),
+        "2023.10.120"
+    );
+    assert!(client.parse_version("warp-cli").is_err());
+    assert!(client.parse_version("").is_err());
+}
+
+#[test]
+fn test_status_reason_parsing() {
+    let client = WarpClient::new();
+
+    let info = client.parse_status_text("Status update: Disconnected\nReason: Settings Changed");
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(info.reason.as_deref(), Some("Settings Changed"));
+
+    let info = client.parse_status_text("Status update: Connected");
+    assert_eq!(info.status, WarpStatus::Connected);
+    assert_eq!(info.reason, None);
+}
+
+#[test]
+fn test_warp_info_display() {
+    let info = WarpInfo {
+        status: WarpStatus::Disconnected,
+        reason: Some("Settings Changed".to_string()),
+        mode: Some(WarpMode::WarpDoH),
+        account_type: Some("Free".to_string()),
+        warp_enabled: true,
+        gateway_enabled: false,
+        connected_networks: vec!["HomeWifi".to_string(), "Office".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        info.to_string(),
+        "Status: Disconnected (Settings Changed)\n\
+         Mode: Warp+DoH\n\
+         Account Type: Free\n\
+         WARP Enabled: Yes\n\
+         Gateway Enabled: No\n\
+         Connected Networks:\n  \
+         - HomeWifi\n  \
+         - Office"
+    );
+
+    assert_eq!(
+        WarpInfo::default().to_string(),
+        "Status: Unknown\n\
+         Mode: N/A\n\
+         Account Type: N/A\n\
+         WARP Enabled: No\n\
+         Gateway Enabled: No\n\
+         Connected Networks: None"
+    );
+}
+
+#[test]
+fn test_connect_outcome_from_stderr() {
+    let client = WarpClient::new();
+
+    assert_eq!(
+        client
+            .parse_connect_error("Error: already connected")
+            .unwrap(),
+        ConnectOutcome::AlreadyConnected
+    );
+    assert_eq!(
+        client.parse_connect_error("Already Connected").unwrap(),
+        ConnectOutcome::AlreadyConnected
+    );
+    assert!(matches!(
+        client.parse_connect_error("Error: registration missing"),
+        Err(WarpError::ConnectionFailed(_))
+    ));
+    // "already disconnected" doesn't mean the connection succeeded
+    assert!(client.parse_connect_error("already disconnected").is_err());
+}
+
+#[test]
+fn test_disconnect_outcome_from_stderr() {
+    let client = WarpClient::new();
+
+    assert_eq!(
+        client
+            .parse_disconnect_error("Error: already disconnected")
+            .unwrap(),
+        DisconnectOutcome::AlreadyDisconnected
+    );
+    assert!(matches!(
+        client.parse_disconnect_error("Error: daemon not ready"),
+        Err(WarpError::DisconnectionFailed(_))
+    ));
+    assert!(client.parse_disconnect_error("already connected").is_err());
+
+    assert_eq!(
+        ConnectOutcome::AlreadyConnected.to_string(),
+        "Already connected"
+    );
+    assert_eq!(DisconnectOutcome::Disconnected.to_string(), "Disconnected");
+}
+
+#[test]
+fn test_parse_status_json() {
+    let client = WarpClient::new();
+
+    let info = client
+        .parse_status_json(r#"{"status":"Connected"}"#)
+        .unwrap();
+    assert_eq!(info.status, WarpStatus::Connected);
+    assert_eq!(info.reason, None);
+    assert_eq!(info.mode, None);
+
+    let info = client
+        .parse_status_json(
+            r#"{"status":"Disconnected","reason":"Settings Changed","account_type":"Free","warp_enabled":true}"#,
+        )
+        .unwrap();
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(info.reason.as_deref(), Some("Settings Changed"));
+    assert_eq!(info.account_type.as_deref(), Some("Free"));
+    assert!(info.warp_enabled);
+    assert!(!info.gateway_enabled);
+
+    let info = client
+        .parse_status_json(r#"{"status":"Registering"}"#)
+        .unwrap();
+    assert_eq!(info.status, WarpStatus::Unknown);
+}
+
+#[test]
+fn test_parse_status_json_networks() {
+    let client = WarpClient::new();
+
+    let info = client
+        .parse_status_json(
+            r#"{"status":"Connected","network":"eth0","ssid":"Coffee Shop","trusted_networks":["HomeWifi"]}"#,
+        )
+        .unwrap();
+    assert_eq!(info.connected_networks, ["eth0", "Coffee Shop", "HomeWifi"]);
+    let network = |name: &str, trusted, network_type| NetworkInfo {
+        name: name.to_string(),
+        trusted,
+        network_type,
+    };
+    assert_eq!(
+        info.networks,
+        vec![
+            network("eth0", false, NetworkType::Ethernet),
+            network("Coffee Shop", false, NetworkType::Wifi),
+            network("HomeWifi", true, NetworkType::Wifi),
+        ]
+    );
+
+    let info = client
+        .parse_status_json(r#"{"status":"Connected"}"#)
+        .unwrap();
+    assert!(info.connected_networks.is_empty());
+    assert!(info.networks.is_empty());
+}
+
+#[test]
+fn test_parse_status_json_falls_back_to_text() {
+    let client = WarpClient::new();
+    let output = "Status update: Disconnected\nReason: Manual Disconnection";
+
+    // Plain output is rejected by the JSON parser, and handled by the text parser
+    assert!(matches!(
+        client.parse_status_json(output),
+        Err(WarpError::ParseError(_))
+    ));
+    let info = client.parse_status_text(output);
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(info.reason.as_deref(), Some("Manual Disconnection"));
+}
+
+#[test]
+fn test_client_builder() {
+    let client = WarpClient::new();
+    assert_eq!(client.timeout(), Duration::from_secs(30));
+    assert_eq!(client.binary_path(), Path::new("warp-cli"));
+    assert!(client.prefers_json());
+
+    let client = WarpClient::builder()
+        .timeout(Duration::from_secs(5))
+        .binary_path("/opt/cloudflare-warp/bin/warp-cli")
+        .prefer_json(false)
+        .build();
+    assert_eq!(client.timeout(), Duration::from_secs(5));
+    assert_eq!(
+        client.binary_path(),
+        Path::new("/opt/cloudflare-warp/bin/warp-cli")
+    );
+    assert!(!client.prefers_json());
+
+    assert_eq!(
+        WarpClient::with_timeout(7).timeout(),
+        Duration::from_secs(7)
+    );
+}
+
+#[test]
+fn test_client_uses_binary_path() {
+    let client = WarpClient::builder()
+        .binary_path("/nonexistent/warp-tui-test/warp-cli")
+        .build();
+    assert!(matches!(
+        client.get_version_sync(),
+        Err(WarpError::CommandNotFound)
+    ));
+    assert!(matches!(
+        client.get_status_sync(),
+        Err(WarpError::CommandNotFound)
+    ));
+}
+
+#[test]
+fn test_truncate_output() {
+    assert_eq!(truncate_output("a\nb\nc", 3), "a\nb\nc");
+    assert_eq!(truncate_output("a\nb\nc", 10), "a\nb\nc");
+    assert_eq!(
+        truncate_output("a\nb\nc\nd", 2),
+        "a\nb\n... (2 more lines truncated)"
+    );
+    assert_eq!(truncate_output("a\nb", 0), "... (2 more lines truncated)");
+
+    let long = "line\n".repeat(DEFAULT_MAX_OUTPUT_LINES * 2);
+    assert_eq!(
+        truncate_output(&long, DEFAULT_MAX_OUTPUT_LINES)
+            .lines()
+            .count(),
+        DEFAULT_MAX_OUTPUT_LINES + 1
+    );
+}
+
+#[test]
+fn test_additional_modes() {
+    let client = WarpClient::new();
+
+    for (operation_mode, mode) in [
+        ("warp", WarpMode::Warp),
+        ("proxy", WarpMode::Proxy),
+        ("WarpProxy", WarpMode::Proxy),
+        ("tunnel_only", WarpMode::TunnelOnly),
+        ("TunnelOnly", WarpMode::TunnelOnly),
+        ("dns_only", WarpMode::DnsOnly),
+        ("DnsOnly", WarpMode::DnsOnly),
+    ] {
+        let output = format!(
+            r#"{{"settings": {{"operation_mode": "{}"}}}}"#,
+            operation_mode
+        );
+        let settings = client.parse_settings_output(&output).unwrap();
+        assert_eq!(settings.mode, mode, "operation_mode {:?}", operation_mode);
+    }
+
+    assert_eq!(WarpMode::Proxy.to_string(), "Proxy");
+    assert_eq!(WarpMode::TunnelOnly.to_string(), "Tunnel only");
+    assert_eq!(WarpMode::DnsOnly.to_string(), "DNS only");
+}
+
+#[test]
+fn test_unsupported_command_detection() {
+    assert!(is_unsupported_command(
+        "error: unrecognized subcommand 'warp-enabled'"
+    ));
+    assert!(is_unsupported_command(
+        "error: unexpected argument 'true' found"
+    ));
+    assert!(is_unsupported_command(
+        "error: Found argument 'gateway-enabled' which wasn't expected"
+    ));
+    assert!(!is_unsupported_command("Error: daemon not ready"));
+    assert!(!is_unsupported_command(""));
+
+    let client = WarpClient::builder()
+        .binary_path("/nonexistent/warp-tui-test/warp-cli")
+        .build();
+    assert!(matches!(
+        client.set_warp_enabled_sync(true),
+        Err(WarpError::CommandNotFound)
+    ));
+}
+
+#[test]
+fn test_warp_info_status_helpers() {
+    // (status, connected, transitioning, actionable)
+    let cases = [
+        (WarpStatus::Connected, true, false, true),
+        (WarpStatus::Disconnected, false, false, true),
+        (WarpStatus::Connecting, false, true, false),
+        (WarpStatus::Disconnecting, false, true, false),
+        (WarpStatus::Unknown, false, false, false),
+    ];
+
+    for (status, connected, transitioning, actionable) in cases {
+        let info = WarpInfo {
+            status: status.clone(),
+            ..Default::default()
+        };
+        assert_eq!(info.is_connected(), connected, "{:?}", status);
+        assert_eq!(info.is_transitioning(), transitioning, "{:?}", status);
+        assert_eq!(info.is_actionable(), actionable, "{:?}", status);
+    }
+}
+
+#[test]
+fn test_tunnel_protocol_conversion() {
+    for protocol in TunnelProtocol::ALL {
+        assert_eq!(
+            TunnelProtocol::from_cli_str(protocol.as_cli_str()),
+            Some(protocol)
+        );
+    }
+    assert_eq!(
+        TunnelProtocol::from_cli_str("wireguard"),
+        Some(TunnelProtocol::WireGuard)
+    );
+    assert_eq!(
+        TunnelProtocol::from_cli_str(" masque "),
+        Some(TunnelProtocol::Masque)
+    );
+    assert_eq!(TunnelProtocol::from_cli_str("openvpn"), None);
+    assert_eq!(TunnelProtocol::Masque.to_string(), "MASQUE");
+}
+
+#[test]
+fn test_set_tunnel_protocol_rejects_unknown_protocols() {
+    // A binary that doesn't exist proves validation happens before spawning
+    let client = WarpClient::builder()
+        .binary_path("/nonexistent/warp-cli")
+        .build();
+
+    match client.set_tunnel_protocol_sync("openvpn") {
+        Err(WarpError::CommandFailed { stderr: msg, .. }) => {
+            assert!(msg.contains("unknown tunnel protocol: openvpn"));
+            assert!(msg.contains("WireGuard, MASQUE"));
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_dns_stats_parsing() {
+    let client = WarpClient::new();
+
+    let output = r#"{"total_queries": 200, "cached": 150, "blocked": 12, "latency": 8.5}"#;
+    let stats = client.parse_dns_stats_json(output).unwrap();
+    assert_eq!(stats.total_queries, 200);
+    assert_eq!(stats.cached, 150);
+    assert_eq!(stats.blocked, 12);
+    assert_eq!(stats.latency, Some(8.5));
+    assert_eq!(stats.cache_hit_rate(), Some(0.75));
+
+    let output = "Total queries: 40\nCache hits: 10\nBlocked: 2\nAverage latency: 12.5 ms\n";
+    let stats = client.parse_dns_stats_output(output).unwrap();
+    assert_eq!(stats.total_queries, 40);
+    assert_eq!(stats.cached, 10);
+    assert_eq!(stats.blocked, 2);
+    assert_eq!(stats.latency, Some(12.5));
+
+    // No queries yet, so no hit rate
+    let stats = client.parse_dns_stats_json("{}").unwrap();
+    assert_eq!(stats.cache_hit_rate(), None);
+    assert_eq!(stats.latency, None);
+
+    assert!(matches!(
+        client.parse_dns_stats_output("Error: DNS stats are disabled"),
+        Err(WarpError::DnsStatsDisabled)
+    ));
+    assert!(matches!(
+        client.parse_dns_stats_json("DNS logging is not enabled"),
+        Err(WarpError::DnsStatsDisabled)
+    ));
+    // Only output that isn't JSON, or has no statistics, is taken for warp-cli's message
+    let stats = client
+        .parse_dns_stats_json(r#"{"total_queries": 3, "filtering": "disabled"}"#)
+        .unwrap();
+    assert_eq!(stats.total_queries, 3);
+    let stats = client
+        .parse_dns_stats_output("Total queries: 3\nFiltering: disabled\n")
+        .unwrap();
+    assert_eq!(stats.total_queries, 3);
+    assert!(matches!(
+        client.parse_dns_stats_output("garbage"),
+        Err(WarpError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_register_with_key_rejects_empty_key() {
+    // A binary that doesn't exist proves validation happens before spawning
+    let client = WarpClient::builder()
+        .binary_path("/nonexistent/warp-cli")
+        .build();
+
+    for key in ["", "   "] {
+        match client.register_with_key_sync(key) {
+            Err(WarpError::CommandFailed { stderr: msg, .. }) => {
+                assert!(msg.contains("license key must not be empty"))
+            }
+            other => panic!("expected CommandFailed, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_set_override_rejects_empty_policy() {
+    // A binary that doesn't exist proves validation happens before spawning
+    let client = WarpClient::builder()
+        .binary_path("/nonexistent/warp-cli")
+        .build();
+
+    for policy in ["", "  "] {
+        match client.set_override_sync(policy) {
+            Err(WarpError::CommandFailed { stderr: msg, .. }) => {
+                assert!(msg.contains("override policy must not be empty"))
+            }
+            other => panic!("expected CommandFailed, got {:?}", other),
+        }
+    }
+    assert!(matches!(
+        client.set_override_sync("local-network"),
+        Err(WarpError::CommandNotFound)
+    ));
+}
+
+#[test]
+fn test_override_state_parsing() {
+    let client = WarpClient::new();
+
+    let info = client.parse_status_text("Status update: Connected\nOverride: local-network\n");
+    assert_eq!(info.override_state.as_deref(), Some("local-network"));
+    let info = client.parse_status_text("Status update: Connected\n");
+    assert_eq!(info.override_state, None);
+
+    let info = client
+        .parse_status_json(r#"{"status":"Connected","override":"local-network"}"#)
+        .unwrap();
+    assert_eq!(info.override_state.as_deref(), Some("local-network"));
+    let info = client
+        .parse_status_json(r#"{"status":"Connected","override":""}"#)
+        .unwrap();
+    assert_eq!(info.override_state, None);
+}
+
+#[test]
+fn test_redact_args() {
+    assert_eq!(
+        redact_args(&["registration", "license", "abcd1234-efgh5678-ijkl9012"]),
+        ["registration", "license", "****-****-9012"]
+    );
+    assert_eq!(
+        redact_args(&[
+            "registration",
+            "token",
+            "com.cloudflare.warp://example/auth?token=secret"
+        ]),
+        ["registration", "token", "****-****-cret"]
+    );
+    assert_eq!(redact_args(&["--json", "status"]), ["--json", "status"]);
+}
+
+#[test]
+fn test_command_failed_exit_code() {
+    let error = WarpError::CommandFailed {
+        code: Some(2),
+        stderr: "unrecognized subcommand".to_string(),
+    };
+    assert_eq!(error.exit_code(), Some(2));
+    assert_eq!(
+        error.to_string(),
+        "Command execution failed (exit code 2): unrecognized subcommand"
+    );
+
+    let error = WarpError::command_failed("SSID must not be empty");
+    assert_eq!(error.exit_code(), None);
+    assert_eq!(
+        error.to_string(),
+        "Command execution failed: SSID must not be empty"
+    );
+    assert_eq!(WarpError::NotConnected.exit_code(), None);
+}
+
+#[test]
+fn test_refresh_coalescer() {
+    let mut coalescer = RefreshCoalescer::new();
+    let start = Instant::now();
+
+    assert!(coalescer.try_begin(start));
+    // In flight
+    assert!(!coalescer.try_begin(start));
+    coalescer.finish(start);
+
+    // Completed too recently
+    assert!(!coalescer.try_begin(start + REFRESH_COALESCE_WINDOW / 2));
+    assert!(coalescer.try_begin(start + REFRESH_COALESCE_WINDOW));
+    coalescer.finish(start + REFRESH_COALESCE_WINDOW);
+
+    // A forced refresh runs regardless and blocks others until it's done
+    coalescer.begin_forced();
+    assert!(!coalescer.try_begin(start + REFRESH_COALESCE_WINDOW * 4));
+    coalescer.finish(start + REFRESH_COALESCE_WINDOW * 4);
+    assert!(coalescer.try_begin(start + REFRESH_COALESCE_WINDOW * 5));
+}
+
+#[test]
+fn test_endpoint_and_tunnel_ip_parsing() {
+    let client = WarpClient::new();
+
+    let ipv4_only = "Status update: Connected\n\
+                     Endpoint: 162.159.193.1:2408\n\
+                     Tunnel IP: 172.16.0.2\n";
+    let info = client.parse_status_text(ipv4_only);
+    assert_eq!(info.status, WarpStatus::Connected);
+    assert_eq!(info.endpoint.as_deref(), Some("162.159.193.1:2408"));
+    assert_eq!(info.tunnel_ip.as_deref(), Some("172.16.0.2"));
+
+    let dual_stack = "Status update: Connected\n\
+                      Endpoint IPv4: 162.159.193.1:2408\n\
+                      Endpoint IPv6: [2606:4700:d0::a29f:c001]:2408\n\
+                      Tunnel IPv4: 172.16.0.2\n\
+                      Tunnel IPv6: 2606:4700:110:8a36::2\n";
+    let info = client.parse_status_text(dual_stack);
+    assert_eq!(
+        info.endpoint.as_deref(),
+        Some("162.159.193.1:2408, [2606:4700:d0::a29f:c001]:2408")
+    );
+    assert_eq!(
+        info.tunnel_ip.as_deref(),
+        Some("172.16.0.2, 2606:4700:110:8a36::2")
+    );
+
+    // Older output without the details
+    let info = client.parse_status_text("Status update: Disconnected\n");
+    assert_eq!(info.endpoint, None);
+    assert_eq!(info.tunnel_ip, None);
+
+    let json = r#"{"status":"Connected","endpoint":"162.159.193.1:2408","tunnel_ip":"172.16.0.2"}"#;
+    let info = client.parse_status_json(json).unwrap();
+    assert_eq!(info.endpoint.as_deref(), Some("162.159.193.1:2408"));
+    assert_eq!(info.tunnel_ip.as_deref(), Some("172.16.0.2"));
+}
+
+#[test]
+fn test_warp_info_diff() {
+    let before = WarpInfo {
+        status: WarpStatus::Connected,
+        mode: Some(WarpMode::WarpDoH),
+        account_type: Some("Free".to_string()),
+        warp_enabled: true,
+        connected_networks: vec!["Home".to_string()],
+        ..Default::default()
+    };
+
+    // No change
+    assert!(before.diff(&before.clone()).is_empty());
+    // Fields that follow the status aren't compared
+    let after = WarpInfo {
+        reason: Some("Settings Changed".to_string()),
+        endpoint: Some("162.159.193.1:2408".to_string()),
+        ..before.clone()
+    };
+    assert!(before.diff(&after).is_empty());
+
+    // A single field
+    let after = WarpInfo {
+        mode: Some(WarpMode::DoH),
+        ..before.clone()
+    };
+    let changes = before.diff(&after);
+    assert_eq!(
+        changes,
+        vec![WarpInfoChange::ModeChanged(
+            Some(WarpMode::WarpDoH),
+            Some(WarpMode::DoH)
+        )]
+    );
+    assert_eq!(changes[0].to_string(), "Mode: Warp+DoH -> DoH");
+
+    // Several fields, in a fixed order
+    let after = WarpInfo {
+        status: WarpStatus::Disconnected,
+        mode: None,
+        warp_enabled: false,
+        gateway_enabled: true,
+        connected_networks: Vec::new(),
+        override_state: Some("local-network".to_string()),
+        ..before.clone()
+    };
+    let changes = before.diff(&after);
+    assert_eq!(
+        changes,
+        vec![
+            WarpInfoChange::StatusChanged(WarpStatus::Connected, WarpStatus::Disconnected),
+            WarpInfoChange::ModeChanged(Some(WarpMode::WarpDoH), None),
+            WarpInfoChange::WarpToggled(false),
+            WarpInfoChange::GatewayToggled(true),
+            WarpInfoChange::NetworksChanged(Vec::new()),
+            WarpInfoChange::OverrideChanged(Some("local-network".to_string())),
+        ]
+    );
+    let descriptions: Vec<String> = changes.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        descriptions,
+        vec![
+            "Status: Connected -> Disconnected",
+            "Mode: Warp+DoH -> N/A",
+            "WARP disabled",
+            "Gateway enabled",
+            "Connected networks: None",
+            "Override: local-network",
+        ]
+    );
+
+    // The diff is directional
+    assert_eq!(
+        after.diff(&before)[0],
+        WarpInfoChange::StatusChanged(WarpStatus::Disconnected, WarpStatus::Connected)
+    );
+}
+
+#[test]
+fn test_values_containing_colons() {
+    let client = WarpClient::new();
+
+    let output = "Status update: Disconnected\n\
+                  Reason: Captive portal detected at http://neverssl.com:80/login\n\
+                  Network: fe80::1c2b:3aff:fe4d:5e6f\n\
+                  Override: local-network 2001:db8::/32\n";
+    let info = client.parse_status_text(output);
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(
+        info.reason.as_deref(),
+        Some("Captive portal detected at http://neverssl.com:80/login")
+    );
+    assert_eq!(info.connected_networks, vec!["fe80::1c2b:3aff:fe4d:5e6f"]);
+    assert_eq!(
+        info.override_state.as_deref(),
+        Some("local-network 2001:db8::/32")
+    );
+}
+
+#[test]
+fn test_parse_mode_from_text() {
+    let client = WarpClient::new();
+
+    assert_eq!(
+        client.parse_mode_from_text("Mode: warp+doh\n"),
+        Some(WarpMode::WarpDoH)
+    );
+
+    // Newer versions note where each setting comes from and use long mode names
+    let output = "Merged configuration:\n\
+                  (default) Always On: true\n\
+                  (default) Families mode: off\n\
+                  (user set) Mode: WarpWithDnsOverHttps\n\
+                  (default) WARP tunnel protocol: WireGuard\n";
+    assert_eq!(client.parse_mode_from_text(output), Some(WarpMode::WarpDoH));
+    assert_eq!(
+        client.parse_mode_from_text("(default)   Mode: DnsOverTls"),
+        Some(WarpMode::DoT)
+    );
+    assert_eq!(
+        client.parse_mode_from_text("  Mode: TunnelOnly  "),
+        Some(WarpMode::TunnelOnly)
+    );
+
+    // An unrecognized mode is still a mode
+    assert_eq!(
+        client.parse_mode_from_text("Mode: something-new"),
+        Some(WarpMode::Unknown)
+    );
+    assert_eq!(client.parse_mode_from_text("Families mode: off\n"), None);
+    assert_eq!(client.parse_mode_from_text(""), None);
+}
+
+#[test]
+fn test_daemon_down_detection() {
+    assert!(is_daemon_down_error(
+        "Error: Unable to connect to the CloudflareWARP daemon. Maybe the daemon is not running?"
+    ));
+    assert!(is_daemon_down_error("The daemon is not running"));
+
+    assert!(!is_daemon_down_error("Error: Missing registration"));
+    assert!(!is_daemon_down_error("Error: daemon not ready"));
+}
+
+#[test]
+fn test_registration_with_invalid_utf8() {
+    let client = WarpClient::new();
+
+    let valid = "Account type: Team\nDevice ID: 0123-abcd\nLicense key: ABCD-1234-EFGH\n";
+    let info = client.parse_registration_output(valid).unwrap();
+    assert_eq!(info.device_id.as_deref(), Some("0123-abcd"));
+    assert_eq!(info.license_key.as_deref(), Some("ABCD-1234-EFGH"));
+
+    let bytes = b"Device ID: 0123-\xff\xfeabcd\nLicense key: ABCD-1234-EFGH\n";
+    let output = String::from_utf8_lossy(bytes);
+    match client.parse_registration_output(&output) {
+        Err(WarpError::ParseError(msg)) => assert!(msg.contains("Device ID"), "{}", msg),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    // The corrupted key isn't echoed in the error
+    let bytes = b"Device ID: 0123-abcd\nLicense key: ABCD-\xc3(-EFGH\n";
+    let output = String::from_utf8_lossy(bytes);
+    let err = client.parse_registration_output(&output).unwrap_err();
+    assert!(matches!(err, WarpError::ParseError(_)));
+    assert!(!err.to_string().contains("ABCD"), "{}", err);
+
+    // Other fields are only displayed, so a replaced byte there is tolerated
+    let bytes = b"Organization: Caf\xe9\nDevice ID: 0123-abcd\n";
+    let output = String::from_utf8_lossy(bytes);
+    let info = client.parse_registration_output(&output).unwrap();
+    assert_eq!(info.organization.as_deref(), Some("Caf\u{FFFD}"));
+}
+
+#[test]
+fn test_warp_info_envelope_serialization() {
+    let info = WarpInfo {
+        status: WarpStatus::Connected,
+        mode: Some(WarpMode::WarpDoH),
+        ..WarpInfo::default()
+    };
+
+    let json = serde_json::to_value(WarpInfoEnvelope::from(info)).unwrap();
+    assert_eq!(json["version"], WARP_INFO_SCHEMA_VERSION);
+    assert_eq!(json["version"], 2);
+    assert_eq!(json["info"]["status"], "Connected");
+    assert_eq!(json["info"]["mode"], "warp+doh");
+    assert_eq!(json.as_object().unwrap().len(), 2);
+
+    let envelope: WarpInfoEnvelope = serde_json::from_value(json).unwrap();
+    assert_eq!(envelope.version, WARP_INFO_SCHEMA_VERSION);
+    assert_eq!(envelope.info.status, WarpStatus::Connected);
+    assert_eq!(envelope.info.mode, Some(WarpMode::WarpDoH));
+}
+
+#[test]
+fn test_trusted_and_untrusted_networks() {
+    let client = WarpClient::new();
+    let network = |name: &str, trusted, network_type| NetworkInfo {
+        name: name.to_string(),
+        trusted,
+        network_type,
+    };
+
+    let output = "Status update: Connected\n\
+        Network: eth0\n\
+        SSID: \"Coffee Shop\"\n\
+        Network: HomeWifi\n\
+        Trusted networks:\n\
+        - HomeWifi\n\
+        - Office\n";
+    let info = client.parse_status_text(output);
+    assert_eq!(
+        info.networks,
+        vec![
+            network("eth0", false, NetworkType::Ethernet),
+            network("Coffee Shop", false, NetworkType::Wifi),
+            network("HomeWifi", true, NetworkType::Wifi),
+            network("Office", true, NetworkType::Unknown),
+        ]
+    );
+    // The plain names are unchanged
+    assert_eq!(
+        info.connected_networks,
+        ["eth0", "Coffee Shop", "HomeWifi", "Office"]
+    );
+
+    // Inline trusted lists are trusted too
+    let info = client.parse_status_text("Network: wlan0\nTrusted networks: wlan0, Lab");
+    assert_eq!(
+        info.networks,
+        vec![
+            network("wlan0", true, NetworkType::Wifi),
+            network("Lab", true, NetworkType::Unknown),
+        ]
+    );
+
+    let info = client.parse_status_text("Status update: Connected");
+    assert!(info.networks.is_empty());
+}
+
+#[test]
+fn test_network_type_from_interface_name() {
+    assert_eq!(NetworkType::from_interface_name("wlan0"), NetworkType::Wifi);
+    assert_eq!(
+        NetworkType::from_interface_name("wlp2s0"),
+        NetworkType::Wifi
+    );
+    assert_eq!(NetworkType::from_interface_name("Wi-Fi"), NetworkType::Wifi);
+    assert_eq!(
+        NetworkType::from_interface_name("eth0"),
+        NetworkType::Ethernet
+    );
+    assert_eq!(
+        NetworkType::from_interface_name("enp3s0"),
+        NetworkType::Ethernet
+    );
+    assert_eq!(
+        NetworkType::from_interface_name("Ethernet 2"),
+        NetworkType::Ethernet
+    );
+    assert_eq!(
+        NetworkType::from_interface_name("HomeWifi"),
+        NetworkType::Wifi
+    );
+    assert_eq!(
+        NetworkType::from_interface_name("Office"),
+        NetworkType::Unknown
+    );
+}
+
+#[test]
+fn test_run_cancellable_kills_cancelled_command() {
+    if Command::new("sleep").arg("0").status().is_err() {
+        println!("Skipping test: sleep not available");
+        return;
+    }
+
+    let cancel = CancelToken::new();
+    let canceller = cancel.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        canceller.cancel();
+    });
+
+    let mut command = Command::new("sleep");
+    command.arg("5");
+    let started = Instant::now();
+    let result = run_cancellable(command, Duration::from_secs(10), Some(&cancel));
+    assert!(matches!(result, Err(WarpError::Cancelled)));
+    assert!(started.elapsed() < Duration::from_secs(2));
+
+    // Once cancelled, nothing else is started
+    assert!(cancel.is_cancelled());
+    let command = Command::new("warp-cli-does-not-exist");
+    assert!(matches!(
+        run_cancellable(command, Duration::from_secs(1), Some(&cancel)),
+        Err(WarpError::Cancelled)
+    ));
+}
+
+#[test]
+fn test_tunnel_stats_parsing() {
+    let client = WarpClient::new();
+    let now = UNIX_EPOCH + Duration::from_secs(1_700_000_090);
+
+    let output = r#"{
+        "rx_bytes": 1048576,
+        "tx_bytes": 262144,
+        "rx_packets": 900,
+        "tx_packets": 450,
+        "last_handshake": 1700000000
+    }"#;
+    let stats = client.parse_tunnel_stats_json(output, now).unwrap();
+    assert_eq!(stats.rx_bytes, 1_048_576);
+    assert_eq!(stats.tx_bytes, 262_144);
+    assert_eq!(stats.rx_packets, 900);
+    assert_eq!(stats.tx_packets, 450);
+    assert_eq!(stats.last_handshake, Some(1_700_000_000));
+    assert_eq!(stats.since_last_handshake, Some(Duration::from_secs(90)));
+
+    // A tunnel that never connected has no handshake, missing or reported as 0
+    let output = r#"{"rx_bytes": 0, "tx_bytes": 0, "rx_packets": 0, "tx_packets": 0}"#;
+    let stats = client.parse_tunnel_stats_json(output, now).unwrap();
+    assert_eq!(stats.last_handshake, None);
+    assert_eq!(stats.since_last_handshake, None);
+    let output = r#"{"rx_bytes": 0, "last_handshake_time": 0}"#;
+    let stats = client.parse_tunnel_stats_json(output, now).unwrap();
+    assert_eq!(stats.since_last_handshake, None);
+
+    // Clock skew doesn't make the handshake happen in the future
+    let output = r#"{"last_handshake": 1700000100}"#;
+    let stats = client.parse_tunnel_stats_json(output, now).unwrap();
+    assert_eq!(stats.since_last_handshake, Some(Duration::ZERO));
+
+    assert!(matches!(
+        client.parse_tunnel_stats_json("not json", SystemTime::now()),
+        Err(WarpError::ParseError(_))
+    ));
+    assert!(matches!(
+        client.parse_tunnel_stats_json("Error: Not connected", SystemTime::now()),
+        Err(WarpError::NotConnected)
+    ));
+    let output = r#"{"rx_bytes": 7, "state": "not connected before"}"#;
+    let stats = client.parse_tunnel_stats_json(output, now).unwrap();
+    assert_eq!(stats.rx_bytes, 7);
+}
+
+#[test]
+fn test_crlf_output_parsing() {
+    let client = WarpClient::new();
+
+    let info = client.parse_status_text(
+        "Status update: Connected\r\nReason: Manual\r\nAccount type: Free\r\nNetwork: healthy\r\n",
+    );
+    assert_eq!(info.status, WarpStatus::Connected);
+    assert_eq!(info.reason.as_deref(), Some("Manual"));
+    assert_eq!(info.account_type.as_deref(), Some("Free"));
+
+    // A trailing '\r' without '\n' isn't split off by lines()
+    let registration = client
+        .parse_registration_output("Account type: Free\r\nDevice ID: 0123-abcd\r")
+        .unwrap();
+    assert_eq!(registration.device_id.as_deref(), Some("0123-abcd"));
+
+    let stats = client
+        .parse_dns_stats_output("Total queries: 120\r\nCached: 90\r\nLatency: 4.2 ms\r\n")
+        .unwrap();
+    assert_eq!(stats.total_queries, 120);
+    assert_eq!(stats.latency, Some(4.2));
+}
+
+#[cfg(windows)]
+#[test]
+fn test_windows_binary_path() {
+    use std::ffi::OsStr;
+    use std::path::PathBuf;
+    use warp_tui::warp::client::windows_install_path;
+
+    assert_eq!(
+        windows_install_path(Some(OsStr::new(r"D:\Apps"))),
+        PathBuf::from(r"D:\Apps\Cloudflare\Cloudflare WARP\warp-cli.exe")
+    );
+    let default = PathBuf::from(r"C:\Program Files\Cloudflare\Cloudflare WARP\warp-cli.exe");
+    assert_eq!(windows_install_path(None), default);
+    assert_eq!(windows_install_path(Some(OsStr::new(""))), default);
+
+    // The installed binary is used when it's there, otherwise warp-cli from PATH
+    let installed = windows_install_path(std::env::var_os("ProgramFiles").as_deref());
+    let expected = if installed.is_file() {
+        installed
+    } else {
+        PathBuf::from("warp-cli")
+    };
+    if std::env::var_os("WARP_CLI_PATH").is_none() {
+        assert_eq!(WarpClient::new().binary_path(), expected.as_path());
+    }
+}
+
+#[test]
+fn test_mode_from_json_layouts() {
+    let client = WarpClient::new();
+
+    // Nested under "settings"
+    let nested = r#"{"settings":{"operation_mode":"WarpWithDnsOverHttps","always_on":false}}"#;
+    assert_eq!(
+        client.parse_mode_from_json(nested).unwrap(),
+        WarpMode::WarpDoH
+    );
+
+    // Flat, with the value wrapped in an object alongside where it came from
+    let flat = r#"{"mode":{"value":"DnsOverHttps","source":"default"},"always_on":true}"#;
+    assert_eq!(client.parse_mode_from_json(flat).unwrap(), WarpMode::DoH);
+    let flat = r#"{"operation_mode":"warp"}"#;
+    assert_eq!(client.parse_mode_from_json(flat).unwrap(), WarpMode::Warp);
+
+    // Valid JSON without a mode anywhere is an unknown mode, not a parse error
+    let absent = r#"{"settings":{"always_on":false}}"#;
+    assert_eq!(
+        client.parse_mode_from_json(absent).unwrap(),
+        WarpMode::Unknown
+    );
+
+    assert!(matches!(
+        client.parse_mode_from_json("{\"settings\":"),
+        Err(WarpError::ParseError(_))
+    ));
+}
+
+#[test]
+fn test_status_transitions() {
+    use WarpStatus::*;
+
+    let legal = [
+        (Disconnected, Connecting),
+        (Connecting, Connected),
+        (Connecting, Disconnected),
+        (Connecting, Disconnecting),
+        (Connected, Disconnecting),
+        (Connected, Disconnected),
+        (Connected, Connecting),
+        (Disconnecting, Disconnected),
+        (Connected, Connected),
+        (Disconnected, Disconnected),
+        (Unknown, Connected),
+        (Disconnecting, Unknown),
+    ];
+    for (from, to) in legal {
+        assert!(from.can_transition_to(&to), "{} -> {}", from, to);
+    }
+
+    let illegal = [
+        (Disconnected, Connected),
+        (Disconnected, Disconnecting),
+        (Disconnecting, Connected),
+        (Disconnecting, Connecting),
+    ];
+    for (from, to) in illegal {
+        assert!(!from.can_transition_to(&to), "{} -> {}", from, to);
+    }
+}
+
+#[test]
+fn test_warp_mode_serde_round_trip() {
+    for (mode, cli) in [
+        (WarpMode::DoH, "doh"),
+        (WarpMode::DoT, "dot"),
+        (WarpMode::WarpDoH, "warp+doh"),
+        (WarpMode::WarpDoT, "warp+dot"),
+        (WarpMode::Warp, "warp"),
+        (WarpMode::Proxy, "proxy"),
+        (WarpMode::TunnelOnly, "tunnel_only"),
+        (WarpMode::DnsOnly, "dns_only"),
+        (WarpMode::Unknown, "unknown"),
+    ] {
+        let json = serde_json::to_string(&mode).unwrap();
+        assert_eq!(json, format!("\"{}\"", cli));
+        assert_eq!(serde_json::from_str::<WarpMode>(&json).unwrap(), mode);
+    }
+
+    // Variant names written by older versions still parse
+    assert_eq!(
+        serde_json::from_str::<WarpMode>("\"WarpDoH\"").unwrap(),
+        WarpMode::WarpDoH
+    );
+    assert_eq!(
+        serde_json::from_str::<WarpMode>("\"TunnelOnly\"").unwrap(),
+        WarpMode::TunnelOnly
+    );
+    assert_eq!(
+        serde_json::from_str::<WarpMode>("\"something-new\"").unwrap(),
+        WarpMode::Unknown
+    );
+}
+
+#[test]
+fn test_raw_status_off_by_default() {
+    assert_eq!(WarpInfo::default().raw, None);
+    assert!(!WarpClient::new().captures_raw_status());
+
+    // Only the parsed fields are part of the JSON output
+    let info = WarpInfo {
+        raw: Some("Status update: Connected".to_string()),
+        ..WarpInfo::default()
+    };
+    let json = serde_json::to_value(&info).unwrap();
+    assert!(json.get("raw").is_none());
+}
+
+#[test]
+fn test_settings_batch_changes() {
+    let batch = SettingsBatch {
+        tunnel_protocol: Some(TunnelProtocol::Masque),
+        families_mode: Some(FamiliesMode::Full),
+        mode: Some(WarpMode::DoH),
+    };
+    assert_eq!(
+        batch.changes(),
+        [
+            SettingChange::Mode(WarpMode::DoH),
+            SettingChange::FamiliesMode(FamiliesMode::Full),
+            SettingChange::TunnelProtocol(TunnelProtocol::Masque),
+        ]
+    );
+    assert!(SettingsBatch::default().changes().is_empty());
+    assert_eq!(
+        SettingChange::TunnelProtocol(TunnelProtocol::Masque).to_string(),
+        "set tunnel protocol MASQUE"
+    );
+
+    let previous = WarpSettingsInfo {
+        mode: WarpMode::Warp,
+        families_mode: Some("off".to_string()),
+        ..WarpSettingsInfo::default()
+    };
+    assert_eq!(
+        SettingChange::Mode(WarpMode::DoH).previous(&previous),
+        Some(SettingChange::Mode(WarpMode::Warp))
+    );
+    assert_eq!(
+        SettingChange::FamiliesMode(FamiliesMode::Full).previous(&previous),
+        Some(SettingChange::FamiliesMode(FamiliesMode::Off))
+    );
+    assert_eq!(
+        SettingChange::TunnelProtocol(TunnelProtocol::Masque).previous(&previous),
+        None
+    );
+
+    let error = WarpError::BatchFailed {
+        step: "set mode dot".to_string(),
+        source: Box::new(WarpError::command_failed("boom")),
+        rolled_back: true,
+    };
+    assert_eq!(
+        error.to_string(),
+        "Failed to set mode dot: Command execution failed: boom; the previous settings were restored"
+    );
+}
+
+#[test]
+fn test_warp_info_summary() {
+    let connected = WarpInfo {
+        status: WarpStatus::Connected,
+        mode: Some(WarpMode::WarpDoH),
+        account_type: Some("Free".to_string()),
+        warp_enabled: true,
+        connected_networks: vec!["Home".to_string()],
+        ..WarpInfo::default()
+    };
+    assert_eq!(connected.summary(), "Connected · Warp+DoH · Free");
+
+    let disconnected = WarpInfo {
+        status: WarpStatus::Disconnected,
+        reason: Some("Manual Disconnection".to_string()),
+        account_type: Some(" ".to_string()),
+        ..WarpInfo::default()
+    };
+    assert_eq!(disconnected.summary(), "Disconnected");
+
+    let unknown = WarpInfo {
+        mode: Some(WarpMode::Unknown),
+        ..WarpInfo::default()
+    };
+    assert_eq!(unknown.summary(), "Unknown");
+    assert_eq!(WarpInfo::default().summary(), "Unknown");
+}
+
+#[test]
+fn test_current_ssid_extraction() {
+    let client = WarpClient::new();
+    let ssid = |output: &str| client.current_ssid(&client.parse_status_text(output));
+
+    assert_eq!(
+        ssid("Status update: Connected\nSSID: HomeWifi\n").unwrap(),
+        "HomeWifi"
+    );
+    // The Wi-Fi network wins over a wired one listed first
+    assert_eq!(
+        ssid("Status update: Connected\nNetwork: eth0\nSSID: Cafe Guest\n").unwrap(),
+        "Cafe Guest"
+    );
+    // A network of unknown type may well be an SSID
+    assert_eq!(ssid("Network: Office\n").unwrap(), "Office");
+
+    let error = ssid("Status update: Connected\nNetwork: enp3s0\n").unwrap_err();
+    assert!(
+        error.to_string().contains("wired network enp3s0"),
+        "{}",
+        error
+    );
+    let error = ssid("Status update: Disconnected\n").unwrap_err();
+    assert!(
+        error.to_string().contains("no connected Wi-Fi network"),
+        "{}",
+        error
+    );
+}
+
+/// Fields indented beneath a header are attributed to that header's section
+#[test]
+fn test_indented_status_sections() {
+    let client = WarpClient::new();
+
+    let output = "Status update: Connected\n\
+        Account type: Team\n    \
+            Status: Inactive\n    \
+            Device ID: 0123-abcd\n    \
+            Warp enabled: true\n\
+        Gateway enabled: true\n\
+        Trusted networks:\n    \
+            HomeWifi\n    \
+            - Office\n\
+        Endpoint: 162.159.193.1:2408\n";
+    let info = client.parse_status_text(output);
+    // The account's "Status:" doesn't replace the connection status
+    assert_eq!(info.status, WarpStatus::Connected);
+    assert_eq!(info.account_type.as_deref(), Some("Team"));
+    assert!(info.warp_enabled);
+    assert!(info.gateway_enabled);
+    assert_eq!(
+        info.connected_networks,
+        vec!["HomeWifi".to_string(), "Office".to_string()]
+    );
+    assert_eq!(info.endpoint.as_deref(), Some("162.159.193.1:2408"));
+
+    // An "Account:" header with the type beneath it, all indented by the same amount
+    let output = "  Status update: Disconnected\n  \
+        Account:\n      \
+            Type: Free\n      \
+            Status: Active\n  \
+        Reason: Manual Disconnection\n";
+    let info = client.parse_status_text(output);
+    assert_eq!(info.status, WarpStatus::Disconnected);
+    assert_eq!(info.account_type.as_deref(), Some("Free"));
+    assert_eq!(info.reason.as_deref(), Some("Manual Disconnection"));
+    assert!(!info.warp_enabled);
+}